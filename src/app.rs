@@ -1,52 +1,129 @@
 use leptos::*;
 use leptos_meta::*;
-use crate::models::{Company, FundingRound, DealType, ExitType};
-use crate::utils::{simulate_funding_round, generate_exit_scenarios, calculate_cap_table_summary};
-use crate::components::{FounderSetup, FundingRounds, CapTable, ExitScenarios, OwnershipChart};
+use chrono::NaiveDate;
+use crate::models::{
+    AntiDilution, Company, FundingRound, DealType, InvestorAllocation, LiquidationPreference, Participation,
+    RoundInput, Seniority,
+};
+use crate::money::{dec, Decimal};
+use crate::document::ExitSettings;
+use crate::share_link::decode_share_link;
+use crate::storage::{
+    clear_location_fragment, load_current_model, location_fragment, save_as_scenario, save_current_model,
+};
+use crate::utils::{
+    calculate_cap_table_summary, default_exit_valuations, generate_exit_scenarios, resolve_round_terms,
+};
+use crate::components::{
+    FounderSetup, FundingRounds, CapTable, ExitScenarios, OwnershipChart, OptionGrants, CompanyHistory,
+    ScenarioLibrary,
+};
 
 #[component]
 pub fn App() -> impl IntoView {
-    let (company, set_company) = create_signal(Company::default());
+    // A shared link opens its scenario in place of the last visit's, which is
+    // first kept in the scenario library; if it cannot be kept, the link is
+    // not opened. The fragment is then dropped, so a reload keeps later edits.
+    let (link_error, set_link_error) = create_signal(None::<String>);
+    let (link_notice, set_link_notice) = create_signal(None::<String>);
+    let shared = location_fragment().map(|fragment| {
+        let _ = clear_location_fragment();
+        decode_share_link(&fragment)
+    });
+    let saved = match shared {
+        Some(Ok(document)) => match load_current_model() {
+            None => Some(document),
+            Some(current) => {
+                let backup_name = format!("{} (before shared link)", current.company.name);
+                match save_as_scenario(&backup_name, &current.company) {
+                    Ok(name) => {
+                        set_link_notice.set(Some(format!(
+                            "Opened the shared scenario. The model you had open is saved in Scenarios as \"{}\".",
+                            name
+                        )));
+                        Some(document)
+                    }
+                    Err(message) => {
+                        set_link_error.set(Some(format!(
+                            "The shared scenario was not opened, as the model you had open could not be saved first: {}",
+                            message
+                        )));
+                        Some(current)
+                    }
+                }
+            }
+        },
+        Some(Err(message)) => {
+            set_link_error.set(Some(message));
+            load_current_model()
+        }
+        None => load_current_model(),
+    };
+    let today = chrono::Local::now().date_naive();
+    let starting_company = saved.as_ref().map(|d| d.company.clone()).unwrap_or_else(|| {
+        let mut company = Company::default();
+        let _ = company.set_founding_date(today);
+        company
+    });
+    let (company, set_company) = create_signal(starting_company);
+    let saved_exit = saved.map(|d| d.exit).unwrap_or_else(|| ExitSettings::for_company(&company.get_untracked()));
     
     // Track current funding round being added
-    let (current_round, set_current_round) = create_signal(FundingRound {
+    let mut seed_round = FundingRound {
         name: "Seed Round".to_string(),
-        amount: 1_000_000.0,
-        valuation: 5_000_000.0,
-        equity_sold: 20.0,
-        deal_type: DealType::Equity,
-        investors: vec!["VC Fund".to_string()],
-        esop_allocation: 10.0,
-        anti_dilution: false,
+        close_date: today,
+        amount: dec!(1_000_000),
+        valuation: dec!(5_000_000),
+        equity_sold: Decimal::ZERO,
+        price_per_share: Decimal::ZERO,
+        input: RoundInput::AmountAndPreMoney,
+        deal_type: DealType::PreferredStock,
+        investors: vec![InvestorAllocation {
+            investor: "VC Fund".to_string(),
+            amount: dec!(1_000_000),
+        }],
+        esop_allocation: dec!(10),
+        esop_target: None,
+        anti_dilution: AntiDilution::None,
+        liquidation_preference: Some(LiquidationPreference {
+            multiple: Decimal::ONE,
+            participation: Participation::NonParticipating,
+            seniority: Seniority::Senior,
+        }),
+        pro_rata_rights: true,
+        pro_rata: vec![],
+    };
+    let _ = resolve_round_terms(&mut seed_round, company.get().total_shares);
+    let (current_round, set_current_round) = create_signal(seed_round);
+
+    // Exit assumptions shared by every scenario
+    let (exit_type, set_exit_type) = create_signal(saved_exit.exit_type);
+    let (exit_date, set_exit_date) = create_signal(saved_exit.exit_date);
+
+    // Autosave; without storage the app still works, it just forgets on reload
+    create_effect(move |_| {
+        let exit = ExitSettings { exit_type: exit_type.get(), exit_date: exit_date.get() };
+        let _ = save_current_model(&company.get(), &exit);
     });
 
-    // Add funding round
-    let add_funding_round = create_action(move |round: &FundingRound| {
-        let mut new_company = company.get();
-        new_company.funding_rounds.push(round.clone());
-        
-        // Simulate the round and update company state
-        let snapshot = simulate_funding_round(&mut new_company, round);
-        set_company.set(new_company);
+    // Generate exit scenarios. Derived rather than memoized, as a memo needs
+    // to compare the old value with the new and scenarios have no equality.
+    let exit_scenarios = Signal::derive(move || {
+        generate_exit_scenarios(&company.get(), default_exit_valuations(), exit_type.get(), exit_date.get())
     });
 
-    // Generate exit scenarios
-    let exit_scenarios = create_memo(move |_| {
-        let valuations = vec![
-            10_000_000.0,   // $10M
-            25_000_000.0,   // $25M
-            50_000_000.0,   // $50M
-            100_000_000.0,  // $100M
-            250_000_000.0,  // $250M
-            500_000_000.0,  // $500M
-            1_000_000_000.0, // $1B
-        ];
-        generate_exit_scenarios(&company.get(), valuations)
+    // The cap table as it stood on a chosen date, or as it stands now
+    let (as_of, set_as_of) = create_signal(None::<NaiveDate>);
+    let cap_table_company = Signal::derive(move || {
+        match as_of.get() {
+            Some(date) => company.get().as_of(date).unwrap_or_default(),
+            None => company.get(),
+        }
     });
 
     // Cap table summary
     let cap_table = create_memo(move |_| {
-        calculate_cap_table_summary(&company.get())
+        calculate_cap_table_summary(&cap_table_company.get())
     });
 
     view! {
@@ -82,6 +159,23 @@ pub fn App() -> impl IntoView {
 
             // Main Content
             <main class="max-w-7xl mx-auto px-4 sm:px-6 lg:px-8 py-8">
+                {move || link_notice.get().map(|message| view! {
+                    <div class="mb-8 p-4 bg-blue-50 border border-blue-200 rounded-lg flex justify-between items-center">
+                        <span class="text-blue-800">{message}</span>
+                        <button class="text-sm text-blue-600 hover:text-blue-800" on:click=move |_| set_link_notice.set(None)>
+                            Dismiss
+                        </button>
+                    </div>
+                })}
+                {move || link_error.get().map(|message| view! {
+                    <div class="mb-8 p-4 bg-red-50 border border-red-200 rounded-lg flex justify-between items-center">
+                        <span class="text-red-800">{message}</span>
+                        <button class="text-sm text-red-600 hover:text-red-800" on:click=move |_| set_link_error.set(None)>
+                            Dismiss
+                        </button>
+                    </div>
+                })}
+
                 <div class="mb-8">
                     <div class="bg-blue-50 border border-blue-200 rounded-lg p-6">
                         <h2 class="text-xl font-semibold text-blue-800 mb-2">
@@ -98,23 +192,43 @@ pub fn App() -> impl IntoView {
                 <div class="grid grid-cols-1 lg:grid-cols-3 gap-8">
                     // Left Column - Setup & Inputs
                     <div class="lg:col-span-1 space-y-6">
+                        <ScenarioLibrary
+                            company=company
+                            set_company=set_company
+                            exit_type=exit_type
+                            set_exit_type=set_exit_type
+                            exit_date=exit_date
+                            set_exit_date=set_exit_date
+                        />
+
                         <FounderSetup company=company set_company=set_company />
                         
                         <FundingRounds 
                             current_round=current_round 
                             set_current_round=set_current_round 
-                            add_round=add_funding_round 
                             company=company 
+                            set_company=set_company
                         />
+
+                        <OptionGrants company=company set_company=set_company />
+
+                        <CompanyHistory company=company set_company=set_company />
                     </div>
 
                     // Right Column - Results & Visualizations
                     <div class="lg:col-span-2 space-y-6">
-                        <CapTable cap_table=cap_table company=company />
+                        <CapTable cap_table=cap_table company=cap_table_company as_of=as_of set_as_of=set_as_of />
                         
                         <OwnershipChart company=company />
                         
-                        <ExitScenarios scenarios=exit_scenarios company=company />
+                        <ExitScenarios
+                            scenarios=exit_scenarios
+                            company=company
+                            exit_type=exit_type
+                            set_exit_type=set_exit_type
+                            exit_date=exit_date
+                            set_exit_date=set_exit_date
+                        />
                     </div>
                 </div>
 
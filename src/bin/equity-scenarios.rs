@@ -0,0 +1,259 @@
+use clap::{Parser, ValueEnum};
+use startup_equity_scenario_builder::csv::{exit_distributions_csv, round_cap_tables_csv};
+use startup_equity_scenario_builder::money::Decimal;
+use startup_equity_scenario_builder::scenario_file::{run_scenario, ScenarioFile, ScenarioReport};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+// Runs a scenario file through the same engine as the app, so scenarios can
+// live in version control and their results be diffed in review.
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Replay a scenario's funding rounds and price its exits"
+)]
+struct Args {
+    #[arg(help = "Scenario file, read as TOML if it ends in .toml and as JSON otherwise")]
+    file: PathBuf,
+    #[arg(long, value_enum, default_value_t = Format::Table)]
+    format: Format,
+    #[arg(
+        long,
+        value_enum,
+        help = "Which results to print [default: all, or exits for CSV]"
+    )]
+    report: Option<Report>,
+    #[arg(long, short, help = "Write to this file instead of standard output")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Report {
+    All,
+    Rounds, // the cap table after each round
+    Exits,  // every holder's payout in every exit
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let text = std::fs::read_to_string(&args.file)
+        .map_err(|e| format!("cannot read {}: {}", args.file.display(), e))?;
+    let scenario: ScenarioFile = if args.file.extension().is_some_and(|ext| ext == "toml") {
+        toml::from_str(&text).map_err(|e| format!("{}: {}", args.file.display(), e))?
+    } else {
+        serde_json::from_str(&text).map_err(|e| format!("{}: {}", args.file.display(), e))?
+    };
+    let report = run_scenario(&scenario)?;
+
+    // A CSV file holds one table, so it takes the exits unless asked otherwise
+    let part = args.report.unwrap_or(match args.format {
+        Format::Csv => Report::Exits,
+        Format::Table | Format::Json => Report::All,
+    });
+    let output = match args.format {
+        Format::Table => table_report(&report, part),
+        Format::Json => json_report(&report, part)?,
+        Format::Csv => csv_report(&report, part)?,
+    };
+    match &args.output {
+        Some(path) => std::fs::write(path, output)
+            .map_err(|e| format!("cannot write {}: {}", path.display(), e)),
+        None => {
+            print!("{}", output);
+            Ok(())
+        }
+    }
+}
+
+fn json_report(report: &ScenarioReport, part: Report) -> Result<String, String> {
+    let json = match part {
+        Report::All => serde_json::to_string_pretty(report),
+        Report::Rounds => serde_json::to_string_pretty(&report.rounds),
+        Report::Exits => serde_json::to_string_pretty(&report.exits),
+    };
+    json.map(|json| json + "\n").map_err(|e| e.to_string())
+}
+
+// A CSV file holds one table, so rounds and exits go out separately.
+fn csv_report(report: &ScenarioReport, part: Report) -> Result<String, String> {
+    match part {
+        Report::All => {
+            Err("CSV holds one table; pick --report rounds or --report exits".to_string())
+        }
+        Report::Rounds => {
+            let tables: Vec<_> = report
+                .rounds
+                .iter()
+                .map(|r| (r.round.name.clone(), r.cap_table.clone()))
+                .collect();
+            Ok(round_cap_tables_csv(&tables))
+        }
+        Report::Exits => Ok(exit_distributions_csv(&report.exits)),
+    }
+}
+
+fn table_report(report: &ScenarioReport, part: Report) -> String {
+    let mut out = format!("{}\n", report.name);
+
+    if part != Report::Exits {
+        for round in &report.rounds {
+            let snapshot = &round.snapshot;
+            // A pool top-up taken from the pre-money leaves the old holders'
+            // stake worth less than the headline figure
+            let effective = if snapshot.effective_pre_money == round.round.valuation {
+                String::new()
+            } else {
+                format!(
+                    " ({} effective after the pool top-up)",
+                    dollars(snapshot.effective_pre_money)
+                )
+            };
+            out += &format!(
+                "\n{} (closed {})\nRaised {} at {} pre-money{}, {} per share; {} post-money\n\n",
+                round.round.name,
+                round.round.close_date,
+                dollars(round.round.amount),
+                dollars(round.round.valuation),
+                effective,
+                price(snapshot.price_per_share),
+                dollars(snapshot.total_valuation),
+            );
+            let rows = round
+                .cap_table
+                .iter()
+                .map(|row| {
+                    vec![
+                        row.holder.clone(),
+                        format!("{:?}", row.kind),
+                        row.share_class.clone(),
+                        grouped(row.outstanding_shares),
+                        grouped(row.as_converted_shares),
+                        percent(row.fully_diluted_percentage),
+                    ]
+                })
+                .collect();
+            out += &table(
+                &[
+                    "Holder",
+                    "Type",
+                    "Class",
+                    "Outstanding",
+                    "As Converted",
+                    "Fully Diluted",
+                ],
+                rows,
+                3,
+            );
+        }
+    }
+
+    if part != Report::Rounds {
+        for exit in &report.exits {
+            out += &format!(
+                "\n{} ({:?}, {})\n\n",
+                exit.name, exit.exit_type, exit.exit_date
+            );
+            let rows = exit
+                .distribution
+                .iter()
+                .map(|payout| {
+                    vec![
+                        payout.holder.clone(),
+                        payout.share_class.clone(),
+                        grouped(payout.shares),
+                        dollars(payout.preference_amount),
+                        dollars(payout.participation_amount),
+                        dollars(payout.payout_amount),
+                    ]
+                })
+                .collect();
+            out += &table(
+                &[
+                    "Holder",
+                    "Class",
+                    "Shares",
+                    "Preference",
+                    "Participation",
+                    "Payout",
+                ],
+                rows,
+                2,
+            );
+        }
+    }
+    out
+}
+
+// Columns padded to their widest cell: the first `text_columns` aligned left,
+// the numbers after them right.
+fn table(header: &[&str], rows: Vec<Vec<String>>, text_columns: usize) -> String {
+    let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, width))| {
+                if column < text_columns {
+                    format!("{:<width$}", cell, width = width)
+                } else {
+                    format!("{:>width$}", cell, width = width)
+                }
+            })
+            .collect();
+        out += cells.join("  ").trim_end();
+        out += "\n";
+    }
+    out
+}
+
+fn grouped(shares: u64) -> String {
+    let digits = shares.to_string();
+    let mut out = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+fn dollars(amount: Decimal) -> String {
+    let text = format!("{:.2}", amount.abs().round_dp(2));
+    let (whole, cents) = text.split_once('.').unwrap_or((&text, "00"));
+    let sign = if amount < Decimal::ZERO { "-" } else { "" };
+    format!("{}${}.{}", sign, grouped(whole.parse().unwrap_or(0)), cents)
+}
+
+fn price(amount: Decimal) -> String {
+    format!("${:.4}", amount.round_dp(4))
+}
+
+fn percent(value: Decimal) -> String {
+    format!("{:.2}%", value.round_dp(2))
+}
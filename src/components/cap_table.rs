@@ -1,24 +1,68 @@
 use leptos::*;
-use crate::models::Company;
+use chrono::NaiveDate;
+use crate::csv::cap_table_csv;
+use crate::models::{CapTableRow, Company, HolderKind, NoteOutcome};
+use crate::money::{dec, Decimal, HUNDRED};
+use crate::storage::download_file;
 use crate::utils::{format_percentage, format_shares, format_currency};
+use crate::validation::validate_company;
 
 #[component]
 pub fn CapTable(
-    cap_table: ReadSignal<Vec<(String, f64, u64)>>,
-    company: ReadSignal<Company>,
+    #[prop(into)] cap_table: Signal<Vec<CapTableRow>>,
+    #[prop(into)] company: Signal<Company>,
+    as_of: ReadSignal<Option<NaiveDate>>,
+    set_as_of: WriteSignal<Option<NaiveDate>>,
 ) -> impl IntoView {
-    let total_ownership: f64 = cap_table.get().iter().map(|(_, ownership, _)| ownership).sum();
+    let total_ownership: Decimal = cap_table.get().iter().map(|row| row.fully_diluted_percentage).sum();
     let total_shares = company.get().total_shares;
+    let (export_error, set_export_error) = create_signal(None::<String>);
+
+    // Stock is grouped by class; options and the pool sit together at the end
+    let section_of = |row: &CapTableRow| match row.kind {
+        HolderKind::OptionHolder | HolderKind::OptionPool => "Options".to_string(),
+        _ => row.share_class.clone(),
+    };
 
     view! {
         <div class="bg-white rounded-lg shadow-lg p-6">
-            <h2 class="text-2xl font-bold text-gray-800 mb-6">Cap Table</h2>
+            <div class="flex items-center justify-between mb-6">
+                <h2 class="text-2xl font-bold text-gray-800">Cap Table</h2>
+                <div class="flex items-center">
+                    // Blank shows the cap table as it stands today
+                    <label class="text-sm text-gray-600">
+                        As of
+                        <input
+                            type="date"
+                            class="ml-2 px-2 py-1 border border-gray-300 rounded text-sm"
+                            value={move || as_of.get().map(|d| d.to_string()).unwrap_or_default()}
+                            on:change=move |ev| set_as_of.set(event_target_value(&ev).parse::<NaiveDate>().ok())
+                        />
+                    </label>
+                    <button
+                        class="ml-2 px-3 py-1 border border-gray-300 text-sm text-gray-700 rounded hover:bg-gray-50"
+                        on:click=move |_| {
+                            let filename = match as_of.get() {
+                                Some(date) => format!("cap-table-{}.csv", date),
+                                None => "cap-table.csv".to_string(),
+                            };
+                            let csv = cap_table_csv(&cap_table.get());
+                            set_export_error.set(download_file(&filename, "text/csv", &csv).err());
+                        }
+                    >
+                        Export CSV
+                    </button>
+                </div>
+            </div>
+            {move || export_error.get().map(|message| view! {
+                <div class="mb-4 p-3 bg-red-50 border border-red-200 rounded text-sm text-red-800">{message}</div>
+            })}
             
             // Company Summary
             <div class="bg-blue-50 border border-blue-200 rounded-lg p-4 mb-6">
                 <div class="grid grid-cols-2 gap-4">
                     <div>
-                        <span class="text-blue-600 font-medium">Total Shares:</span>
+                        <span class="text-blue-600 font-medium">Fully Diluted Shares:</span>
                         <span class="ml-2 text-lg font-bold">{format_shares(total_shares)}</span>
                     </div>
                     <div>
@@ -26,6 +70,10 @@ pub fn CapTable(
                         <span class="ml-2 text-lg font-bold">{format_percentage(total_ownership)}</span>
                     </div>
                 </div>
+                {validate_company(&company.get()).err().unwrap_or_default().into_iter()
+                    .filter(|error| error.field() == "shares")
+                    .map(|error| view! { <p class="mt-2 text-sm text-red-600">{error.to_string()}</p> })
+                    .collect::<Vec<_>>()}
             </div>
 
             // Cap Table
@@ -33,60 +81,105 @@ pub fn CapTable(
                 <table class="min-w-full divide-y divide-gray-200">
                     <thead class="bg-gray-50">
                         <tr>
-                            <th class="px-6 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider">
+                            <th class="px-4 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider">
                                 Stakeholder
                             </th>
-                            <th class="px-6 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider">
-                                Ownership
+                            <th class="px-4 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider">
+                                Outstanding
+                            </th>
+                            <th class="px-4 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider">
+                                As Converted
                             </th>
-                            <th class="px-6 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider">
-                                Shares
+                            <th class="px-4 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider">
+                                Outstanding %
                             </th>
-                            <th class="px-6 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider">
+                            <th class="px-4 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider">
+                                Fully Diluted %
+                            </th>
+                            <th class="px-4 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider">
                                 Value at $10M
                             </th>
                         </tr>
                     </thead>
                     <tbody class="bg-white divide-y divide-gray-200">
                         {move || {
-                            cap_table.get().iter().map(|(name, ownership, shares)| {
-                                let value_at_10m = (*ownership / 100.0) * 10_000_000.0;
-                                let is_founder = company.get().founders.iter().any(|f| f.name == *name);
-                                
+                            let rows = cap_table.get();
+                            let company = company.get();
+                            let mut sections: Vec<(String, Vec<CapTableRow>)> = Vec::new();
+                            for row in rows {
+                                let section = section_of(&row);
+                                match sections.last_mut() {
+                                    Some((name, section_rows)) if *name == section => section_rows.push(row),
+                                    _ => sections.push((section, vec![row])),
+                                }
+                            }
+
+                            sections.into_iter().map(|(section, rows)| {
+                                let terms = company.share_class(&section).filter(|c| c.is_preferred()).map(|class| {
+                                    format!(
+                                        "OIP {} · converts {:.4}:1 · {} vote(s) per share",
+                                        format_currency(class.original_issue_price),
+                                        class.conversion_ratio(),
+                                        class.votes_per_share,
+                                    )
+                                });
+                                let outstanding: u64 = rows.iter().map(|r| r.outstanding_shares).sum();
+                                let as_converted: u64 = rows.iter().map(|r| r.as_converted_shares).sum();
+
                                 view! {
-                                    <tr class={if is_founder { "bg-blue-50" } else { "bg-white" }}>
-                                        <td class="px-6 py-4 whitespace-nowrap">
-                                            <div class="flex items-center">
-                                                <div class="text-sm font-medium text-gray-900">
-                                                    {name.clone()}
-                                                </div>
-                                                {if is_founder {
-                                                    view! {
-                                                        <span class="ml-2 inline-flex items-center px-2.5 py-0.5 rounded-full text-xs font-medium bg-blue-100 text-blue-800">
-                                                            Founder
-                                                        </span>
-                                                    }
-                                                } else {
-                                                    view! { <div></div> }
-                                                }}
-                                            </div>
-                                        </td>
-                                        <td class="px-6 py-4 whitespace-nowrap">
-                                            <div class="text-sm text-gray-900 font-medium">
-                                                {format_percentage(*ownership)}
-                                            </div>
-                                        </td>
-                                        <td class="px-6 py-4 whitespace-nowrap">
-                                            <div class="text-sm text-gray-900">
-                                                {format_shares(*shares)}
-                                            </div>
-                                        </td>
-                                        <td class="px-6 py-4 whitespace-nowrap">
-                                            <div class="text-sm text-gray-900">
-                                                {format_currency(value_at_10m)}
-                                            </div>
+                                    <tr class="bg-gray-100">
+                                        <td class="px-4 py-2 text-sm font-semibold text-gray-800">
+                                            {section.clone()}
+                                            <div class="text-xs font-normal text-gray-500">{terms}</div>
                                         </td>
+                                        <td class="px-4 py-2 text-sm font-semibold text-gray-800">{format_shares(outstanding)}</td>
+                                        <td class="px-4 py-2 text-sm font-semibold text-gray-800">{format_shares(as_converted)}</td>
+                                        <td></td>
+                                        <td></td>
+                                        <td></td>
                                     </tr>
+                                    {rows.into_iter().map(|row| {
+                                        let value_at_10m = (row.fully_diluted_percentage / HUNDRED) * dec!(10_000_000);
+                                        let badge = match row.kind {
+                                            HolderKind::Founder => Some("Founder"),
+                                            HolderKind::Employee => Some("Employee"),
+                                            HolderKind::OptionHolder => Some("Options"),
+                                            HolderKind::OptionPool => Some("Unallocated"),
+                                            HolderKind::Investor => None,
+                                        };
+
+                                        view! {
+                                            <tr class={if row.kind == HolderKind::Founder { "bg-blue-50" } else { "bg-white" }}>
+                                                <td class="px-4 py-4 whitespace-nowrap">
+                                                    <div class="flex items-center">
+                                                        <div class="text-sm font-medium text-gray-900">
+                                                            {row.holder.clone()}
+                                                        </div>
+                                                        {badge.map(|badge| view! {
+                                                            <span class="ml-2 inline-flex items-center px-2.5 py-0.5 rounded-full text-xs font-medium bg-blue-100 text-blue-800">
+                                                                {badge}
+                                                            </span>
+                                                        })}
+                                                    </div>
+                                                </td>
+                                                <td class="px-4 py-4 whitespace-nowrap text-sm text-gray-900">
+                                                    {format_shares(row.outstanding_shares)}
+                                                </td>
+                                                <td class="px-4 py-4 whitespace-nowrap text-sm text-gray-900">
+                                                    {format_shares(row.as_converted_shares)}
+                                                </td>
+                                                <td class="px-4 py-4 whitespace-nowrap text-sm text-gray-900">
+                                                    {format_percentage(row.outstanding_percentage)}
+                                                </td>
+                                                <td class="px-4 py-4 whitespace-nowrap text-sm text-gray-900 font-medium">
+                                                    {format_percentage(row.fully_diluted_percentage)}
+                                                </td>
+                                                <td class="px-4 py-4 whitespace-nowrap text-sm text-gray-900">
+                                                    {format_currency(value_at_10m)}
+                                                </td>
+                                            </tr>
+                                        }
+                                    }).collect::<Vec<_>>()}
                                 }
                             }).collect::<Vec<_>>()
                         }}
@@ -102,20 +195,19 @@ pub fn CapTable(
                         <div class="mt-6">
                             <h3 class="text-lg font-medium text-gray-800 mb-3">Ownership Distribution</h3>
                             <div class="space-y-2">
-                                {cap_table_data.iter().map(|(name, ownership, _)| {
-                                    let width = format!("{:.1}%", ownership);
-                                    let color_class = if company.get().founders.iter().any(|f| f.name == *name) {
-                                        "bg-blue-500"
-                                    } else if name == "ESOP Pool" {
-                                        "bg-green-500"
-                                    } else {
-                                        "bg-gray-500"
+                                {cap_table_data.iter().map(|row| {
+                                    let width = format!("{:.1}%", row.fully_diluted_percentage);
+                                    let color_class = match row.kind {
+                                        HolderKind::Founder => "bg-blue-500",
+                                        HolderKind::OptionPool => "bg-green-500",
+                                        HolderKind::OptionHolder | HolderKind::Employee => "bg-green-300",
+                                        HolderKind::Investor => "bg-gray-500",
                                     };
                                     
                                     view! {
                                         <div class="flex items-center space-x-3">
                                             <div class="w-24 text-sm text-gray-600 truncate">
-                                                {name.clone()}
+                                                {row.holder.clone()}
                                             </div>
                                             <div class="flex-1 bg-gray-200 rounded-full h-4">
                                                 <div
@@ -124,7 +216,7 @@ pub fn CapTable(
                                                 ></div>
                                             </div>
                                             <div class="w-16 text-sm text-gray-900 font-medium text-right">
-                                                {format_percentage(*ownership)}
+                                                {format_percentage(row.fully_diluted_percentage)}
                                             </div>
                                         </div>
                                     }
@@ -137,6 +229,143 @@ pub fn CapTable(
                 }
             }}
 
+            // Investors across rounds
+            {move || {
+                let positions = company.get().investor_positions();
+                if !positions.is_empty() {
+                    view! {
+                        <div class="mt-6">
+                            <h3 class="text-lg font-medium text-gray-800 mb-3">Investors</h3>
+                            <div class="space-y-2">
+                                {positions.iter().map(|position| {
+                                    view! {
+                                        <div class="flex justify-between items-center bg-gray-50 px-4 py-2 rounded-lg text-sm">
+                                            <span class="font-medium text-gray-700">
+                                                {position.name.clone()}
+                                                <span class="ml-2 text-gray-500">{position.rounds.join(", ")}</span>
+                                            </span>
+                                            <span class="text-gray-600">
+                                                {format_currency(position.invested)} " invested · "
+                                                {format_shares(position.shares)} " shares · "
+                                                {format_percentage(position.ownership)}
+                                            </span>
+                                        </div>
+                                    }
+                                }).collect::<Vec<_>>()}
+                            </div>
+                        </div>
+                    }
+                } else {
+                    view! { <div></div> }
+                }
+            }}
+
+            // Unconverted SAFEs
+            {move || {
+                let safes = company.get().safes.clone();
+                if !safes.is_empty() {
+                    view! {
+                        <div class="mt-6">
+                            <h3 class="text-lg font-medium text-gray-800 mb-3">Unconverted SAFEs</h3>
+                            <div class="space-y-2">
+                                {safes.iter().map(|safe| {
+                                    let cap = if safe.terms.valuation_cap > Decimal::ZERO {
+                                        format!("{} cap", format_currency(safe.terms.valuation_cap))
+                                    } else {
+                                        "Uncapped".to_string()
+                                    };
+
+                                    view! {
+                                        <div class="flex justify-between items-center bg-yellow-50 px-4 py-2 rounded-lg text-sm">
+                                            <span class="font-medium text-gray-700">
+                                                {safe.holder.clone()} " (" {safe.round_name.clone()} ")"
+                                            </span>
+                                            <span class="text-gray-600">
+                                                {format_currency(safe.investment)} " · " {cap}
+                                                {if safe.terms.discount_rate > Decimal::ZERO {
+                                                    format!(" · {:.0}% discount", safe.terms.discount_rate)
+                                                } else {
+                                                    "".to_string()
+                                                }}
+                                                {if safe.terms.mfn { " · MFN" } else { "" }}
+                                            </span>
+                                        </div>
+                                    }
+                                }).collect::<Vec<_>>()}
+                            </div>
+                        </div>
+                    }
+                } else {
+                    view! { <div></div> }
+                }
+            }}
+
+            // Outstanding Convertible Notes
+            {move || {
+                let notes = company.get().notes.clone();
+                if !notes.is_empty() {
+                    let today = chrono::Local::now().date_naive();
+                    view! {
+                        <div class="mt-6">
+                            <h3 class="text-lg font-medium text-gray-800 mb-3">Outstanding Convertible Notes</h3>
+                            <div class="space-y-2">
+                                {notes.iter().map(|note| {
+                                    view! {
+                                        <div class="flex justify-between items-center bg-yellow-50 px-4 py-2 rounded-lg text-sm">
+                                            <span class="font-medium text-gray-700">
+                                                {note.holder.clone()} " (" {note.round_name.clone()} ")"
+                                            </span>
+                                            <span class="text-gray-600">
+                                                {format_currency(note.principal)} " principal · "
+                                                {format_currency(note.accrued_amount(today))} " accrued · matures "
+                                                {note.terms.maturity_date.to_string()}
+                                            </span>
+                                        </div>
+                                    }
+                                }).collect::<Vec<_>>()}
+                            </div>
+                        </div>
+                    }
+                } else {
+                    view! { <div></div> }
+                }
+            }}
+
+            // Notes that reached maturity without converting in a round
+            {move || {
+                let settlements = company.get().note_settlements.clone();
+                if !settlements.is_empty() {
+                    view! {
+                        <div class="mt-6">
+                            <h3 class="text-lg font-medium text-gray-800 mb-3">Matured Convertible Notes</h3>
+                            <div class="space-y-2">
+                                {settlements.iter().map(|settlement| {
+                                    let outcome = match &settlement.outcome {
+                                        NoteOutcome::Converted { shares } => {
+                                            format!("converted at the cap into {} shares", format_shares(*shares))
+                                        }
+                                        NoteOutcome::Repaid { amount } => format!("repaid {}", format_currency(*amount)),
+                                        NoteOutcome::Extended { maturity_date } => format!("extended to {}", maturity_date),
+                                    };
+                                    view! {
+                                        <div class="flex justify-between items-center bg-gray-50 px-4 py-2 rounded-lg text-sm">
+                                            <span class="font-medium text-gray-700">
+                                                {settlement.holder.clone()} " (" {settlement.round_name.clone()} ")"
+                                            </span>
+                                            <span class="text-gray-600">
+                                                "Matured " {settlement.date.to_string()} " · " {outcome}
+                                            </span>
+                                        </div>
+                                    }
+                                }).collect::<Vec<_>>()}
+                            </div>
+                        </div>
+                    }
+                } else {
+                    view! { <div></div> }
+                }
+            }}
+
             // Dilution Impact
             {move || {
                 let founders = company.get().founders.clone();
@@ -147,8 +376,8 @@ pub fn CapTable(
                             <div class="space-y-3">
                                 {founders.iter().map(|founder| {
                                     let dilution = founder.initial_ownership - founder.current_ownership;
-                                    let dilution_color = if dilution > 10.0 { "text-red-600" } 
-                                                       else if dilution > 5.0 { "text-orange-600" } 
+                                    let dilution_color = if dilution > dec!(10) { "text-red-600" } 
+                                                       else if dilution > dec!(5) { "text-orange-600" } 
                                                        else { "text-green-600" };
                                     
                                     view! {
@@ -161,7 +390,7 @@ pub fn CapTable(
                                                     {format_percentage(founder.initial_ownership)} → {format_percentage(founder.current_ownership)}
                                                 </div>
                                                 <div class={format!("text-sm font-medium {}", dilution_color)}>
-                                                    {if dilution > 0.0 {
+                                                    {if dilution > Decimal::ZERO {
                                                         format!("-{:.1}%", dilution)
                                                     } else {
                                                         "No dilution".to_string()
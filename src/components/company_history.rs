@@ -0,0 +1,204 @@
+use leptos::*;
+use chrono::NaiveDate;
+use crate::models::{Company, CompanyEvent, EventKind, HolderKind, COMMON_CLASS};
+use crate::money::{parse_decimal, Decimal};
+
+#[component]
+pub fn company_history(
+    company: ReadSignal<Company>,
+    set_company: WriteSignal<Company>,
+) -> impl IntoView {
+    let (error, set_error) = create_signal(None::<String>);
+
+    let (event_type, set_event_type) = create_signal("Transfer".to_string());
+    let (event_date, set_event_date) = create_signal(chrono::Local::now().date_naive());
+    let (holder, set_holder) = create_signal("".to_string());
+    let (recipient, set_recipient) = create_signal("".to_string());
+    let (share_class, set_share_class) = create_signal(COMMON_CLASS.to_string());
+    let (shares, set_shares) = create_signal(0u64);
+    let (price_per_share, set_price_per_share) = create_signal(Decimal::ZERO);
+    let (split_ratio, set_split_ratio) = create_signal(2u64);
+
+    let record_event = create_action(move |event: &CompanyEvent| {
+        let mut new_company = company.get();
+        match new_company.record_event(event.clone()) {
+            Ok(()) => {
+                set_error.set(None);
+                set_company.set(new_company);
+            }
+            Err(message) => set_error.set(Some(message)),
+        }
+    });
+
+    let update_founding_date = create_action(move |date: &NaiveDate| {
+        let mut new_company = company.get();
+        match new_company.set_founding_date(*date) {
+            Ok(()) => {
+                set_error.set(None);
+                set_company.set(new_company);
+            }
+            Err(message) => set_error.set(Some(message)),
+        }
+    });
+
+    view! {
+        <div class="bg-white rounded-lg shadow-lg p-6">
+            <h2 class="text-2xl font-bold text-gray-800 mb-6">Company History</h2>
+
+            // Founding
+            <div class="mb-6">
+                <label class="block text-sm font-medium text-gray-700 mb-1">Founded</label>
+                <input
+                    type="date"
+                    class="w-full px-3 py-2 border border-gray-300 rounded-md"
+                    value={move || company.get().founding_date().map(|d| d.to_string()).unwrap_or_default()}
+                    on:change=move |ev| {
+                        if let Ok(date) = event_target_value(&ev).parse::<NaiveDate>() {
+                            update_founding_date.dispatch(date);
+                        }
+                    }
+                />
+            </div>
+
+            // New Event
+            <div class="space-y-3 mb-6">
+                <div class="grid grid-cols-2 gap-3">
+                    <select
+                        class="w-full px-3 py-2 border border-gray-300 rounded-md"
+                        on:change=move |ev| set_event_type.set(event_target_value(&ev))
+                    >
+                        <option value="Transfer">Transfer</option>
+                        <option value="Repurchase">Repurchase</option>
+                        <option value="Split">Stock split</option>
+                    </select>
+                    <input
+                        type="date"
+                        class="w-full px-3 py-2 border border-gray-300 rounded-md"
+                        value={move || event_date.get().to_string()}
+                        on:change=move |ev| {
+                            if let Ok(date) = event_target_value(&ev).parse::<NaiveDate>() {
+                                set_event_date.set(date);
+                            }
+                        }
+                    />
+                </div>
+
+                {move || if event_type.get() == "Split" {
+                    view! {
+                        <div>
+                            <label class="block text-sm font-medium text-gray-700 mb-1">New shares per existing share</label>
+                            <input
+                                type="number"
+                                min="1"
+                                class="w-full px-3 py-2 border border-gray-300 rounded-md"
+                                value={split_ratio.get().to_string()}
+                                on:change=move |ev| set_split_ratio.set(event_target_value(&ev).parse::<u64>().unwrap_or(1))
+                            />
+                        </div>
+                    }
+                } else {
+                    view! {
+                        <div class="grid grid-cols-2 gap-3">
+                            <input
+                                type="text"
+                                placeholder="Holder"
+                                class="w-full px-3 py-2 border border-gray-300 rounded-md"
+                                value=holder
+                                on:change=move |ev| set_holder.set(event_target_value(&ev))
+                            />
+                            {if event_type.get() == "Transfer" {
+                                view! {
+                                    <input
+                                        type="text"
+                                        placeholder="Transferee"
+                                        class="w-full px-3 py-2 border border-gray-300 rounded-md"
+                                        value=recipient
+                                        on:change=move |ev| set_recipient.set(event_target_value(&ev))
+                                    />
+                                }
+                            } else {
+                                view! {
+                                    <input
+                                        type="number"
+                                        step="0.01"
+                                        min="0"
+                                        placeholder="Price per share ($)"
+                                        class="w-full px-3 py-2 border border-gray-300 rounded-md"
+                                        value={price_per_share.get().to_string()}
+                                        on:change=move |ev| set_price_per_share.set(parse_decimal(&event_target_value(&ev)).unwrap_or_default())
+                                    />
+                                }
+                            }}
+                            <select
+                                class="w-full px-3 py-2 border border-gray-300 rounded-md"
+                                on:change=move |ev| set_share_class.set(event_target_value(&ev))
+                            >
+                                {company.get().share_classes.iter().map(|class| view! {
+                                    <option value={class.name.clone()} selected={class.name == share_class.get()}>
+                                        {class.name.clone()}
+                                    </option>
+                                }).collect::<Vec<_>>()}
+                            </select>
+                            <input
+                                type="number"
+                                min="0"
+                                placeholder="Shares"
+                                class="w-full px-3 py-2 border border-gray-300 rounded-md"
+                                value={shares.get().to_string()}
+                                on:change=move |ev| set_shares.set(event_target_value(&ev).parse::<u64>().unwrap_or(0))
+                            />
+                        </div>
+                    }
+                }}
+
+                <button
+                    class="w-full px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 focus:outline-none focus:ring-2 focus:ring-blue-500"
+                    on:click=move |_| {
+                        let kind = match event_type.get().as_str() {
+                            "Split" => EventKind::Split {
+                                numerator: split_ratio.get(),
+                                denominator: 1,
+                            },
+                            "Repurchase" => EventKind::Repurchase {
+                                holder: holder.get(),
+                                share_class: share_class.get(),
+                                shares: shares.get(),
+                                price_per_share: price_per_share.get(),
+                            },
+                            _ => EventKind::Transfer {
+                                from: holder.get(),
+                                to: recipient.get(),
+                                // Secondary buyers are investors unless they already hold as something else
+                                to_kind: company.get().ledger.holdings.iter()
+                                    .find(|h| h.holder == recipient.get())
+                                    .map(|h| h.kind.clone())
+                                    .unwrap_or(HolderKind::Investor),
+                                share_class: share_class.get(),
+                                shares: shares.get(),
+                            },
+                        };
+                        record_event.dispatch(CompanyEvent { date: event_date.get(), kind });
+                    }
+                >
+                    Record Event
+                </button>
+                {move || error.get().map(|message| view! {
+                    <div class="p-3 bg-red-50 border border-red-200 rounded text-sm text-red-800">{message}</div>
+                })}
+            </div>
+
+            // Event Log
+            <div>
+                <h3 class="text-lg font-medium text-gray-700 mb-3">Event Log</h3>
+                <div class="space-y-2">
+                    {move || company.get().events.iter().map(|event| view! {
+                        <div class="bg-gray-50 px-3 py-2 rounded text-sm flex justify-between">
+                            <span class="text-gray-900">{event.kind.describe()}</span>
+                            <span class="text-gray-500">{event.date.to_string()}</span>
+                        </div>
+                    }).collect::<Vec<_>>()}
+                </div>
+            </div>
+        </div>
+    }
+}
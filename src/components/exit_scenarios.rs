@@ -1,15 +1,87 @@
 use leptos::*;
+use chrono::NaiveDate;
+use crate::csv::exit_distributions_csv;
 use crate::models::{Company, ExitScenario, ExitType};
-use crate::utils::{format_currency, format_percentage};
+use crate::money::{dec, Decimal, HUNDRED};
+use crate::storage::download_file;
+use crate::utils::{format_currency, format_percentage, format_shares};
 
 #[component]
 pub fn ExitScenarios(
-    scenarios: ReadSignal<Vec<ExitScenario>>,
+    #[prop(into)] scenarios: Signal<Vec<ExitScenario>>,
     company: ReadSignal<Company>,
+    exit_type: ReadSignal<ExitType>,
+    set_exit_type: WriteSignal<ExitType>,
+    exit_date: ReadSignal<NaiveDate>,
+    set_exit_date: WriteSignal<NaiveDate>,
 ) -> impl IntoView {
+    let (export_error, set_export_error) = create_signal(None::<String>);
+
     view! {
         <div class="bg-white rounded-lg shadow-lg p-6">
-            <h2 class="text-2xl font-bold text-gray-800 mb-6">Exit Scenarios</h2>
+            <div class="flex items-center justify-between mb-6">
+                <h2 class="text-2xl font-bold text-gray-800">Exit Scenarios</h2>
+                <button
+                    class="px-3 py-1 border border-gray-300 text-sm text-gray-700 rounded hover:bg-gray-50"
+                    on:click=move |_| {
+                        let csv = exit_distributions_csv(&scenarios.get());
+                        set_export_error.set(download_file("exit-payouts.csv", "text/csv", &csv).err());
+                    }
+                >
+                    Export CSV
+                </button>
+            </div>
+            {move || export_error.get().map(|message| view! {
+                <div class="mb-4 p-3 bg-red-50 border border-red-200 rounded text-sm text-red-800">{message}</div>
+            })}
+
+            // Exit Assumptions
+            <div class="grid grid-cols-1 md:grid-cols-2 gap-4 mb-6">
+                <div>
+                    <label class="block text-sm font-medium text-gray-700 mb-1">
+                        Exit Type
+                    </label>
+                    <select
+                        class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                        value={move || match exit_type.get() {
+                            ExitType::IPO => "IPO",
+                            ExitType::Acquisition => "Acquisition",
+                            ExitType::Merger => "Merger",
+                            ExitType::SecondarySale => "SecondarySale",
+                        }}
+                        on:change=move |ev| {
+                            let exit_type = match event_target_value(&ev).as_str() {
+                                "IPO" => ExitType::IPO,
+                                "Merger" => ExitType::Merger,
+                                "SecondarySale" => ExitType::SecondarySale,
+                                _ => ExitType::Acquisition,
+                            };
+                            set_exit_type.set(exit_type);
+                        }
+                    >
+                        <option value="Acquisition">Acquisition</option>
+                        <option value="Merger">Merger</option>
+                        <option value="IPO">IPO</option>
+                        <option value="SecondarySale">Secondary Sale</option>
+                    </select>
+                </div>
+
+                <div>
+                    <label class="block text-sm font-medium text-gray-700 mb-1">
+                        Exit Date
+                    </label>
+                    <input
+                        type="date"
+                        class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                        value={move || exit_date.get().to_string()}
+                        on:change=move |ev| {
+                            if let Ok(date) = event_target_value(&ev).parse::<NaiveDate>() {
+                                set_exit_date.set(date);
+                            }
+                        }
+                    />
+                </div>
+            </div>
             
             <div class="grid grid-cols-1 lg:grid-cols-2 gap-6">
                 {move || {
@@ -41,7 +113,7 @@ pub fn ExitScenarios(
                                 <div class="space-y-3">
                                     <h4 class="font-medium text-gray-700">Founder Payouts</h4>
                                     {scenario.founder_payouts.iter().map(|payout| {
-                                        let payout_percentage = (payout.payout_amount / scenario.exit_valuation) * 100.0;
+                                        let payout_percentage = payout.payout_amount.checked_div(scenario.exit_valuation).unwrap_or_default() * HUNDRED;
                                         
                                         view! {
                                             <div key={payout.founder_name.clone()} class="bg-white rounded p-3 border">
@@ -63,10 +135,10 @@ pub fn ExitScenarios(
                                                     </div>
                                                     <div>
                                                         <span class="text-gray-600">Dilution:</span>
-                                                        <span class={if payout.dilution_impact > 10.0 { "ml-2 font-semibold text-red-600" } 
-                                                                   else if payout.dilution_impact > 5.0 { "ml-2 font-semibold text-orange-600" } 
+                                                        <span class={if payout.dilution_impact > dec!(10) { "ml-2 font-semibold text-red-600" } 
+                                                                   else if payout.dilution_impact > dec!(5) { "ml-2 font-semibold text-orange-600" } 
                                                                    else { "ml-2 font-semibold text-green-600" }}>
-                                                            {if payout.dilution_impact > 0.0 {
+                                                            {if payout.dilution_impact > Decimal::ZERO {
                                                                 format!("-{:.1}%", payout.dilution_impact)
                                                             } else {
                                                                 "None".to_string()
@@ -74,6 +146,26 @@ pub fn ExitScenarios(
                                                         </span>
                                                     </div>
                                                 </div>
+
+                                                {if payout.unvested_shares > 0 || payout.accelerated_shares > 0 {
+                                                    view! {
+                                                        <div class="mt-2 text-xs text-gray-600">
+                                                            {format!(
+                                                                "{} vested · {} accelerated · {} unvested",
+                                                                format_shares(payout.vested_shares),
+                                                                format_shares(payout.accelerated_shares),
+                                                                format_shares(payout.unvested_shares),
+                                                            )}
+                                                            {if payout.unvested_payout > Decimal::ZERO {
+                                                                format!(" ({} held back until vested)", format_currency(payout.unvested_payout))
+                                                            } else {
+                                                                "".to_string()
+                                                            }}
+                                                        </div>
+                                                    }
+                                                } else {
+                                                    view! { <div></div> }
+                                                }}
                                             </div>
                                         }
                                     }).collect::<Vec<_>>()}
@@ -84,10 +176,42 @@ pub fn ExitScenarios(
                                     <div class="flex justify-between items-center">
                                         <span class="font-medium text-gray-700">Total Founder Value:</span>
                                         <span class="text-lg font-bold text-green-600">
-                                            {format_currency(scenario.founder_payouts.iter().map(|p| p.payout_amount).sum::<f64>())}
+                                            {format_currency(scenario.founder_payouts.iter().map(|p| p.payout_amount).sum::<Decimal>())}
                                         </span>
                                     </div>
                                 </div>
+
+                                // Full Distribution
+                                <div class="mt-4">
+                                    <h4 class="font-medium text-gray-700 mb-2">Distribution</h4>
+                                    <table class="min-w-full text-xs">
+                                        <thead>
+                                            <tr class="text-gray-500 uppercase">
+                                                <th class="text-left py-1">Holder</th>
+                                                <th class="text-left py-1">Class</th>
+                                                <th class="text-right py-1">Preference</th>
+                                                <th class="text-right py-1">Participation</th>
+                                                <th class="text-right py-1">Total</th>
+                                            </tr>
+                                        </thead>
+                                        <tbody>
+                                            {scenario.distribution.iter().map(|payout| {
+                                                view! {
+                                                    <tr class="border-t border-gray-200">
+                                                        <td class="py-1 text-gray-800">{payout.holder.clone()}</td>
+                                                        <td class="py-1 text-gray-600">
+                                                            {payout.share_class.clone()}
+                                                            {if payout.converted_to_common { " (converted)" } else { "" }}
+                                                        </td>
+                                                        <td class="py-1 text-right">{format_currency(payout.preference_amount)}</td>
+                                                        <td class="py-1 text-right">{format_currency(payout.participation_amount)}</td>
+                                                        <td class="py-1 text-right font-semibold">{format_currency(payout.payout_amount)}</td>
+                                                    </tr>
+                                                }
+                                            }).collect::<Vec<_>>()}
+                                        </tbody>
+                                    </table>
+                                </div>
                             </div>
                         }
                     }).collect::<Vec<_>>()
@@ -122,7 +246,7 @@ pub fn ExitScenarios(
                                     </thead>
                                     <tbody class="bg-white divide-y divide-gray-200">
                                         {scenarios_data.iter().map(|scenario| {
-                                            let total_founder_value: f64 = scenario.founder_payouts.iter().map(|p| p.payout_amount).sum();
+                                            let total_founder_value: Decimal = scenario.founder_payouts.iter().map(|p| p.payout_amount).sum();
                                             
                                             view! {
                                                 <tr>
@@ -1,43 +1,58 @@
 use leptos::*;
-use crate::models::{Company, Founder};
+use chrono::NaiveDate;
+use crate::models::{Acceleration, Company, VestingCadence, VestingSchedule};
+use crate::money::{parse_decimal, Decimal, HUNDRED};
+use crate::validation::validate_company;
 
 #[component]
 pub fn founder_setup(
     company: ReadSignal<Company>,
     set_company: WriteSignal<Company>,
 ) -> impl IntoView {
-    let add_founder = create_action(move |name: &String| {
+    // Founder edits replay every round from the founding split; one that
+    // would break a later event is refused with the reason
+    let (founder_error, set_founder_error) = create_signal(None::<String>);
+    let edit_founders = move |edit: &dyn Fn(&mut Company) -> Result<(), String>| {
         let mut new_company = company.get();
-        let new_founder = Founder {
-            name: name.clone(),
-            initial_ownership: 0.0,
-            current_ownership: 0.0,
-            shares: 0,
-        };
-        new_company.founders.push(new_founder);
-        set_company.set(new_company);
+        match edit(&mut new_company) {
+            Ok(()) => {
+                set_founder_error.set(None);
+                set_company.set(new_company);
+            }
+            Err(message) => set_founder_error.set(Some(message)),
+        }
+    };
+
+    let add_founder = create_action(move |name: &String| {
+        edit_founders(&|c: &mut Company| c.add_founder(name));
     });
 
     let remove_founder = create_action(move |index: &usize| {
+        edit_founders(&|c: &mut Company| c.remove_founder(*index));
+    });
+
+    let update_founder_name = create_action(move |(index, name): &(usize, String)| {
         let mut new_company = company.get();
-        new_company.founders.remove(*index);
+        new_company.rename_founder(*index, name);
         set_company.set(new_company);
     });
 
-    let update_founder_name = create_action(move |(index, name): &(usize, String)| {
+    let update_founder_ownership = create_action(move |(index, ownership): &(usize, Decimal)| {
+        edit_founders(&|c: &mut Company| c.set_founder_ownership(*index, *ownership));
+    });
+
+    let update_founder_vesting = create_action(move |(index, vesting): &(usize, Option<VestingSchedule>)| {
         let mut new_company = company.get();
         if let Some(founder) = new_company.founders.get_mut(*index) {
-            founder.name = name.clone();
+            founder.vesting = vesting.clone();
             set_company.set(new_company);
         }
     });
 
-    let update_founder_ownership = create_action(move |(index, ownership): &(usize, f64)| {
+    let update_founder_departure = create_action(move |(index, date): &(usize, Option<NaiveDate>)| {
         let mut new_company = company.get();
         if let Some(founder) = new_company.founders.get_mut(*index) {
-            founder.initial_ownership = *ownership;
-            founder.current_ownership = *ownership;
-            founder.shares = (new_company.total_shares as f64 * *ownership / 100.0) as u64;
+            founder.departure_date = *date;
             set_company.set(new_company);
         }
     });
@@ -112,7 +127,7 @@ pub fn founder_setup(
                                                 class="w-20 px-2 py-1 border border-gray-300 rounded text-sm"
                                                 value={founder.initial_ownership.to_string()}
                                                 on:change=move |ev| {
-                                                    let ownership = event_target_value(&ev).parse::<f64>().unwrap_or(0.0);
+                                                    let ownership = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
                                                     update_founder_ownership.dispatch((index, ownership));
                                                 }
                                             />
@@ -133,22 +148,170 @@ pub fn founder_setup(
                                 <div class="mt-3 text-sm text-gray-600">
                                     Shares: {format!("{:,}", founder.shares)}
                                 </div>
+
+                                // Vesting
+                                <div class="mt-3">
+                                    <label class="flex items-center">
+                                        <input
+                                            type="checkbox"
+                                            class="h-4 w-4 text-blue-600 focus:ring-blue-500 border-gray-300 rounded"
+                                            checked={founder.vesting.is_some()}
+                                            on:change=move |ev| {
+                                                let vesting = if event_target_checked(&ev) {
+                                                    Some(VestingSchedule {
+                                                        start_date: chrono::Local::now().date_naive(),
+                                                        cliff_months: 12,
+                                                        total_months: 48,
+                                                        cadence: VestingCadence::Monthly,
+                                                        acceleration: Acceleration::None,
+                                                    })
+                                                } else {
+                                                    None
+                                                };
+                                                update_founder_vesting.dispatch((index, vesting));
+                                            }
+                                        />
+                                        <span class="ml-2 text-sm text-gray-700">Shares vest</span>
+                                    </label>
+
+                                    {match founder.vesting.clone() {
+                                        Some(schedule) => {
+                                            let start_schedule = schedule.clone();
+                                            let cliff_schedule = schedule.clone();
+                                            let total_schedule = schedule.clone();
+                                            let cadence_schedule = schedule.clone();
+                                            let acceleration_schedule = schedule.clone();
+                                            view! {
+                                                <div class="grid grid-cols-2 gap-3 mt-2 text-sm">
+                                                    <div>
+                                                        <label class="block text-xs text-gray-600 mb-1">Vesting start</label>
+                                                        <input
+                                                            type="date"
+                                                            class="w-full px-2 py-1 border border-gray-300 rounded"
+                                                            value={schedule.start_date.to_string()}
+                                                            on:change=move |ev| {
+                                                                if let Ok(date) = event_target_value(&ev).parse::<NaiveDate>() {
+                                                                    let mut schedule = start_schedule.clone();
+                                                                    schedule.start_date = date;
+                                                                    update_founder_vesting.dispatch((index, Some(schedule)));
+                                                                }
+                                                            }
+                                                        />
+                                                    </div>
+                                                    <div>
+                                                        <label class="block text-xs text-gray-600 mb-1">Cadence</label>
+                                                        <select
+                                                            class="w-full px-2 py-1 border border-gray-300 rounded"
+                                                            value={match schedule.cadence {
+                                                                VestingCadence::Monthly => "Monthly",
+                                                                VestingCadence::Quarterly => "Quarterly",
+                                                            }}
+                                                            on:change=move |ev| {
+                                                                let mut schedule = cadence_schedule.clone();
+                                                                schedule.cadence = match event_target_value(&ev).as_str() {
+                                                                    "Quarterly" => VestingCadence::Quarterly,
+                                                                    _ => VestingCadence::Monthly,
+                                                                };
+                                                                update_founder_vesting.dispatch((index, Some(schedule)));
+                                                            }
+                                                        >
+                                                            <option value="Monthly">Monthly</option>
+                                                            <option value="Quarterly">Quarterly</option>
+                                                        </select>
+                                                    </div>
+                                                    <div>
+                                                        <label class="block text-xs text-gray-600 mb-1">Cliff (months)</label>
+                                                        <input
+                                                            type="number"
+                                                            min="0"
+                                                            class="w-full px-2 py-1 border border-gray-300 rounded"
+                                                            value={schedule.cliff_months.to_string()}
+                                                            on:change=move |ev| {
+                                                                let mut schedule = cliff_schedule.clone();
+                                                                schedule.cliff_months = event_target_value(&ev).parse::<u32>().unwrap_or(0);
+                                                                update_founder_vesting.dispatch((index, Some(schedule)));
+                                                            }
+                                                        />
+                                                    </div>
+                                                    <div>
+                                                        <label class="block text-xs text-gray-600 mb-1">Duration (months)</label>
+                                                        <input
+                                                            type="number"
+                                                            min="0"
+                                                            class="w-full px-2 py-1 border border-gray-300 rounded"
+                                                            value={schedule.total_months.to_string()}
+                                                            on:change=move |ev| {
+                                                                let mut schedule = total_schedule.clone();
+                                                                schedule.total_months = event_target_value(&ev).parse::<u32>().unwrap_or(0);
+                                                                update_founder_vesting.dispatch((index, Some(schedule)));
+                                                            }
+                                                        />
+                                                    </div>
+                                                    <div>
+                                                        <label class="block text-xs text-gray-600 mb-1">Acceleration</label>
+                                                        <select
+                                                            class="w-full px-2 py-1 border border-gray-300 rounded"
+                                                            value={match schedule.acceleration {
+                                                                Acceleration::None => "None",
+                                                                Acceleration::SingleTrigger { .. } => "SingleTrigger",
+                                                                Acceleration::DoubleTrigger { .. } => "DoubleTrigger",
+                                                            }}
+                                                            on:change=move |ev| {
+                                                                let mut schedule = acceleration_schedule.clone();
+                                                                schedule.acceleration = match event_target_value(&ev).as_str() {
+                                                                    "SingleTrigger" => Acceleration::SingleTrigger { percentage: HUNDRED },
+                                                                    "DoubleTrigger" => Acceleration::DoubleTrigger { percentage: HUNDRED },
+                                                                    _ => Acceleration::None,
+                                                                };
+                                                                update_founder_vesting.dispatch((index, Some(schedule)));
+                                                            }
+                                                        >
+                                                            <option value="None">None</option>
+                                                            <option value="SingleTrigger">Single trigger (100%)</option>
+                                                            <option value="DoubleTrigger">Double trigger (100%)</option>
+                                                        </select>
+                                                    </div>
+                                                    <div>
+                                                        <label class="block text-xs text-gray-600 mb-1">Departure date</label>
+                                                        <input
+                                                            type="date"
+                                                            class="w-full px-2 py-1 border border-gray-300 rounded"
+                                                            value={founder.departure_date.map(|d| d.to_string()).unwrap_or_default()}
+                                                            on:change=move |ev| {
+                                                                let date = event_target_value(&ev).parse::<NaiveDate>().ok();
+                                                                update_founder_departure.dispatch((index, date));
+                                                            }
+                                                        />
+                                                    </div>
+                                                </div>
+                                            }
+                                        }
+                                        None => view! { <div></div> },
+                                    }}
+                                </div>
                             </div>
                         }
                     }).collect::<Vec<_>>()
                 }}
             </div>
 
-            // Total Ownership Warning
+            {move || founder_error.get().map(|message| view! {
+                <div class="mt-4 p-3 bg-red-50 border border-red-200 rounded text-sm text-red-800">{message}</div>
+            })}
+
+            // Founder Checks
             {move || {
-                let total_ownership: f64 = company.get().founders.iter().map(|f| f.initial_ownership).sum();
-                if (total_ownership - 100.0).abs() > 0.1 {
+                let total_ownership: Decimal = company.get().founders.iter().map(|f| f.initial_ownership).sum();
+                let problems: Vec<String> = validate_company(&company.get()).err().unwrap_or_default().iter()
+                    .filter(|error| error.field() == "founders")
+                    .map(|error| error.to_string())
+                    .collect();
+                if !problems.is_empty() {
                     view! {
                         <div class="mt-4 p-3 bg-yellow-50 border border-yellow-200 rounded-lg">
-                            <div class="text-yellow-800 text-sm">
-                                Total founder ownership: {format!("{:.1}%", total_ownership)}
-                                {if total_ownership > 100.0 { " (exceeds 100%)" } else { " (under 100%)" }}
-                            </div>
+                            {problems.into_iter().map(|message| view! {
+                                <div class="text-yellow-800 text-sm">{message}</div>
+                            }).collect::<Vec<_>>()}
                         </div>
                     }
                 } else {
@@ -1,12 +1,20 @@
 use leptos::*;
-use crate::models::{Company, FundingRound, DealType};
+use chrono::{Months, NaiveDate};
+use crate::models::{
+    AntiDilution, Company, FundingRound, DealType, InterestType, InvestorAllocation, LiquidationPreference,
+    MaturityBehavior, NoteTerms, Participation, ProRataElection, ProRataParticipation, RoundInput, SafeTerms,
+    Seniority,
+};
+use crate::money::{dec, parse_decimal, Decimal, HUNDRED};
+use crate::utils::{format_currency, resolve_round_terms};
+use crate::validation::{validate_company, validate_funding_round, ValidationError};
 
 #[component]
 pub fn FundingRounds(
     current_round: ReadSignal<FundingRound>,
     set_current_round: WriteSignal<FundingRound>,
-    add_round: Action<FundingRound, Result<(), ()>>,
     company: ReadSignal<Company>,
+    set_company: WriteSignal<Company>,
 ) -> impl IntoView {
     let update_round_name = create_action(move |name: &String| {
         let mut new_round = current_round.get();
@@ -14,50 +22,166 @@ pub fn FundingRounds(
         set_current_round.set(new_round);
     });
 
-    let update_round_amount = create_action(move |amount: &f64| {
+    let update_close_date = create_action(move |date: &NaiveDate| {
         let mut new_round = current_round.get();
-        new_round.amount = *amount;
+        new_round.close_date = *date;
+        set_current_round.set(new_round);
+    });
+
+    // Problems with the round being edited, each shown beneath its input
+    let (field_errors, set_field_errors) = create_signal(Vec::<ValidationError>::new());
+    let (round_error, set_round_error) = create_signal(None::<String>);
+    // Index of the existing round loaded into the editor, if any
+    let (editing, set_editing) = create_signal(None::<usize>);
+
+    // Every change to the round list replays the rounds from the founding state
+    let edit_rounds = move |edit: &dyn Fn(&mut Company) -> Result<(), String>| {
+        let mut new_company = company.get();
+        match edit(&mut new_company) {
+            Ok(()) => {
+                set_round_error.set(None);
+                set_company.set(new_company);
+            }
+            Err(message) => set_round_error.set(Some(message)),
+        }
+    };
+
+    // Derive the terms the input mode leaves open from the two that were given
+    let resolve_terms = move |mut new_round: FundingRound| {
+        match resolve_round_terms(&mut new_round, company.get().total_shares) {
+            Ok(()) => set_field_errors.set(Vec::new()),
+            Err(error) => set_field_errors.set(vec![error]),
+        }
+        // A sole investor writes the whole check
+        if let [allocation] = new_round.investors.as_mut_slice() {
+            allocation.amount = new_round.amount;
+        }
         set_current_round.set(new_round);
+    };
+
+    let update_round_input = create_action(move |input: &RoundInput| {
+        let mut new_round = current_round.get();
+        new_round.input = input.clone();
+        resolve_terms(new_round);
+    });
+
+    let update_round_amount = create_action(move |amount: &Decimal| {
+        let mut new_round = current_round.get();
+        new_round.amount = *amount;
+        resolve_terms(new_round);
     });
 
-    let update_round_valuation = create_action(move |valuation: &f64| {
+    let update_round_valuation = create_action(move |valuation: &Decimal| {
         let mut new_round = current_round.get();
         new_round.valuation = *valuation;
-        set_current_round.set(new_round);
+        resolve_terms(new_round);
+    });
+
+    let update_price_per_share = create_action(move |price: &Decimal| {
+        let mut new_round = current_round.get();
+        new_round.price_per_share = *price;
+        resolve_terms(new_round);
     });
 
-    let update_equity_sold = create_action(move |equity: &f64| {
+    let update_equity_sold = create_action(move |equity: &Decimal| {
         let mut new_round = current_round.get();
         new_round.equity_sold = *equity;
-        set_current_round.set(new_round);
+        resolve_terms(new_round);
     });
 
+    // Whether a term is one of the two the user sets, rather than derived
+    let is_given = move |term: &str| {
+        let input = current_round.get().input;
+        match term {
+            "amount" => matches!(input, RoundInput::AmountAndPreMoney | RoundInput::AmountAndPrice | RoundInput::AmountAndOwnership),
+            "valuation" => matches!(input, RoundInput::AmountAndPreMoney | RoundInput::PreMoneyAndOwnership),
+            "price" => matches!(input, RoundInput::AmountAndPrice | RoundInput::PriceAndOwnership),
+            _ => matches!(input, RoundInput::AmountAndOwnership | RoundInput::PreMoneyAndOwnership | RoundInput::PriceAndOwnership),
+        }
+    };
+
+    // The messages for one input, listed beneath it
+    let errors_for = move |field: &'static str| {
+        move || field_errors.get().into_iter()
+            .filter(|error| error.field() == field)
+            .map(|error| view! { <p class="mt-1 text-sm text-red-600">{error.to_string()}</p> })
+            .collect::<Vec<_>>()
+    };
+
     let update_deal_type = create_action(move |deal_type: &DealType| {
         let mut new_round = current_round.get();
         new_round.deal_type = deal_type.clone();
         set_current_round.set(new_round);
     });
 
-    let update_esop_allocation = create_action(move |esop: &f64| {
+    let update_safe_terms = create_action(move |terms: &SafeTerms| {
+        let mut new_round = current_round.get();
+        new_round.deal_type = DealType::SAFE(terms.clone());
+        set_current_round.set(new_round);
+    });
+
+    let update_note_terms = create_action(move |terms: &NoteTerms| {
+        let mut new_round = current_round.get();
+        new_round.deal_type = DealType::ConvertibleNote(terms.clone());
+        set_current_round.set(new_round);
+    });
+
+    let update_liquidation_preference = create_action(move |preference: &Option<LiquidationPreference>| {
+        let mut new_round = current_round.get();
+        new_round.liquidation_preference = preference.clone();
+        set_current_round.set(new_round);
+    });
+
+    let update_esop_allocation = create_action(move |esop: &Decimal| {
         let mut new_round = current_round.get();
         new_round.esop_allocation = *esop;
         set_current_round.set(new_round);
     });
 
-    let update_anti_dilution = create_action(move |anti_dilution: &bool| {
+    let update_esop_target = create_action(move |target: &Option<Decimal>| {
+        let mut new_round = current_round.get();
+        new_round.esop_target = *target;
+        set_current_round.set(new_round);
+    });
+
+    let update_anti_dilution = create_action(move |anti_dilution: &AntiDilution| {
         let mut new_round = current_round.get();
-        new_round.anti_dilution = *anti_dilution;
+        new_round.anti_dilution = anti_dilution.clone();
         set_current_round.set(new_round);
     });
 
     let (new_investor, set_new_investor) = create_signal("".to_string());
+    let (new_investor_amount, set_new_investor_amount) = create_signal(Decimal::ZERO);
 
-    let add_investor = create_action(move |investor: &String| {
+    let add_investor = create_action(move |allocation: &InvestorAllocation| {
         let mut new_round = current_round.get();
-        new_round.investors.push(investor.clone());
+        new_round.investors.push(allocation.clone());
         set_current_round.set(new_round);
     });
 
+    // Record an existing investor's pro-rata choice and refresh the allocations from it
+    let update_pro_rata = create_action(move |election: &ProRataElection| {
+        let mut new_round = current_round.get();
+        new_round.pro_rata.retain(|e| e.investor != election.investor);
+        new_round.pro_rata.push(election.clone());
+        company.get().apply_pro_rata(&mut new_round);
+        set_current_round.set(new_round);
+    });
+
+    let update_pro_rata_rights = create_action(move |granted: &bool| {
+        let mut new_round = current_round.get();
+        new_round.pro_rata_rights = *granted;
+        set_current_round.set(new_round);
+    });
+
+    let update_investor_amount = create_action(move |(index, amount): &(usize, Decimal)| {
+        let mut new_round = current_round.get();
+        if let Some(allocation) = new_round.investors.get_mut(*index) {
+            allocation.amount = *amount;
+            set_current_round.set(new_round);
+        }
+    });
+
     let remove_investor = create_action(move |index: &usize| {
         let mut new_round = current_round.get();
         new_round.investors.remove(*index);
@@ -72,7 +196,7 @@ pub fn FundingRounds(
 
     let equity_percentage = create_memo(move |_| {
         let round = current_round.get();
-        (round.amount / post_money_valuation.get()) * 100.0
+        round.amount.checked_div(post_money_valuation.get()).unwrap_or_default() * HUNDRED
     });
 
     view! {
@@ -104,29 +228,99 @@ pub fn FundingRounds(
                         class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
                         value={move || match current_round.get().deal_type {
                             DealType::Equity => "Equity",
-                            DealType::ConvertibleNote => "ConvertibleNote",
-                            DealType::SAFE => "SAFE",
+                            DealType::ConvertibleNote(_) => "ConvertibleNote",
+                            DealType::SAFE(_) => "SAFE",
                             DealType::PreferredStock => "PreferredStock",
                         }}
                         on:change=move |ev| {
                             let value = event_target_value(&ev);
                             let deal_type = match value.as_str() {
                                 "Equity" => DealType::Equity,
-                                "ConvertibleNote" => DealType::ConvertibleNote,
-                                "SAFE" => DealType::SAFE,
+                                "ConvertibleNote" => {
+                                    let round = current_round.get();
+                                    DealType::ConvertibleNote(NoteTerms {
+                                        interest_rate: dec!(6),
+                                        interest_type: InterestType::Simple,
+                                        issue_date: round.close_date,
+                                        maturity_date: round
+                                            .close_date
+                                            .checked_add_months(Months::new(24))
+                                            .unwrap_or(round.close_date),
+                                        valuation_cap: round.valuation,
+                                        discount_rate: dec!(20),
+                                        qualified_financing: dec!(1_000_000),
+                                        maturity_behavior: MaturityBehavior::ConvertAtCap,
+                                    })
+                                }
+                                "SAFE" => {
+                                    let round = current_round.get();
+                                    DealType::SAFE(SafeTerms {
+                                        valuation_cap: round.valuation + round.amount,
+                                        discount_rate: Decimal::ZERO,
+                                        mfn: false,
+                                    })
+                                }
                                 "PreferredStock" => DealType::PreferredStock,
                                 _ => DealType::Equity,
                             };
                             update_deal_type.dispatch(deal_type);
                         }
                     >
-                        <option value="Equity">Equity</option>
+                        <option value="Equity">Common Equity</option>
                         <option value="ConvertibleNote">Convertible Note</option>
                         <option value="SAFE">SAFE</option>
                         <option value="PreferredStock">Preferred Stock</option>
                     </select>
                 </div>
 
+                <div>
+                    <label class="block text-sm font-medium text-gray-700 mb-1">
+                        Close Date
+                    </label>
+                    <input
+                        type="date"
+                        class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                        value={move || current_round.get().close_date.to_string()}
+                        on:change=move |ev| {
+                            if let Ok(date) = event_target_value(&ev).parse::<NaiveDate>() {
+                                update_close_date.dispatch(date);
+                            }
+                        }
+                    />
+                </div>
+
+                <div>
+                    <label class="block text-sm font-medium text-gray-700 mb-1">
+                        Terms Given As
+                    </label>
+                    <select
+                        class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                        value={move || match current_round.get().input {
+                            RoundInput::AmountAndPreMoney => "AmountAndPreMoney",
+                            RoundInput::AmountAndPrice => "AmountAndPrice",
+                            RoundInput::AmountAndOwnership => "AmountAndOwnership",
+                            RoundInput::PreMoneyAndOwnership => "PreMoneyAndOwnership",
+                            RoundInput::PriceAndOwnership => "PriceAndOwnership",
+                        }}
+                        on:change=move |ev| {
+                            let input = match event_target_value(&ev).as_str() {
+                                "AmountAndPrice" => RoundInput::AmountAndPrice,
+                                "AmountAndOwnership" => RoundInput::AmountAndOwnership,
+                                "PreMoneyAndOwnership" => RoundInput::PreMoneyAndOwnership,
+                                "PriceAndOwnership" => RoundInput::PriceAndOwnership,
+                                _ => RoundInput::AmountAndPreMoney,
+                            };
+                            update_round_input.dispatch(input);
+                        }
+                    >
+                        <option value="AmountAndPreMoney">Amount + pre-money</option>
+                        <option value="AmountAndPrice">Amount + price per share</option>
+                        <option value="AmountAndOwnership">Amount + equity sold</option>
+                        <option value="PreMoneyAndOwnership">Pre-money + equity sold</option>
+                        <option value="PriceAndOwnership">Price per share + equity sold</option>
+                    </select>
+                </div>
+
                 <div>
                     <label class="block text-sm font-medium text-gray-700 mb-1">
                         Investment Amount (USD)
@@ -134,13 +328,15 @@ pub fn FundingRounds(
                     <input
                         type="number"
                         step="100000"
+                        disabled={move || !is_given("amount")}
                         class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
                         value={move || current_round.get().amount.to_string()}
                         on:change=move |ev| {
-                            let amount = event_target_value(&ev).parse::<f64>().unwrap_or(0.0);
+                            let amount = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
                             update_round_amount.dispatch(amount);
                         }
                     />
+                    {errors_for("amount")}
                 </div>
 
                 <div>
@@ -150,13 +346,34 @@ pub fn FundingRounds(
                     <input
                         type="number"
                         step="100000"
+                        disabled={move || !is_given("valuation")}
                         class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
                         value={move || current_round.get().valuation.to_string()}
                         on:change=move |ev| {
-                            let valuation = event_target_value(&ev).parse::<f64>().unwrap_or(0.0);
+                            let valuation = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
                             update_round_valuation.dispatch(valuation);
                         }
                     />
+                    {errors_for("valuation")}
+                </div>
+
+                <div>
+                    <label class="block text-sm font-medium text-gray-700 mb-1">
+                        Price per Share (USD)
+                    </label>
+                    <input
+                        type="number"
+                        step="0.01"
+                        min="0"
+                        disabled={move || !is_given("price")}
+                        class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                        value={move || format!("{:.4}", current_round.get().price_per_share)}
+                        on:change=move |ev| {
+                            let price = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
+                            update_price_per_share.dispatch(price);
+                        }
+                    />
+                    {errors_for("price")}
                 </div>
 
                 <div>
@@ -168,13 +385,15 @@ pub fn FundingRounds(
                         step="0.1"
                         min="0"
                         max="100"
+                        disabled={move || !is_given("ownership")}
                         class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
-                        value={move || current_round.get().equity_sold.to_string()}
+                        value={move || format!("{:.2}", current_round.get().equity_sold)}
                         on:change=move |ev| {
-                            let equity = event_target_value(&ev).parse::<f64>().unwrap_or(0.0);
+                            let equity = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
                             update_equity_sold.dispatch(equity);
                         }
                     />
+                    {errors_for("ownership")}
                 </div>
 
                 <div>
@@ -189,27 +408,441 @@ pub fn FundingRounds(
                         class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
                         value={move || current_round.get().esop_allocation.to_string()}
                         on:change=move |ev| {
-                            let esop = event_target_value(&ev).parse::<f64>().unwrap_or(0.0);
+                            let esop = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
                             update_esop_allocation.dispatch(esop);
                         }
                     />
+                    {errors_for("esop_allocation")}
                 </div>
-            </div>
 
-            // Anti-dilution Protection
-            <div class="mb-6">
-                <label class="flex items-center">
+                <div>
+                    <label class="block text-sm font-medium text-gray-700 mb-1">
+                        Target Pool (% post-money, created pre-money)
+                    </label>
                     <input
-                        type="checkbox"
-                        class="h-4 w-4 text-blue-600 focus:ring-blue-500 border-gray-300 rounded"
-                        checked={move || current_round.get().anti_dilution}
+                        type="number"
+                        step="0.1"
+                        min="0"
+                        max="100"
+                        placeholder="No pool shuffle"
+                        class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                        value={move || current_round.get().esop_target.map(|t| t.to_string()).unwrap_or_default()}
                         on:change=move |ev| {
-                            let checked = event_target_checked(&ev);
-                            update_anti_dilution.dispatch(checked);
+                            let target = parse_decimal(&event_target_value(&ev)).filter(|t| *t > Decimal::ZERO);
+                            update_esop_target.dispatch(target);
                         }
                     />
-                    <span class="ml-2 text-sm text-gray-700">Anti-dilution protection</span>
+                    {errors_for("esop_target")}
+                </div>
+            </div>
+
+            // SAFE Terms
+            {move || {
+                if let DealType::SAFE(terms) = current_round.get().deal_type {
+                    let cap_terms = terms.clone();
+                    let discount_terms = terms.clone();
+                    let mfn_terms = terms.clone();
+                    view! {
+                        <div class="grid grid-cols-1 md:grid-cols-2 gap-4 mb-6">
+                            <div>
+                                <label class="block text-sm font-medium text-gray-700 mb-1">
+                                    Post-Money Valuation Cap (USD)
+                                </label>
+                                <input
+                                    type="number"
+                                    step="100000"
+                                    min="0"
+                                    class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                                    value={terms.valuation_cap.to_string()}
+                                    on:change=move |ev| {
+                                        let mut terms = cap_terms.clone();
+                                        terms.valuation_cap = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
+                                        update_safe_terms.dispatch(terms);
+                                    }
+                                />
+                                {errors_for("valuation_cap")}
+                            </div>
+
+                            <div>
+                                <label class="block text-sm font-medium text-gray-700 mb-1">
+                                    Discount (%)
+                                </label>
+                                <input
+                                    type="number"
+                                    step="1"
+                                    min="0"
+                                    max="100"
+                                    class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                                    value={terms.discount_rate.to_string()}
+                                    on:change=move |ev| {
+                                        let mut terms = discount_terms.clone();
+                                        terms.discount_rate = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
+                                        update_safe_terms.dispatch(terms);
+                                    }
+                                />
+                                {errors_for("discount_rate")}
+                            </div>
+
+                            <label class="flex items-center">
+                                <input
+                                    type="checkbox"
+                                    class="h-4 w-4 text-blue-600 focus:ring-blue-500 border-gray-300 rounded"
+                                    checked={terms.mfn}
+                                    on:change=move |ev| {
+                                        let mut terms = mfn_terms.clone();
+                                        terms.mfn = event_target_checked(&ev);
+                                        update_safe_terms.dispatch(terms);
+                                    }
+                                />
+                                <span class="ml-2 text-sm text-gray-700">Most favored nation (MFN)</span>
+                            </label>
+                        </div>
+                    }
+                } else {
+                    view! { <div></div> }
+                }
+            }}
+
+            // Convertible Note Terms
+            {move || {
+                if let DealType::ConvertibleNote(terms) = current_round.get().deal_type {
+                    let rate_terms = terms.clone();
+                    let type_terms = terms.clone();
+                    let maturity_terms = terms.clone();
+                    let cap_terms = terms.clone();
+                    let discount_terms = terms.clone();
+                    let qualified_terms = terms.clone();
+                    let behavior_terms = terms.clone();
+                    view! {
+                        <div class="grid grid-cols-1 md:grid-cols-2 gap-4 mb-6">
+                            <div>
+                                <label class="block text-sm font-medium text-gray-700 mb-1">
+                                    Interest Rate (% per year)
+                                </label>
+                                <input
+                                    type="number"
+                                    step="0.5"
+                                    min="0"
+                                    class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                                    value={terms.interest_rate.to_string()}
+                                    on:change=move |ev| {
+                                        let mut terms = rate_terms.clone();
+                                        terms.interest_rate = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
+                                        update_note_terms.dispatch(terms);
+                                    }
+                                />
+                                {errors_for("interest_rate")}
+                            </div>
+
+                            <div>
+                                <label class="block text-sm font-medium text-gray-700 mb-1">
+                                    Interest
+                                </label>
+                                <select
+                                    class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                                    value={match terms.interest_type {
+                                        InterestType::Simple => "Simple",
+                                        InterestType::Compounding => "Compounding",
+                                    }}
+                                    on:change=move |ev| {
+                                        let mut terms = type_terms.clone();
+                                        terms.interest_type = match event_target_value(&ev).as_str() {
+                                            "Compounding" => InterestType::Compounding,
+                                            _ => InterestType::Simple,
+                                        };
+                                        update_note_terms.dispatch(terms);
+                                    }
+                                >
+                                    <option value="Simple">Simple</option>
+                                    <option value="Compounding">Compounding (annual)</option>
+                                </select>
+                            </div>
+
+                            <div>
+                                <label class="block text-sm font-medium text-gray-700 mb-1">
+                                    Maturity Date
+                                </label>
+                                <input
+                                    type="date"
+                                    class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                                    value={terms.maturity_date.to_string()}
+                                    on:change=move |ev| {
+                                        if let Ok(date) = event_target_value(&ev).parse::<NaiveDate>() {
+                                            let mut terms = maturity_terms.clone();
+                                            terms.maturity_date = date;
+                                            update_note_terms.dispatch(terms);
+                                        }
+                                    }
+                                />
+                                {errors_for("maturity_date")}
+                            </div>
+
+                            <div>
+                                <label class="block text-sm font-medium text-gray-700 mb-1">
+                                    Pre-Money Valuation Cap (USD)
+                                </label>
+                                <input
+                                    type="number"
+                                    step="100000"
+                                    min="0"
+                                    class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                                    value={terms.valuation_cap.to_string()}
+                                    on:change=move |ev| {
+                                        let mut terms = cap_terms.clone();
+                                        terms.valuation_cap = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
+                                        update_note_terms.dispatch(terms);
+                                    }
+                                />
+                                {errors_for("valuation_cap")}
+                            </div>
+
+                            <div>
+                                <label class="block text-sm font-medium text-gray-700 mb-1">
+                                    Discount (%)
+                                </label>
+                                <input
+                                    type="number"
+                                    step="1"
+                                    min="0"
+                                    max="100"
+                                    class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                                    value={terms.discount_rate.to_string()}
+                                    on:change=move |ev| {
+                                        let mut terms = discount_terms.clone();
+                                        terms.discount_rate = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
+                                        update_note_terms.dispatch(terms);
+                                    }
+                                />
+                                {errors_for("discount_rate")}
+                            </div>
+
+                            <div>
+                                <label class="block text-sm font-medium text-gray-700 mb-1">
+                                    Qualified Financing (USD)
+                                </label>
+                                <input
+                                    type="number"
+                                    step="100000"
+                                    min="0"
+                                    class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                                    value={terms.qualified_financing.to_string()}
+                                    on:change=move |ev| {
+                                        let mut terms = qualified_terms.clone();
+                                        terms.qualified_financing = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
+                                        update_note_terms.dispatch(terms);
+                                    }
+                                />
+                            </div>
+
+                            <div>
+                                <label class="block text-sm font-medium text-gray-700 mb-1">
+                                    At Maturity
+                                </label>
+                                <select
+                                    class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                                    value={match terms.maturity_behavior {
+                                        MaturityBehavior::ConvertAtCap => "ConvertAtCap",
+                                        MaturityBehavior::Repay => "Repay",
+                                        MaturityBehavior::Extend { .. } => "Extend",
+                                    }}
+                                    on:change=move |ev| {
+                                        let mut terms = behavior_terms.clone();
+                                        terms.maturity_behavior = match event_target_value(&ev).as_str() {
+                                            "Repay" => MaturityBehavior::Repay,
+                                            "Extend" => MaturityBehavior::Extend { months: 12 },
+                                            _ => MaturityBehavior::ConvertAtCap,
+                                        };
+                                        update_note_terms.dispatch(terms);
+                                    }
+                                >
+                                    <option value="ConvertAtCap">Convert at cap</option>
+                                    <option value="Repay">Repay</option>
+                                    <option value="Extend">Extend 12 months</option>
+                                </select>
+                            </div>
+                        </div>
+                    }
+                } else {
+                    view! { <div></div> }
+                }
+            }}
+
+            // Liquidation Preference
+            {move || {
+                let round = current_round.get();
+                // Only preferred stock carries a preference; equity rounds issue common
+                if !matches!(round.deal_type, DealType::PreferredStock) {
+                    return view! { <div></div> };
+                }
+
+                let preference = round.liquidation_preference.clone();
+                let multiple_pref = preference.clone();
+                let participation_pref = preference.clone();
+                let cap_pref = preference.clone();
+                let seniority_pref = preference.clone();
+                view! {
+                    <div class="mb-6">
+                        <label class="flex items-center mb-3">
+                            <input
+                                type="checkbox"
+                                class="h-4 w-4 text-blue-600 focus:ring-blue-500 border-gray-300 rounded"
+                                checked={preference.is_some()}
+                                on:change=move |ev| {
+                                    let preference = if event_target_checked(&ev) {
+                                        Some(LiquidationPreference {
+                                            multiple: Decimal::ONE,
+                                            participation: Participation::NonParticipating,
+                                            seniority: Seniority::Senior,
+                                        })
+                                    } else {
+                                        None
+                                    };
+                                    update_liquidation_preference.dispatch(preference);
+                                }
+                            />
+                            <span class="ml-2 text-sm text-gray-700">Liquidation preference</span>
+                        </label>
+
+                        {match preference {
+                            Some(preference) => view! {
+                                <div class="grid grid-cols-1 md:grid-cols-3 gap-4">
+                                    <div>
+                                        <label class="block text-sm font-medium text-gray-700 mb-1">
+                                            Multiple (x)
+                                        </label>
+                                        <input
+                                            type="number"
+                                            step="0.5"
+                                            min="0"
+                                            class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                                            value={preference.multiple.to_string()}
+                                            on:change=move |ev| {
+                                                let mut preference = multiple_pref.clone();
+                                                if let Some(p) = preference.as_mut() {
+                                                    p.multiple = parse_decimal(&event_target_value(&ev)).unwrap_or(Decimal::ONE);
+                                                }
+                                                update_liquidation_preference.dispatch(preference);
+                                            }
+                                        />
+                                    </div>
+
+                                    <div>
+                                        <label class="block text-sm font-medium text-gray-700 mb-1">
+                                            Participation
+                                        </label>
+                                        <select
+                                            class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                                            value={match preference.participation {
+                                                Participation::NonParticipating => "NonParticipating",
+                                                Participation::Participating => "Participating",
+                                                Participation::CappedParticipating { .. } => "CappedParticipating",
+                                            }}
+                                            on:change=move |ev| {
+                                                let mut preference = participation_pref.clone();
+                                                if let Some(p) = preference.as_mut() {
+                                                    p.participation = match event_target_value(&ev).as_str() {
+                                                        "Participating" => Participation::Participating,
+                                                        "CappedParticipating" => Participation::CappedParticipating { cap_multiple: dec!(3) },
+                                                        _ => Participation::NonParticipating,
+                                                    };
+                                                }
+                                                update_liquidation_preference.dispatch(preference);
+                                            }
+                                        >
+                                            <option value="NonParticipating">Non-participating</option>
+                                            <option value="Participating">Participating</option>
+                                            <option value="CappedParticipating">Participating (capped)</option>
+                                        </select>
+                                    </div>
+
+                                    <div>
+                                        <label class="block text-sm font-medium text-gray-700 mb-1">
+                                            Seniority
+                                        </label>
+                                        <select
+                                            class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                                            value={match preference.seniority {
+                                                Seniority::Senior => "Senior",
+                                                Seniority::PariPassu => "PariPassu",
+                                            }}
+                                            on:change=move |ev| {
+                                                let mut preference = seniority_pref.clone();
+                                                if let Some(p) = preference.as_mut() {
+                                                    p.seniority = match event_target_value(&ev).as_str() {
+                                                        "PariPassu" => Seniority::PariPassu,
+                                                        _ => Seniority::Senior,
+                                                    };
+                                                }
+                                                update_liquidation_preference.dispatch(preference);
+                                            }
+                                        >
+                                            <option value="Senior">Senior (stacked)</option>
+                                            <option value="PariPassu">Pari passu</option>
+                                        </select>
+                                    </div>
+
+                                    {if let Participation::CappedParticipating { cap_multiple } = preference.participation {
+                                        view! {
+                                            <div>
+                                                <label class="block text-sm font-medium text-gray-700 mb-1">
+                                                    Participation Cap (x)
+                                                </label>
+                                                <input
+                                                    type="number"
+                                                    step="0.5"
+                                                    min="1"
+                                                    class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                                                    value={cap_multiple.to_string()}
+                                                    on:change=move |ev| {
+                                                        let mut preference = cap_pref.clone();
+                                                        if let Some(p) = preference.as_mut() {
+                                                            let cap_multiple = parse_decimal(&event_target_value(&ev)).unwrap_or(dec!(3));
+                                                            p.participation = Participation::CappedParticipating { cap_multiple };
+                                                        }
+                                                        update_liquidation_preference.dispatch(preference);
+                                                    }
+                                                />
+                                            </div>
+                                        }
+                                    } else {
+                                        view! { <div></div> }
+                                    }}
+                                </div>
+                            },
+                            None => view! { <div></div> },
+                        }}
+                    </div>
+                }
+            }}
+
+            // Anti-dilution Protection
+            <div class="mb-6">
+                <label class="block text-sm font-medium text-gray-700 mb-1">
+                    Anti-dilution Protection
                 </label>
+                <select
+                    class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                    value={move || match current_round.get().anti_dilution {
+                        AntiDilution::None => "None",
+                        AntiDilution::FullRatchet => "FullRatchet",
+                        AntiDilution::BroadBasedWeightedAverage => "BroadBasedWeightedAverage",
+                        AntiDilution::NarrowBasedWeightedAverage => "NarrowBasedWeightedAverage",
+                    }}
+                    on:change=move |ev| {
+                        let anti_dilution = match event_target_value(&ev).as_str() {
+                            "FullRatchet" => AntiDilution::FullRatchet,
+                            "BroadBasedWeightedAverage" => AntiDilution::BroadBasedWeightedAverage,
+                            "NarrowBasedWeightedAverage" => AntiDilution::NarrowBasedWeightedAverage,
+                            _ => AntiDilution::None,
+                        };
+                        update_anti_dilution.dispatch(anti_dilution);
+                    }
+                >
+                    <option value="None">None</option>
+                    <option value="FullRatchet">Full ratchet</option>
+                    <option value="BroadBasedWeightedAverage">Broad-based weighted average</option>
+                    <option value="NarrowBasedWeightedAverage">Narrow-based weighted average</option>
+                </select>
             </div>
 
             // Investors
@@ -225,12 +858,34 @@ pub fn FundingRounds(
                             set_new_investor.set(event_target_value(&ev));
                         }
                     />
+                    <input
+                        type="number"
+                        step="10000"
+                        min="0"
+                        placeholder="Check ($)"
+                        class="w-32 px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                        value={move || new_investor_amount.get().to_string()}
+                        on:change=move |ev| {
+                            set_new_investor_amount.set(parse_decimal(&event_target_value(&ev)).unwrap_or_default());
+                        }
+                    />
                     <button
                         class="px-4 py-2 bg-green-600 text-white rounded-md hover:bg-green-700 focus:outline-none focus:ring-2 focus:ring-green-500"
                         on:click=move |_| {
                             if !new_investor.get().is_empty() {
-                                add_investor.dispatch(new_investor.get());
+                                // An empty check takes whatever is left of the round
+                                let round = current_round.get();
+                                let amount = if new_investor_amount.get() > Decimal::ZERO {
+                                    new_investor_amount.get()
+                                } else {
+                                    (round.amount - round.allocated_amount()).max(Decimal::ZERO)
+                                };
+                                add_investor.dispatch(InvestorAllocation {
+                                    investor: new_investor.get(),
+                                    amount,
+                                });
                                 set_new_investor.set("".to_string());
+                                set_new_investor_amount.set(Decimal::ZERO);
                             }
                         }
                     >
@@ -240,10 +895,21 @@ pub fn FundingRounds(
                 
                 <div class="space-y-2">
                     {move || {
-                        current_round.get().investors.iter().enumerate().map(|(index, investor)| {
+                        current_round.get().investors.iter().enumerate().map(|(index, allocation)| {
                             view! {
                                 <div key=index class="flex items-center justify-between bg-gray-50 px-3 py-2 rounded">
-                                    <span class="text-sm">{investor}</span>
+                                    <span class="text-sm flex-1">{allocation.investor.clone()}</span>
+                                    <input
+                                        type="number"
+                                        step="10000"
+                                        min="0"
+                                        class="w-32 mr-3 px-2 py-1 border border-gray-300 rounded text-sm"
+                                        value={allocation.amount.to_string()}
+                                        on:change=move |ev| {
+                                            let amount = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
+                                            update_investor_amount.dispatch((index, amount));
+                                        }
+                                    />
                                     <button
                                         class="text-red-600 hover:text-red-800 text-sm"
                                         on:click=move |_| remove_investor.dispatch(index)
@@ -255,15 +921,127 @@ pub fn FundingRounds(
                         }).collect::<Vec<_>>()
                     }}
                 </div>
+
+                {move || {
+                    let round = current_round.get();
+                    let unallocated = round.amount - round.allocated_amount();
+                    if unallocated.abs() > dec!(0.01) {
+                        view! {
+                            <div class="mt-2 text-sm text-yellow-800">
+                                {if unallocated > Decimal::ZERO {
+                                    format!("{} of the round is not yet allocated", format_currency(unallocated))
+                                } else {
+                                    format!("Checks exceed the round by {}", format_currency(-unallocated))
+                                }}
+                            </div>
+                        }
+                    } else {
+                        view! { <div></div> }
+                    }
+                }}
+                {errors_for("investors")}
             </div>
 
+            // Pro Rata
+            {move || {
+                let company = company.get();
+                let round = current_round.get();
+                let holders: Vec<_> = company.investors.iter().filter(|i| i.pro_rata_rights).cloned().collect();
+                if holders.is_empty() {
+                    return view! { <div></div> };
+                }
+
+                view! {
+                    <div class="mb-6">
+                        <label class="block text-sm font-medium text-gray-700 mb-2">Pro-Rata Participation</label>
+                        <div class="space-y-2">
+                            {holders.into_iter().map(|investor| {
+                                let entitlement = company.pro_rata_entitlement(&investor.name, round.amount);
+                                let participation = round.pro_rata.iter()
+                                    .find(|e| e.investor == investor.name)
+                                    .map(|e| e.participation.clone());
+                                let select_name = investor.name.clone();
+                                let amount_name = investor.name.clone();
+
+                                view! {
+                                    <div class="bg-gray-50 px-3 py-2 rounded text-sm">
+                                        <div class="flex items-center justify-between">
+                                            <span class="font-medium">{investor.name.clone()}</span>
+                                            <span class="text-gray-600">{format!("Entitled to {}", format_currency(entitlement))}</span>
+                                        </div>
+                                        <div class="flex items-center space-x-2 mt-2">
+                                            <select
+                                                class="flex-1 px-2 py-1 border border-gray-300 rounded"
+                                                value={match participation {
+                                                    Some(ProRataParticipation::Full) => "Full",
+                                                    Some(ProRataParticipation::Partial { .. }) => "Partial",
+                                                    Some(ProRataParticipation::Declined) => "Declined",
+                                                    None => "",
+                                                }}
+                                                on:change=move |ev| {
+                                                    let participation = match event_target_value(&ev).as_str() {
+                                                        "Full" => ProRataParticipation::Full,
+                                                        "Partial" => ProRataParticipation::Partial { amount: entitlement / dec!(2) },
+                                                        _ => ProRataParticipation::Declined,
+                                                    };
+                                                    update_pro_rata.dispatch(ProRataElection {
+                                                        investor: select_name.clone(),
+                                                        participation,
+                                                    });
+                                                }
+                                            >
+                                                <option value="">Not elected</option>
+                                                <option value="Full">Full</option>
+                                                <option value="Partial">Partial</option>
+                                                <option value="Declined">Declined</option>
+                                            </select>
+                                            {if let Some(ProRataParticipation::Partial { amount }) = participation {
+                                                view! {
+                                                    <input
+                                                        type="number"
+                                                        step="10000"
+                                                        min="0"
+                                                        class="w-32 px-2 py-1 border border-gray-300 rounded"
+                                                        value={amount.to_string()}
+                                                        on:change=move |ev| {
+                                                            let amount = parse_decimal(&event_target_value(&ev)).unwrap_or_default();
+                                                            update_pro_rata.dispatch(ProRataElection {
+                                                                investor: amount_name.clone(),
+                                                                participation: ProRataParticipation::Partial { amount },
+                                                            });
+                                                        }
+                                                    />
+                                                }
+                                            } else {
+                                                view! { <input type="hidden" /> }
+                                            }}
+                                        </div>
+                                    </div>
+                                }
+                            }).collect::<Vec<_>>()}
+                        </div>
+                        {errors_for("pro_rata")}
+                    </div>
+                }
+            }}
+
+            <label class="flex items-center mb-6">
+                <input
+                    type="checkbox"
+                    class="h-4 w-4 text-blue-600 focus:ring-blue-500 border-gray-300 rounded"
+                    checked={move || current_round.get().pro_rata_rights}
+                    on:change=move |ev| update_pro_rata_rights.dispatch(event_target_checked(&ev))
+                />
+                <span class="ml-2 text-sm text-gray-700">Investors in this round get pro-rata rights</span>
+            </label>
+
             // Round Summary
             <div class="bg-blue-50 border border-blue-200 rounded-lg p-4 mb-6">
                 <h3 class="font-medium text-blue-800 mb-2">Round Summary</h3>
                 <div class="grid grid-cols-2 gap-4 text-sm">
                     <div>
                         <span class="text-blue-600">Post-Money Valuation:</span>
-                        <span class="ml-2 font-medium">{move || format!("${:.1}M", post_money_valuation.get() / 1_000_000.0)}</span>
+                        <span class="ml-2 font-medium">{move || format!("${:.1}M", post_money_valuation.get() / dec!(1_000_000))}</span>
                     </div>
                     <div>
                         <span class="text-blue-600">Equity Percentage:</span>
@@ -276,28 +1054,125 @@ pub fn FundingRounds(
             <button
                 class="w-full px-4 py-3 bg-blue-600 text-white rounded-md hover:bg-blue-700 focus:outline-none focus:ring-2 focus:ring-blue-500 font-medium"
                 on:click=move |_| {
-                    add_round.dispatch(current_round.get());
+                    match editing.get() {
+                        // Replaying prices the edited round at its own place in the sequence
+                        Some(index) => {
+                            let round = current_round.get();
+                            match validate_funding_round(&round) {
+                                Ok(()) => {
+                                    set_field_errors.set(Vec::new());
+                                    edit_rounds(&|c: &mut Company| c.update_round(index, round.clone()));
+                                    if round_error.get().is_none() {
+                                        set_editing.set(None);
+                                    }
+                                }
+                                Err(errors) => set_field_errors.set(errors),
+                            }
+                        }
+                        None => {
+                            // Price against the shares on the close date, which for a
+                            // backdated round is not today's count
+                            let mut round = current_round.get();
+                            let pre_round_shares = company
+                                .get()
+                                .fully_diluted_shares_on(round.close_date)
+                                .unwrap_or(company.get().total_shares);
+                            let checked = resolve_round_terms(&mut round, pre_round_shares)
+                                .map_err(|error| vec![error])
+                                .and_then(|_| validate_funding_round(&round));
+                            match checked {
+                                Ok(()) => {
+                                    set_field_errors.set(Vec::new());
+                                    // The replay can still refuse it, say a backdated round
+                                    // that no longer fits; the reason shows beneath the button
+                                    edit_rounds(&|c: &mut Company| c.add_round(round.clone()));
+                                }
+                                Err(errors) => set_field_errors.set(errors),
+                            }
+                        }
+                    }
                 }
             >
-                Add Funding Round
+                {move || if editing.get().is_some() { "Save Round" } else { "Add Funding Round" }}
             </button>
+            {move || editing.get().map(|_| view! {
+                <button
+                    class="w-full mt-2 px-4 py-2 border border-gray-300 text-gray-700 rounded-md hover:bg-gray-50"
+                    on:click=move |_| {
+                        set_editing.set(None);
+                        set_round_error.set(None);
+                        set_field_errors.set(Vec::new());
+                    }
+                >
+                    Cancel Editing
+                </button>
+            })}
+            {move || round_error.get().map(|message| view! {
+                <div class="mt-2 p-3 bg-red-50 border border-red-200 rounded text-sm text-red-800">{message}</div>
+            })}
 
             // Existing Rounds
             {move || {
                 let rounds = company.get().funding_rounds.clone();
+                let company_errors = validate_company(&company.get()).err().unwrap_or_default();
                 if !rounds.is_empty() {
                     view! {
                         <div class="mt-6">
                             <h3 class="text-lg font-medium text-gray-800 mb-3">Existing Rounds</h3>
                             <div class="space-y-2">
-                                {rounds.iter().map(|round| {
+                                {rounds.iter().enumerate().map(|(index, round)| {
+                                    let last = rounds.len() - 1;
+                                    let editable = round.clone();
+                                    let problems: Vec<String> = company_errors.iter()
+                                        .filter_map(|error| match error {
+                                            ValidationError::InRound { index: i, error, .. } if *i == index => Some(error.to_string()),
+                                            _ => None,
+                                        })
+                                        .collect();
                                     view! {
-                                        <div class="bg-gray-50 px-3 py-2 rounded text-sm">
+                                        <div class={if editing.get() == Some(index) { "bg-blue-50 px-3 py-2 rounded text-sm" } else { "bg-gray-50 px-3 py-2 rounded text-sm" }}>
                                             <span class="font-medium">{round.name.clone()}</span>
                                             <span class="text-gray-600 ml-2">
-                                                - {format!("${:.1}M", round.amount / 1_000_000.0)} at 
-                                                {format!("${:.1}M", round.valuation / 1_000_000.0)} valuation
+                                                - {format!("${:.1}M", round.amount / dec!(1_000_000))} at 
+                                                {format!("${:.1}M", round.valuation / dec!(1_000_000))} valuation
                                             </span>
+                                            {problems.into_iter().map(|message| view! {
+                                                <p class="mt-1 text-xs text-red-600">{message}</p>
+                                            }).collect::<Vec<_>>()}
+                                            <div class="mt-1 flex space-x-3 text-xs">
+                                                <button
+                                                    class="text-blue-600 hover:text-blue-800"
+                                                    on:click=move |_| {
+                                                        set_current_round.set(editable.clone());
+                                                        set_editing.set(Some(index));
+                                                    }
+                                                >
+                                                    Edit
+                                                </button>
+                                                <button
+                                                    class="text-gray-600 hover:text-gray-800 disabled:opacity-50"
+                                                    disabled={index == 0}
+                                                    on:click=move |_| edit_rounds(&|c: &mut Company| c.move_round(index, index - 1))
+                                                >
+                                                    Move Up
+                                                </button>
+                                                <button
+                                                    class="text-gray-600 hover:text-gray-800 disabled:opacity-50"
+                                                    disabled={index == last}
+                                                    on:click=move |_| edit_rounds(&|c: &mut Company| c.move_round(index, index + 1))
+                                                >
+                                                    Move Down
+                                                </button>
+                                                <button
+                                                    class="text-red-600 hover:text-red-800"
+                                                    on:click=move |_| {
+                                                        edit_rounds(&|c: &mut Company| c.remove_round(index));
+                                                        set_editing.set(None);
+                                                    }
+                                                >
+                                                    Delete
+                                                </button>
+                                            </div>
                                         </div>
                                     }
                                 }).collect::<Vec<_>>()}
@@ -3,9 +3,15 @@ pub mod funding_rounds;
 pub mod cap_table;
 pub mod exit_scenarios;
 pub mod ownership_chart;
+pub mod option_grants;
+pub mod company_history;
+pub mod scenario_library;
 
 pub use founder_setup::FounderSetup;
 pub use funding_rounds::FundingRounds;
 pub use cap_table::CapTable;
 pub use exit_scenarios::ExitScenarios;
 pub use ownership_chart::OwnershipChart;
+pub use option_grants::OptionGrants;
+pub use company_history::CompanyHistory;
+pub use scenario_library::ScenarioLibrary;
@@ -0,0 +1,270 @@
+use leptos::*;
+use chrono::NaiveDate;
+use crate::models::{Acceleration, Company, OptionGrant, VestingCadence, VestingSchedule};
+use crate::money::{parse_decimal, Decimal};
+use crate::utils::{format_currency, format_percentage, format_shares};
+use crate::validation::validate_company;
+
+#[component]
+pub fn option_grants(
+    company: ReadSignal<Company>,
+    set_company: WriteSignal<Company>,
+) -> impl IntoView {
+    let (error, set_error) = create_signal(None::<String>);
+
+    let (grantee, set_grantee) = create_signal("".to_string());
+    let (shares, set_shares) = create_signal(0u64);
+    let (strike_price, set_strike_price) = create_signal(Decimal::ZERO);
+    let (grant_date, set_grant_date) = create_signal(chrono::Local::now().date_naive());
+    let (board_approved, set_board_approved) = create_signal(true);
+    let (action_date, set_action_date) = create_signal(chrono::Local::now().date_naive());
+
+    let add_grant = create_action(move |grant: &OptionGrant| {
+        let mut new_company = company.get();
+        match new_company.grant_options(grant.clone()) {
+            Ok(()) => {
+                set_error.set(None);
+                set_company.set(new_company);
+            }
+            Err(message) => set_error.set(Some(message)),
+        }
+    });
+
+    let approve_grant = create_action(move |index: &usize| {
+        let mut new_company = company.get();
+        new_company.approve_grant(*index);
+        set_company.set(new_company);
+    });
+
+    let exercise_vested = create_action(move |index: &usize| {
+        let mut new_company = company.get();
+        let exercisable = new_company
+            .esop_pool
+            .grants
+            .get(*index)
+            .map(|g| g.exercisable_shares(action_date.get()))
+            .unwrap_or(0);
+        match new_company.exercise_options(*index, exercisable, action_date.get()) {
+            Ok(()) => {
+                set_error.set(None);
+                set_company.set(new_company);
+            }
+            Err(message) => set_error.set(Some(message)),
+        }
+    });
+
+    let terminate_grant = create_action(move |index: &usize| {
+        let mut new_company = company.get();
+        new_company.terminate_grant(*index, action_date.get());
+        set_company.set(new_company);
+    });
+
+    view! {
+        <div class="bg-white rounded-lg shadow-lg p-6">
+            <h2 class="text-2xl font-bold text-gray-800 mb-6">Option Grants</h2>
+
+            // Pool Summary
+            {move || {
+                let pool = company.get().esop_pool;
+                let available = pool.total_allocation - pool.allocated - pool.reserved;
+                view! {
+                    <div class="grid grid-cols-2 gap-3 mb-6 text-sm">
+                        <div class="bg-green-50 border border-green-200 rounded p-3">
+                            <div class="text-green-600">Pool</div>
+                            <div class="text-lg font-bold">{format_percentage(pool.total_allocation)}</div>
+                        </div>
+                        <div class="bg-green-50 border border-green-200 rounded p-3">
+                            <div class="text-green-600">Granted</div>
+                            <div class="text-lg font-bold">{format_percentage(pool.allocated)}</div>
+                        </div>
+                        <div class="bg-green-50 border border-green-200 rounded p-3">
+                            <div class="text-green-600">Promised</div>
+                            <div class="text-lg font-bold">{format_percentage(pool.reserved)}</div>
+                        </div>
+                        <div class="bg-green-50 border border-green-200 rounded p-3">
+                            <div class="text-green-600">Available</div>
+                            <div class="text-lg font-bold">{format_percentage(available)}</div>
+                            <div class="text-xs text-gray-500">
+                                {format!("{} shares", format_shares(company.get().available_pool_shares()))}
+                            </div>
+                        </div>
+                    </div>
+                }
+            }}
+            {move || validate_company(&company.get()).err().unwrap_or_default().into_iter()
+                .filter(|error| error.field() == "esop_pool")
+                .map(|error| view! {
+                    <div class="mb-6 p-3 bg-red-50 border border-red-200 rounded text-sm text-red-800">{error.to_string()}</div>
+                })
+                .collect::<Vec<_>>()}
+
+            // New Grant
+            <div class="space-y-3 mb-6">
+                <input
+                    type="text"
+                    placeholder="Grantee"
+                    class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                    value=grantee
+                    on:change=move |ev| set_grantee.set(event_target_value(&ev))
+                />
+                <div class="grid grid-cols-2 gap-3">
+                    <div>
+                        <label class="block text-sm font-medium text-gray-700 mb-1">Options</label>
+                        <input
+                            type="number"
+                            min="0"
+                            class="w-full px-3 py-2 border border-gray-300 rounded-md"
+                            value={move || shares.get().to_string()}
+                            on:change=move |ev| set_shares.set(event_target_value(&ev).parse::<u64>().unwrap_or(0))
+                        />
+                    </div>
+                    <div>
+                        <label class="block text-sm font-medium text-gray-700 mb-1">Strike ($)</label>
+                        <input
+                            type="number"
+                            step="0.01"
+                            min="0"
+                            class="w-full px-3 py-2 border border-gray-300 rounded-md"
+                            value={move || strike_price.get().to_string()}
+                            on:change=move |ev| set_strike_price.set(parse_decimal(&event_target_value(&ev)).unwrap_or_default())
+                        />
+                    </div>
+                    <div>
+                        <label class="block text-sm font-medium text-gray-700 mb-1">Grant date</label>
+                        <input
+                            type="date"
+                            class="w-full px-3 py-2 border border-gray-300 rounded-md"
+                            value={move || grant_date.get().to_string()}
+                            on:change=move |ev| {
+                                if let Ok(date) = event_target_value(&ev).parse::<NaiveDate>() {
+                                    set_grant_date.set(date);
+                                }
+                            }
+                        />
+                    </div>
+                    <div class="flex items-end">
+                        <label class="flex items-center">
+                            <input
+                                type="checkbox"
+                                class="h-4 w-4 text-blue-600 focus:ring-blue-500 border-gray-300 rounded"
+                                checked=board_approved
+                                on:change=move |ev| set_board_approved.set(event_target_checked(&ev))
+                            />
+                            <span class="ml-2 text-sm text-gray-700">Board approved</span>
+                        </label>
+                    </div>
+                </div>
+                <button
+                    class="w-full px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 focus:outline-none focus:ring-2 focus:ring-blue-500"
+                    on:click=move |_| {
+                        // Standard four-year schedule with a one-year cliff from the grant date
+                        add_grant.dispatch(OptionGrant {
+                            grantee: grantee.get(),
+                            shares: shares.get(),
+                            strike_price: strike_price.get(),
+                            grant_date: grant_date.get(),
+                            vesting: Some(VestingSchedule {
+                                start_date: grant_date.get(),
+                                cliff_months: 12,
+                                total_months: 48,
+                                cadence: VestingCadence::Monthly,
+                                acceleration: Acceleration::None,
+                            }),
+                            board_approved: board_approved.get(),
+                            exercised_shares: 0,
+                            forfeited_shares: 0,
+                        });
+                    }
+                >
+                    Grant Options
+                </button>
+                {move || error.get().map(|message| view! {
+                    <div class="p-3 bg-red-50 border border-red-200 rounded text-sm text-red-800">{message}</div>
+                })}
+            </div>
+
+            // Grants
+            {move || {
+                let grants = company.get().esop_pool.grants.clone();
+                if grants.is_empty() {
+                    view! { <div></div> }
+                } else {
+                    view! {
+                        <div>
+                            <div class="flex items-center justify-between mb-3">
+                                <h3 class="text-lg font-medium text-gray-700">Grants</h3>
+                                <input
+                                    type="date"
+                                    class="px-2 py-1 border border-gray-300 rounded text-sm"
+                                    value={action_date.get().to_string()}
+                                    on:change=move |ev| {
+                                        if let Ok(date) = event_target_value(&ev).parse::<NaiveDate>() {
+                                            set_action_date.set(date);
+                                        }
+                                    }
+                                />
+                            </div>
+                            <div class="space-y-3">
+                                {grants.iter().enumerate().map(|(index, grant)| {
+                                    let exercisable = grant.exercisable_shares(action_date.get());
+                                    view! {
+                                        <div key=index class="bg-gray-50 rounded-lg p-4 text-sm">
+                                            <div class="flex items-center justify-between mb-2">
+                                                <span class="font-medium text-gray-900">{grant.grantee.clone()}</span>
+                                                <span class="text-gray-500">
+                                                    {format!("{} @ {}", format_shares(grant.shares), format_currency(grant.strike_price))}
+                                                </span>
+                                            </div>
+                                            <div class="text-gray-600">
+                                                {format!(
+                                                    "Outstanding {} · exercisable {} · exercised {} · forfeited {}",
+                                                    format_shares(grant.outstanding_shares()),
+                                                    format_shares(exercisable),
+                                                    format_shares(grant.exercised_shares),
+                                                    format_shares(grant.forfeited_shares),
+                                                )}
+                                            </div>
+                                            <div class="mt-2 flex space-x-3">
+                                                {if grant.board_approved {
+                                                    view! {
+                                                        <button
+                                                            class="text-blue-600 hover:text-blue-800"
+                                                            on:click=move |_| exercise_vested.dispatch(index)
+                                                        >
+                                                            Exercise vested
+                                                        </button>
+                                                    }
+                                                } else {
+                                                    view! {
+                                                        <button
+                                                            class="text-blue-600 hover:text-blue-800"
+                                                            on:click=move |_| approve_grant.dispatch(index)
+                                                        >
+                                                            Approve
+                                                        </button>
+                                                    }
+                                                }}
+                                                {if grant.outstanding_shares() > exercisable {
+                                                    view! {
+                                                        <button
+                                                            class="text-red-600 hover:text-red-800"
+                                                            on:click=move |_| terminate_grant.dispatch(index)
+                                                        >
+                                                            Terminate
+                                                        </button>
+                                                    }
+                                                } else {
+                                                    view! { <button class="hidden"></button> }
+                                                }}
+                                            </div>
+                                        </div>
+                                    }
+                                }).collect::<Vec<_>>()}
+                            </div>
+                        </div>
+                    }
+                }
+            }}
+        </div>
+    }
+}
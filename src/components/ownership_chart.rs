@@ -1,6 +1,7 @@
 use leptos::*;
-use crate::models::Company;
-use crate::utils::{format_percentage, format_currency};
+use crate::models::{AntiDilution, Company};
+use crate::money::{Decimal, HUNDRED};
+use crate::utils::{format_percentage, format_currency, format_shares};
 
 #[component]
 pub fn ownership_chart(company: ReadSignal<Company>) -> impl IntoView {
@@ -38,7 +39,7 @@ pub fn ownership_chart(company: ReadSignal<Company>) -> impl IntoView {
                             <h3 class="text-lg font-medium text-gray-700">Funding Rounds</h3>
                             {rounds.iter().enumerate().map(|(index, round)| {
                                 let post_money = round.valuation + round.amount;
-                                let equity_percentage = (round.amount / post_money) * 100.0;
+                                let equity_percentage = round.amount.checked_div(post_money).unwrap_or_default() * HUNDRED;
                                 
                                 view! {
                                     <div key=index class="border-l-4 border-blue-500 pl-6 relative">
@@ -75,8 +76,59 @@ pub fn ownership_chart(company: ReadSignal<Company>) -> impl IntoView {
                                                 </div>
                                             </div>
                                             
+                                            // Price and Share Counts
+                                            {match company.get().round_history.get(index).cloned() {
+                                                Some(snapshot) => {
+                                                    let issued = snapshot.shares_issued.clone();
+                                                    let breakdown: Vec<String> = [
+                                                        ("new money", issued.new_money),
+                                                        ("SAFE conversion", issued.safe_conversion),
+                                                        ("note conversion", issued.note_conversion),
+                                                        ("option pool", issued.option_pool),
+                                                        ("anti-dilution", issued.anti_dilution),
+                                                    ]
+                                                    .iter()
+                                                    .filter(|(_, shares)| *shares > 0)
+                                                    .map(|(label, shares)| format!("{} {}", format_shares(*shares), label))
+                                                    .collect();
+
+                                                    view! {
+                                                        <div class="mb-4 p-3 bg-white border border-blue-100 rounded text-sm">
+                                                            <div class="grid grid-cols-1 md:grid-cols-3 gap-2">
+                                                                <div>
+                                                                    <span class="text-blue-600">Price per Share:</span>
+                                                                    <span class="ml-2 font-semibold">
+                                                                        {if snapshot.price_per_share > Decimal::ZERO {
+                                                                            format!("${:.4}", snapshot.price_per_share)
+                                                                        } else {
+                                                                            "—".to_string()
+                                                                        }}
+                                                                    </span>
+                                                                </div>
+                                                                <div>
+                                                                    <span class="text-blue-600">Pre-Round FD:</span>
+                                                                    <span class="ml-2 font-semibold">{format_shares(snapshot.pre_round_shares)}</span>
+                                                                </div>
+                                                                <div>
+                                                                    <span class="text-blue-600">Post-Round FD:</span>
+                                                                    <span class="ml-2 font-semibold">{format_shares(snapshot.post_round_shares)}</span>
+                                                                </div>
+                                                            </div>
+                                                            <div class="mt-2 text-gray-600">
+                                                                {if breakdown.is_empty() {
+                                                                    "No shares issued".to_string()
+                                                                } else {
+                                                                    format!("New shares: {}", breakdown.join(" · "))
+                                                                }}
+                                                            </div>
+                                                        </div>
+                                                    }
+                                                }
+                                                None => view! { <div></div> },
+                                            }}
+
                                             // ESOP Allocation
-                                            {if round.esop_allocation > 0.0 {
+                                            {if round.esop_allocation > Decimal::ZERO {
                                                 view! {
                                                     <div class="mb-3 p-3 bg-green-100 border border-green-200 rounded">
                                                         <span class="text-green-700 text-sm">
@@ -87,15 +139,30 @@ pub fn ownership_chart(company: ReadSignal<Company>) -> impl IntoView {
                                             } else {
                                                 view! { <div></div> }
                                             }}
+
+                                            // Pre-money Pool Shuffle
+                                            {match (round.esop_target, company.get().round_history.get(index).cloned()) {
+                                                (Some(target), Some(snapshot)) => view! {
+                                                    <div class="mb-3 p-3 bg-green-100 border border-green-200 rounded text-sm text-green-700">
+                                                        <div>
+                                                            {format!("Pool topped up to {} post-money: +{} shares in the pre-money", format_percentage(target), format_shares(snapshot.pool_shares_added))}
+                                                        </div>
+                                                        <div>
+                                                            "Effective pre-money: " {format_currency(snapshot.effective_pre_money)}
+                                                        </div>
+                                                    </div>
+                                                },
+                                                _ => view! { <div></div> },
+                                            }}
                                             
                                             // Investors
                                             <div class="mb-3">
                                                 <span class="text-blue-600 text-sm">Investors:</span>
                                                 <div class="mt-1 flex flex-wrap gap-2">
-                                                    {round.investors.iter().map(|investor| {
+                                                    {round.investors.iter().map(|allocation| {
                                                         view! {
                                                             <span class="inline-flex items-center px-2.5 py-0.5 rounded-full text-xs font-medium bg-blue-100 text-blue-800">
-                                                                {investor}
+                                                                {format!("{} · {}", allocation.investor, format_currency(allocation.amount))}
                                                             </span>
                                                         }
                                                     }).collect::<Vec<_>>()}
@@ -106,18 +173,41 @@ pub fn ownership_chart(company: ReadSignal<Company>) -> impl IntoView {
                                             <div class="text-sm text-gray-600">
                                                 Deal Type: <span class="font-medium">{match round.deal_type {
                                                     crate::models::DealType::Equity => "Equity",
-                                                    crate::models::DealType::ConvertibleNote => "Convertible Note",
-                                                    crate::models::DealType::SAFE => "SAFE",
+                                                    crate::models::DealType::ConvertibleNote(_) => "Convertible Note",
+                                                    crate::models::DealType::SAFE(_) => "SAFE",
                                                     crate::models::DealType::PreferredStock => "Preferred Stock",
                                                 }}</span>
-                                                {if round.anti_dilution {
-                                                    view! {
-                                                        <span class="ml-2 text-orange-600"> Anti-dilution</span>
-                                                    }
-                                                } else {
-                                                    view! { <div></div> }
+                                                {match round.anti_dilution {
+                                                    AntiDilution::None => view! { <span></span> },
+                                                    AntiDilution::FullRatchet => view! {
+                                                        <span class="ml-2 text-orange-600"> Full ratchet</span>
+                                                    },
+                                                    AntiDilution::BroadBasedWeightedAverage => view! {
+                                                        <span class="ml-2 text-orange-600"> Broad-based weighted average</span>
+                                                    },
+                                                    AntiDilution::NarrowBasedWeightedAverage => view! {
+                                                        <span class="ml-2 text-orange-600"> Narrow-based weighted average</span>
+                                                    },
                                                 }}
                                             </div>
+
+                                            // Anti-dilution adjustments triggered by this round
+                                            {company.get().anti_dilution_adjustments.iter()
+                                                .filter(|a| a.round_name == round.name)
+                                                .map(|adjustment| {
+                                                    view! {
+                                                        <div class="mt-3 p-3 bg-orange-50 border border-orange-200 rounded text-sm text-orange-800">
+                                                            {format!(
+                                                                "{} conversion price ${:.4} → ${:.4}: +{} shares, founders -{:.2} pts",
+                                                                adjustment.share_class,
+                                                                adjustment.previous_conversion_price,
+                                                                adjustment.new_conversion_price,
+                                                                format_shares(adjustment.additional_shares),
+                                                                adjustment.founder_dilution,
+                                                            )}
+                                                        </div>
+                                                    }
+                                                }).collect::<Vec<_>>()}
                                         </div>
                                     </div>
                                 }
@@ -139,9 +229,9 @@ pub fn ownership_chart(company: ReadSignal<Company>) -> impl IntoView {
             {move || {
                 let rounds = company.get().funding_rounds.clone();
                 if !rounds.is_empty() {
-                    let current_valuation = rounds.last().map(|r| r.valuation + r.amount).unwrap_or(0.0);
+                    let current_valuation = rounds.last().map(|r| r.valuation + r.amount).unwrap_or(Decimal::ZERO);
                     let total_esop = company.get().esop_pool.total_allocation;
-                    let founder_ownership: f64 = company.get().founders.iter().map(|f| f.current_ownership).sum();
+                    let founder_ownership: Decimal = company.get().founders.iter().map(|f| f.current_ownership).sum();
                     
                     view! {
                         <div class="mt-8">
@@ -162,7 +252,7 @@ pub fn ownership_chart(company: ReadSignal<Company>) -> impl IntoView {
                                     </div>
                                     <div>
                                         <span class="text-purple-600 font-medium">Investor Ownership:</span>
-                                        <span class="ml-2 text-lg font-bold">{format_percentage(100.0 - founder_ownership - total_esop)}</span>
+                                        <span class="ml-2 text-lg font-bold">{format_percentage(HUNDRED - founder_ownership - total_esop)}</span>
                                     </div>
                                 </div>
                             </div>
@@ -177,10 +267,10 @@ pub fn ownership_chart(company: ReadSignal<Company>) -> impl IntoView {
             {move || {
                 let rounds = company.get().funding_rounds.clone();
                 if rounds.len() > 1 {
-                    let total_dilution: f64 = company.get().founders.iter()
+                    let total_dilution: Decimal = company.get().founders.iter()
                         .map(|f| f.initial_ownership - f.current_ownership)
                         .sum();
-                    let avg_round_size: f64 = rounds.iter().map(|r| r.amount).sum::<f64>() / rounds.len() as f64;
+                    let avg_round_size: Decimal = rounds.iter().map(|r| r.amount).sum::<Decimal>() / Decimal::from(rounds.len());
                     
                     view! {
                         <div class="mt-6">
@@ -190,14 +280,14 @@ pub fn ownership_chart(company: ReadSignal<Company>) -> impl IntoView {
                                     <div class="text-yellow-800">
                                         <div class="font-medium mb-1">Total Founder Dilution</div>
                                         <div class="text-2xl font-bold">{format_percentage(total_dilution)}</div>
-                                        <div class="text-sm">Average per round: {format_percentage(total_dilution / rounds.len() as f64)}</div>
+                                        <div class="text-sm">Average per round: {format_percentage(total_dilution / Decimal::from(rounds.len()))}</div>
                                     </div>
                                 </div>
                                 <div class="bg-indigo-50 border border-indigo-200 rounded-lg p-4">
                                     <div class="text-indigo-800">
                                         <div class="font-medium mb-1">Average Round Size</div>
                                         <div class="text-2xl font-bold">{format_currency(avg_round_size)}</div>
-                                        <div class="text-sm">Total raised: {format_currency(rounds.iter().map(|r| r.amount).sum::<f64>())}</div>
+                                        <div class="text-sm">Total raised: {format_currency(rounds.iter().map(|r| r.amount).sum::<Decimal>())}</div>
                                     </div>
                                 </div>
                             </div>
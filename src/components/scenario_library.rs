@@ -0,0 +1,252 @@
+use leptos::*;
+use chrono::NaiveDate;
+use web_sys::HtmlInputElement;
+use crate::document::{export_document, import_document, ExitSettings};
+use crate::models::{Company, ExitType, ScenarioLibrary};
+use crate::ocf::{export_ocf, import_ocf};
+use crate::share_link::encode_share_link;
+use crate::storage::{download_file, load_scenarios, page_address, read_file, save_scenarios};
+
+#[component]
+pub fn scenario_library(
+    company: ReadSignal<Company>,
+    set_company: WriteSignal<Company>,
+    exit_type: ReadSignal<ExitType>,
+    set_exit_type: WriteSignal<ExitType>,
+    exit_date: ReadSignal<NaiveDate>,
+    set_exit_date: WriteSignal<NaiveDate>,
+) -> impl IntoView {
+    let loaded = load_scenarios();
+    let (library, set_library) = create_signal(loaded.clone().unwrap_or_default());
+    let (load_error, _) = create_signal(loaded.err());
+    let (name, set_name) = create_signal("".to_string());
+    let (error, set_error) = create_signal(load_error.get_untracked());
+    let (share_link, set_share_link) = create_signal(None::<String>);
+
+    // Every change to the library is written straight back to storage, unless
+    // the stored library would not load: saving then would overwrite it
+    let edit_library = move |edit: &dyn Fn(&mut ScenarioLibrary) -> Result<(), String>| {
+        if let Some(message) = load_error.get() {
+            set_error.set(Some(format!("{}. Saved scenarios are left untouched until this is fixed.", message)));
+            return;
+        }
+        let mut new_library = library.get();
+        match edit(&mut new_library).and_then(|_| save_scenarios(&new_library)) {
+            Ok(()) => {
+                set_error.set(None);
+                set_library.set(new_library);
+            }
+            Err(message) => set_error.set(Some(message)),
+        }
+    };
+
+    // The whole model as a versioned JSON file
+    let export_model = move |_| {
+        let exit = ExitSettings { exit_type: exit_type.get(), exit_date: exit_date.get() };
+        let exported = export_document(&company.get(), &exit)
+            .and_then(|json| download_file(&format!("{}.json", company.get().name), "application/json", &json));
+        set_error.set(exported.err());
+    };
+
+    let import_model = move |ev| {
+        let input: HtmlInputElement = event_target(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        spawn_local(async move {
+            match read_file(&file).await.and_then(|json| import_document(&json)) {
+                Ok(document) => {
+                    set_error.set(None);
+                    set_company.set(document.company);
+                    set_exit_type.set(document.exit.exit_type);
+                    set_exit_date.set(document.exit.exit_date);
+                }
+                Err(message) => set_error.set(Some(message)),
+            }
+        });
+        // Picking the same file again should load it again
+        input.set_value("");
+    };
+
+    // The cap table as an OCF bundle, for lawyers and cap table vendors
+    let export_cap_table = move |_| {
+        let exported = export_ocf(&company.get())
+            .and_then(|json| download_file(&format!("{}.ocf.json", company.get().name), "application/json", &json));
+        set_error.set(exported.err());
+    };
+
+    // A vendor's OCF export may be split across several files
+    let import_cap_table = move |ev| {
+        let input: HtmlInputElement = event_target(&ev);
+        let Some(files) = input.files() else {
+            return;
+        };
+        let files: Vec<_> = (0..files.length()).filter_map(|i| files.get(i)).collect();
+        spawn_local(async move {
+            let mut contents = Vec::new();
+            for file in &files {
+                match read_file(file).await {
+                    Ok(text) => contents.push(text),
+                    Err(message) => return set_error.set(Some(message)),
+                }
+            }
+            match import_ocf(&contents) {
+                Ok(imported) => {
+                    set_error.set(None);
+                    set_company.set(imported);
+                }
+                Err(message) => set_error.set(Some(message)),
+            }
+        });
+        input.set_value("");
+    };
+
+    // A link that opens this exact scenario, for sending to someone
+    let make_share_link = move |_| {
+        let exit = ExitSettings { exit_type: exit_type.get(), exit_date: exit_date.get() };
+        match encode_share_link(&company.get(), &exit) {
+            Ok(fragment) => {
+                set_error.set(None);
+                set_share_link.set(Some(format!("{}#{}", page_address().unwrap_or_default(), fragment)));
+            }
+            Err(message) => set_error.set(Some(message)),
+        }
+    };
+
+    view! {
+        <div class="bg-white rounded-lg shadow-lg p-6">
+            <h2 class="text-2xl font-bold text-gray-800 mb-6">Scenarios</h2>
+
+            // Files
+            <div class="flex space-x-2 mb-4">
+                <button
+                    class="flex-1 px-4 py-2 border border-gray-300 text-gray-700 rounded-md hover:bg-gray-50"
+                    on:click=export_model
+                >
+                    Export JSON
+                </button>
+                <label class="flex-1 px-4 py-2 border border-gray-300 text-gray-700 rounded-md hover:bg-gray-50 text-center cursor-pointer">
+                    Import JSON
+                    <input type="file" accept=".json,application/json" class="hidden" on:change=import_model />
+                </label>
+            </div>
+            <div class="flex space-x-2 mb-4">
+                <button
+                    class="flex-1 px-4 py-2 border border-gray-300 text-gray-700 rounded-md hover:bg-gray-50"
+                    on:click=export_cap_table
+                >
+                    Export OCF
+                </button>
+                <label class="flex-1 px-4 py-2 border border-gray-300 text-gray-700 rounded-md hover:bg-gray-50 text-center cursor-pointer">
+                    Import OCF
+                    <input type="file" accept=".json,application/json" multiple class="hidden" on:change=import_cap_table />
+                </label>
+            </div>
+            <div class="mb-4">
+                <button
+                    class="w-full px-4 py-2 border border-gray-300 text-gray-700 rounded-md hover:bg-gray-50"
+                    on:click=make_share_link
+                >
+                    Share Link
+                </button>
+                {move || share_link.get().map(|link| view! {
+                    <div class="mt-2">
+                        <input
+                            type="text"
+                            readonly=true
+                            class="w-full px-3 py-2 border border-gray-300 rounded-md text-xs text-gray-700 bg-gray-50"
+                            value=link
+                            on:focus=move |ev| {
+                                let input: HtmlInputElement = event_target(&ev);
+                                input.select();
+                            }
+                        />
+                        <div class="mt-1 text-xs text-gray-500">Copy this link; it opens the scenario as it is now</div>
+                    </div>
+                })}
+            </div>
+
+            // Save Current Model
+            <div class="flex space-x-2 mb-4">
+                <input
+                    type="text"
+                    placeholder="Scenario name"
+                    class="flex-1 px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                    value=name
+                    on:change=move |ev| set_name.set(event_target_value(&ev))
+                />
+                <button
+                    class="px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 focus:outline-none focus:ring-2 focus:ring-blue-500"
+                    on:click=move |_| {
+                        let now = chrono::Local::now().naive_local();
+                        edit_library(&|l: &mut ScenarioLibrary| l.save(&name.get(), &company.get(), now));
+                        if error.get().is_none() {
+                            set_name.set("".to_string());
+                        }
+                    }
+                >
+                    Save
+                </button>
+            </div>
+            {move || error.get().map(|message| view! {
+                <div class="mb-4 p-3 bg-red-50 border border-red-200 rounded text-sm text-red-800">{message}</div>
+            })}
+
+            // Saved Scenarios
+            {move || {
+                let scenarios = library.get().scenarios;
+                if scenarios.is_empty() {
+                    view! { <div class="text-sm text-gray-500">No saved scenarios yet</div> }
+                } else {
+                    view! {
+                        <div class="space-y-2">
+                            {scenarios.into_iter().map(|scenario| {
+                                let open_name = scenario.name.clone();
+                                let duplicate_name = scenario.name.clone();
+                                let delete_name = scenario.name.clone();
+                                view! {
+                                    <div class="bg-gray-50 px-3 py-2 rounded text-sm">
+                                        <div class="flex justify-between">
+                                            <span class="font-medium text-gray-900">{scenario.name.clone()}</span>
+                                            <span class="text-gray-500">{scenario.saved_at.format("%Y-%m-%d %H:%M").to_string()}</span>
+                                        </div>
+                                        <div class="mt-1 flex space-x-3 text-xs">
+                                            <button
+                                                class="text-blue-600 hover:text-blue-800"
+                                                on:click=move |_| {
+                                                    if let Some(saved) = library.get().open(&open_name) {
+                                                        set_company.set(saved.clone());
+                                                    }
+                                                }
+                                            >
+                                                Open
+                                            </button>
+                                            <button
+                                                class="text-gray-600 hover:text-gray-800"
+                                                on:click=move |_| {
+                                                    let now = chrono::Local::now().naive_local();
+                                                    edit_library(&|l: &mut ScenarioLibrary| l.duplicate(&duplicate_name, now).map(|_| ()));
+                                                }
+                                            >
+                                                Duplicate
+                                            </button>
+                                            <button
+                                                class="text-red-600 hover:text-red-800"
+                                                on:click=move |_| edit_library(&|l: &mut ScenarioLibrary| {
+                                                    l.delete(&delete_name);
+                                                    Ok(())
+                                                })
+                                            >
+                                                Delete
+                                            </button>
+                                        </div>
+                                    </div>
+                                }
+                            }).collect::<Vec<_>>()}
+                        </div>
+                    }
+                }
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,176 @@
+use crate::models::{CapTableRow, ExitScenario};
+use crate::money::Decimal;
+
+// Spreadsheet exports. Numbers are written plain, without currency signs or
+// digit grouping, so they paste as numbers: shares whole, percentages to four
+// places and dollars to the cent.
+const CAP_TABLE_HEADER: [&str; 7] = [
+    "Holder",
+    "Type",
+    "Class",
+    "Outstanding Shares",
+    "As-Converted Shares",
+    "Outstanding %",
+    "Fully Diluted %",
+];
+
+const DISTRIBUTION_HEADER: [&str; 10] = [
+    "Scenario",
+    "Exit Value",
+    "Holder",
+    "Type",
+    "Class",
+    "Shares",
+    "Converted to Common",
+    "Preference",
+    "Participation",
+    "Payout",
+];
+
+pub fn cap_table_csv(rows: &[CapTableRow]) -> String {
+    let mut csv = csv_line(&CAP_TABLE_HEADER.map(String::from));
+    for row in rows {
+        csv += &csv_line(&cap_table_fields(row));
+    }
+    csv
+}
+
+// The cap table after each of a run of rounds, every row led by its round.
+pub fn round_cap_tables_csv(tables: &[(String, Vec<CapTableRow>)]) -> String {
+    let header: Vec<String> = ["Round"]
+        .iter()
+        .chain(CAP_TABLE_HEADER.iter())
+        .map(|h| h.to_string())
+        .collect();
+    let mut csv = csv_line(&header);
+    for (round, rows) in tables {
+        for row in rows {
+            let mut fields = vec![round.clone()];
+            fields.extend(cap_table_fields(row));
+            csv += &csv_line(&fields);
+        }
+    }
+    csv
+}
+
+fn cap_table_fields(row: &CapTableRow) -> Vec<String> {
+    vec![
+        row.holder.clone(),
+        format!("{:?}", row.kind),
+        row.share_class.clone(),
+        row.outstanding_shares.to_string(),
+        row.as_converted_shares.to_string(),
+        percentage(row.outstanding_percentage),
+        percentage(row.fully_diluted_percentage),
+    ]
+}
+
+// Every holder's payout in every scenario, one scenario after another.
+pub fn exit_distributions_csv(scenarios: &[ExitScenario]) -> String {
+    let mut csv = csv_line(&DISTRIBUTION_HEADER.map(String::from));
+    for scenario in scenarios {
+        for payout in &scenario.distribution {
+            let converted = if payout.converted_to_common {
+                "Yes"
+            } else {
+                "No"
+            };
+            csv += &csv_line(&[
+                scenario.name.clone(),
+                dollars(scenario.exit_valuation),
+                payout.holder.clone(),
+                format!("{:?}", payout.kind),
+                payout.share_class.clone(),
+                payout.shares.to_string(),
+                converted.to_string(),
+                dollars(payout.preference_amount),
+                dollars(payout.participation_amount),
+                dollars(payout.payout_amount),
+            ]);
+        }
+    }
+    csv
+}
+
+fn percentage(value: Decimal) -> String {
+    value.round_dp(4).normalize().to_string()
+}
+
+fn dollars(value: Decimal) -> String {
+    format!("{:.2}", value.round_dp(2))
+}
+
+// One CRLF-terminated record. Text a spreadsheet would read as a formula is
+// led with an apostrophe so it shows as typed, numbers are left alone, and
+// fields holding a comma, quote or line break are quoted, with quotes doubled.
+fn csv_line(fields: &[String]) -> String {
+    let quoted: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let field =
+                if field.starts_with(['=', '+', '-', '@']) && field.parse::<Decimal>().is_err() {
+                    format!("'{}", field)
+                } else {
+                    field.clone()
+                };
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect();
+    quoted.join(",") + "\r\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::HolderKind;
+    use crate::money::dec;
+
+    fn row(holder: &str) -> CapTableRow {
+        CapTableRow {
+            holder: holder.to_string(),
+            kind: HolderKind::Investor,
+            share_class: "Series A Preferred".to_string(),
+            outstanding_shares: 1_250_000,
+            as_converted_shares: 1_250_000,
+            outstanding_percentage: dec!(12.5),
+            fully_diluted_percentage: dec!(11.11111),
+        }
+    }
+
+    #[test]
+    fn records_end_in_crlf() {
+        let csv = cap_table_csv(&[row("Acme Ventures")]);
+        assert_eq!(
+            csv,
+            "Holder,Type,Class,Outstanding Shares,As-Converted Shares,Outstanding %,Fully Diluted %\r\n\
+             Acme Ventures,Investor,Series A Preferred,1250000,1250000,12.5,11.1111\r\n"
+        );
+    }
+
+    #[test]
+    fn commas_quotes_and_line_breaks_are_quoted() {
+        let line = |field: &str| csv_line(&[field.to_string(), "x".to_string()]);
+        assert_eq!(line("Acme, LLC"), "\"Acme, LLC\",x\r\n");
+        assert_eq!(line("The \"A\" Fund"), "\"The \"\"A\"\" Fund\",x\r\n");
+        assert_eq!(line("Two\nLines"), "\"Two\nLines\",x\r\n");
+        assert_eq!(line("Two\r\nLines"), "\"Two\r\nLines\",x\r\n");
+    }
+
+    #[test]
+    fn text_that_reads_as_a_formula_is_escaped() {
+        let line = |field: &str| csv_line(&[field.to_string()]);
+        assert_eq!(line("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"\r\n");
+        assert_eq!(line("+1 Capital"), "'+1 Capital\r\n");
+        assert_eq!(line("-Founder"), "'-Founder\r\n");
+        assert_eq!(line("@SUM(A1:A2)"), "'@SUM(A1:A2)\r\n");
+        assert_eq!(line("Angel = Bob"), "Angel = Bob\r\n");
+
+        // A negative number is still a number
+        assert_eq!(line("-1250.50"), "-1250.50\r\n");
+        assert!(cap_table_csv(&[row("=cmd|' /C calc'!A0")]).contains("\r\n'=cmd|"));
+    }
+}
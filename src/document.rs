@@ -0,0 +1,302 @@
+use crate::models::{default_founding_date, Company, ExitType, Scenario, ScenarioLibrary};
+use chrono::{Months, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// The whole model as a file. Every document records the schema version it
+// was written with, and older documents are brought forward one version at a
+// time before they are read, so files exported today keep loading after the
+// models change. Any change to the models that older files would not
+// deserialize into needs a new version and a migration step below.
+pub const SCHEMA_VERSION: u64 = 1;
+pub const DOCUMENT_FORMAT: &str = "startup-equity-scenario-builder";
+
+// The exit assumptions every scenario shares.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitSettings {
+    pub exit_type: ExitType,
+    pub exit_date: NaiveDate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelDocument {
+    pub format: String,
+    pub version: u64,
+    pub company: Company,
+    pub exit: ExitSettings,
+}
+
+// A saved scenario as the library is stored: its model is a document of its
+// own, so scenarios saved before a schema change migrate just as files do.
+// Libraries from before documents were versioned held a bare company.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredScenario {
+    name: String,
+    saved_at: NaiveDateTime,
+    #[serde(alias = "company")]
+    document: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredLibrary {
+    scenarios: Vec<StoredScenario>,
+}
+
+// Exit settings for a model that has none: an acquisition five years after
+// the latest event in its history. Derived from the company rather than the
+// clock, so the same company always reads back with the same exit.
+impl ExitSettings {
+    pub fn for_company(company: &Company) -> Self {
+        Self::after(company.events.iter().map(|e| e.date).max())
+    }
+
+    fn after(latest_event: Option<NaiveDate>) -> Self {
+        let start = latest_event.unwrap_or_else(default_founding_date);
+        ExitSettings {
+            exit_type: ExitType::Acquisition,
+            exit_date: start + Months::new(60),
+        }
+    }
+}
+
+// The step at index n takes a version n document to version n + 1.
+const MIGRATIONS: [fn(Value) -> Result<Value, String>; 1] = [wrap_bare_company];
+
+// Version 0 is a bare company with no envelope, the way the app autosaved
+// models before documents were versioned. Its event dates are read from the
+// raw value, so the step does not depend on today's models.
+fn wrap_bare_company(company: Value) -> Result<Value, String> {
+    let latest_event = company["events"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|event| event["date"].as_str()?.parse::<NaiveDate>().ok())
+        .max();
+    let exit =
+        serde_json::to_value(ExitSettings::after(latest_event)).map_err(|e| e.to_string())?;
+    Ok(json!({
+        "format": DOCUMENT_FORMAT,
+        "version": 1,
+        "company": company,
+        "exit": exit,
+    }))
+}
+
+impl ModelDocument {
+    pub fn new(company: &Company, exit: &ExitSettings) -> Self {
+        ModelDocument {
+            format: DOCUMENT_FORMAT.to_string(),
+            version: SCHEMA_VERSION,
+            company: company.clone(),
+            exit: exit.clone(),
+        }
+    }
+}
+
+pub fn export_document(company: &Company, exit: &ExitSettings) -> Result<String, String> {
+    serde_json::to_string_pretty(&ModelDocument::new(company, exit)).map_err(|e| e.to_string())
+}
+
+// Read a document of any version up to this one. The company is replayed
+// from its event log so everything derived from it matches today's rules.
+pub fn import_document(json: &str) -> Result<ModelDocument, String> {
+    let mut value: Value =
+        serde_json::from_str(json).map_err(|e| format!("The file is not valid JSON: {}", e))?;
+
+    let version = match value.get("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| "The file's schema version is not a number".to_string())?,
+    };
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "The file uses schema version {} but this app reads up to version {}",
+            version, SCHEMA_VERSION
+        ));
+    }
+    if version > 0 && value.get("format").and_then(Value::as_str) != Some(DOCUMENT_FORMAT) {
+        return Err("The file is not an equity model".to_string());
+    }
+
+    for migrate in &MIGRATIONS[version as usize..] {
+        value = migrate(value)?;
+    }
+
+    let mut document: ModelDocument = serde_json::from_value(value)
+        .map_err(|e| format!("The file does not describe a model: {}", e))?;
+    document
+        .company
+        .recompute()
+        .map_err(|e| format!("The file's history does not replay: {}", e))?;
+    Ok(document)
+}
+
+// Scenarios keep their company only, so each document carries the exit
+// settings its company implies.
+pub fn export_library(library: &ScenarioLibrary) -> Result<String, String> {
+    let scenarios = library
+        .scenarios
+        .iter()
+        .map(|scenario| {
+            let exit = ExitSettings::for_company(&scenario.company);
+            let document = ModelDocument::new(&scenario.company, &exit);
+            Ok(StoredScenario {
+                name: scenario.name.clone(),
+                saved_at: scenario.saved_at,
+                document: serde_json::to_value(document).map_err(|e| e.to_string())?,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+    serde_json::to_string(&StoredLibrary { scenarios }).map_err(|e| e.to_string())
+}
+
+// A library with any scenario that will not read is an error, never an
+// empty library, so nothing saves over scenarios that failed to load.
+pub fn import_library(json: &str) -> Result<ScenarioLibrary, String> {
+    let stored: StoredLibrary = serde_json::from_str(json)
+        .map_err(|e| format!("The saved scenarios could not be read: {}", e))?;
+    let scenarios = stored
+        .scenarios
+        .into_iter()
+        .map(|scenario| {
+            let document = import_document(&scenario.document.to_string())
+                .map_err(|e| format!("Saved scenario {}: {}", scenario.name, e))?;
+            Ok(Scenario {
+                name: scenario.name,
+                saved_at: scenario.saved_at,
+                company: document.company,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(ScenarioLibrary { scenarios })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{company, date, round};
+    use crate::models::DealType;
+    use crate::money::dec;
+
+    fn saved_at() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap()
+    }
+
+    fn library() -> ScenarioLibrary {
+        let company = Company {
+            name: "Acme".to_string(),
+            ..Company::default()
+        };
+        let mut library = ScenarioLibrary::default();
+        library.save("Base case", &company, saved_at()).unwrap();
+        library
+    }
+
+    #[test]
+    fn library_round_trips_as_documents() {
+        let json = export_library(&library()).unwrap();
+        let stored: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            stored["scenarios"][0]["document"]["version"],
+            json!(SCHEMA_VERSION)
+        );
+
+        let loaded = import_library(&json).unwrap();
+        assert_eq!(loaded.scenarios.len(), 1);
+        assert_eq!(loaded.scenarios[0].name, "Base case");
+        assert_eq!(loaded.scenarios[0].saved_at, saved_at());
+        assert_eq!(loaded.open("Base case").unwrap().name, "Acme");
+    }
+
+    #[test]
+    fn library_of_bare_companies_migrates() {
+        let legacy = serde_json::to_string(&library()).unwrap();
+        let loaded = import_library(&legacy).unwrap();
+        assert_eq!(loaded.open("Base case").unwrap().name, "Acme");
+    }
+
+    #[test]
+    fn unreadable_scenario_fails_the_load() {
+        let json = r#"{"scenarios": [{"name": "Broken", "saved_at": "2024-03-01T09:30:00",
+            "document": {"format": "startup-equity-scenario-builder", "version": 1}}]}"#;
+        let error = import_library(json).unwrap_err();
+        assert!(error.starts_with("Saved scenario Broken: "), "{}", error);
+        assert!(import_library("not json").is_err());
+    }
+
+    fn seeded() -> Company {
+        let mut company = company();
+        company
+            .add_round(round(
+                "Seed",
+                date(2021, 6, 1),
+                dec!(1_000_000),
+                dec!(4_000_000),
+                DealType::Equity,
+            ))
+            .unwrap();
+        company
+    }
+
+    #[test]
+    fn document_round_trips() {
+        let exit = ExitSettings {
+            exit_type: ExitType::IPO,
+            exit_date: date(2027, 1, 1),
+        };
+        let document = import_document(&export_document(&seeded(), &exit).unwrap()).unwrap();
+        assert_eq!(document.version, SCHEMA_VERSION);
+        assert_eq!(document.exit.exit_date, date(2027, 1, 1));
+        assert_eq!(document.company.funding_rounds.len(), 1);
+        assert_eq!(document.company.total_shares, 12_500_000);
+    }
+
+    #[test]
+    fn newer_or_foreign_documents_are_rejected() {
+        let mut document = serde_json::to_value(ModelDocument::new(
+            &seeded(),
+            &ExitSettings::for_company(&seeded()),
+        ))
+        .unwrap();
+
+        document["version"] = json!(SCHEMA_VERSION + 1);
+        let error = import_document(&document.to_string()).unwrap_err();
+        assert_eq!(
+            error,
+            format!(
+                "The file uses schema version {} but this app reads up to version {}",
+                SCHEMA_VERSION + 1,
+                SCHEMA_VERSION
+            )
+        );
+
+        document["version"] = json!("one");
+        let error = import_document(&document.to_string()).unwrap_err();
+        assert_eq!(error, "The file's schema version is not a number");
+
+        document["version"] = json!(SCHEMA_VERSION);
+        document["format"] = json!("spreadsheet");
+        let error = import_document(&document.to_string()).unwrap_err();
+        assert_eq!(error, "The file is not an equity model");
+    }
+
+    #[test]
+    fn bare_company_migrates_with_an_exit_from_its_history() {
+        let legacy = serde_json::to_string(&seeded()).unwrap();
+        let document = import_document(&legacy).unwrap();
+
+        // Five years after the Seed, the latest event, however often it is read
+        assert_eq!(document.version, SCHEMA_VERSION);
+        assert_eq!(document.format, DOCUMENT_FORMAT);
+        assert_eq!(document.exit.exit_date, date(2026, 6, 1));
+        assert_eq!(
+            import_document(&legacy).unwrap().exit.exit_date,
+            document.exit.exit_date
+        );
+        assert_eq!(document.company.funding_rounds[0].name, "Seed");
+    }
+}
@@ -0,0 +1,48 @@
+// Builders shared by the engine's tests.
+use crate::models::{
+    AntiDilution, Company, DealType, FundingRound, InvestorAllocation, RoundInput,
+};
+use crate::money::Decimal;
+use chrono::NaiveDate;
+
+pub fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+// Two founders with 5M shares each, founded at the start of 2020.
+pub fn company() -> Company {
+    let mut company = Company::default();
+    company.set_founding_date(date(2020, 1, 1)).unwrap();
+    company
+}
+
+// A round priced from its amount and pre-money, bought whole by
+// "{name} Investor".
+pub fn round(
+    name: &str,
+    close_date: NaiveDate,
+    amount: Decimal,
+    pre_money: Decimal,
+    deal_type: DealType,
+) -> FundingRound {
+    FundingRound {
+        name: name.to_string(),
+        close_date,
+        amount,
+        valuation: pre_money,
+        equity_sold: Decimal::ZERO,
+        price_per_share: Decimal::ZERO,
+        input: RoundInput::AmountAndPreMoney,
+        deal_type,
+        investors: vec![InvestorAllocation {
+            investor: format!("{} Investor", name),
+            amount,
+        }],
+        esop_allocation: Decimal::ZERO,
+        esop_target: None,
+        anti_dilution: AntiDilution::None,
+        liquidation_preference: None,
+        pro_rata_rights: false,
+        pro_rata: vec![],
+    }
+}
@@ -0,0 +1,280 @@
+use crate::models::{
+    CapTableRow, Company, ExitScenario, ExitType, FundingRound, OwnershipSnapshot,
+};
+use crate::money::Decimal;
+use crate::scenario_file::{run_scenario, FounderSpec, ScenarioFile, ScenarioReport};
+use crate::utils::{calculate_cap_table_summary, resolve_round_terms, simulate_funding_round};
+use crate::validation::validate_funding_round;
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+// The engine for JavaScript callers. Everything crosses as plain objects
+// shaped like the Rust models, so a company looks just as it does in the app's
+// JSON export, and the TypeScript declarations are generated from them.
+// Calls never mutate their arguments: a round returns the company after it.
+
+// Decimals cross as strings so amounts stay exact; numbers are accepted too.
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SCALARS: &'static str = r#"
+export type Decimal = string;
+export type NaiveDate = string; // YYYY-MM-DD
+export type NaiveDateTime = string; // YYYY-MM-DDTHH:MM:SS
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Company")]
+    pub type JsCompany;
+    #[wasm_bindgen(typescript_type = "FounderSpec[]")]
+    pub type JsFounders;
+    #[wasm_bindgen(typescript_type = "FundingRound")]
+    pub type JsFundingRound;
+    #[wasm_bindgen(typescript_type = "RoundResult")]
+    pub type JsRoundResult;
+    #[wasm_bindgen(typescript_type = "CapTableRow[]")]
+    pub type JsCapTable;
+    #[wasm_bindgen(typescript_type = "Decimal | number")]
+    pub type JsDecimal;
+    #[wasm_bindgen(typescript_type = "ExitType")]
+    pub type JsExitType;
+    #[wasm_bindgen(typescript_type = "NaiveDate")]
+    pub type JsDate;
+    #[wasm_bindgen(typescript_type = "ExitScenario")]
+    pub type JsExitScenario;
+    #[wasm_bindgen(typescript_type = "ScenarioFile")]
+    pub type JsScenarioFile;
+    #[wasm_bindgen(typescript_type = "ScenarioReport")]
+    pub type JsScenarioReport;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, tsify::Tsify)]
+pub struct RoundResult {
+    pub company: Company,
+    pub snapshot: OwnershipSnapshot,
+}
+
+fn from_js<T: DeserializeOwned>(value: JsValue, what: &str) -> Result<T, JsError> {
+    serde_wasm_bindgen::from_value(value).map_err(|e| JsError::new(&format!("{}: {}", what, e)))
+}
+
+fn to_js<T: Serialize, J: JsCast>(value: &T) -> Result<J, JsError> {
+    serde_wasm_bindgen::to_value(value)
+        .map(JsCast::unchecked_into)
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+// A company on its founding date, with the founders splitting the founding
+// shares by ownership percentage.
+#[wasm_bindgen(js_name = createCompany)]
+pub fn create_company(
+    name: String,
+    founded: JsDate,
+    founders: JsFounders,
+) -> Result<JsCompany, JsError> {
+    let founded = from_js::<NaiveDate>(founded.into(), "founded")?;
+    let founders = from_js::<Vec<FounderSpec>>(founders.into(), "founders")?;
+    to_js(&new_company(name, founded, founders).map_err(|e| JsError::new(&e))?)
+}
+
+fn new_company(
+    name: String,
+    founded: NaiveDate,
+    founders: Vec<FounderSpec>,
+) -> Result<Company, String> {
+    let scenario = ScenarioFile {
+        name,
+        founded,
+        founders,
+        rounds: Vec::new(),
+        exit: None,
+    };
+    scenario.company()
+}
+
+// Close a round. Its terms are resolved from the two its `input` names, as
+// the round editor does, then checked on their own and against the company.
+#[wasm_bindgen(js_name = simulateRound)]
+pub fn simulate_round(company: JsCompany, round: JsFundingRound) -> Result<JsRoundResult, JsError> {
+    let company: Company = from_js(company.into(), "company")?;
+    let round: FundingRound = from_js(round.into(), "round")?;
+    to_js(&close_round(company, round).map_err(|e| JsError::new(&e))?)
+}
+
+fn close_round(mut company: Company, mut round: FundingRound) -> Result<RoundResult, String> {
+    let pre_round_shares = company.fully_diluted_shares_on(round.close_date)?;
+    resolve_round_terms(&mut round, pre_round_shares)
+        .map_err(|e| format!("{}: {}", round.name, e))?;
+    validate_funding_round(&round).map_err(|errors| {
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        format!("{}: {}", round.name, messages.join("; "))
+    })?;
+    let snapshot = simulate_funding_round(&mut company, &round)
+        .map_err(|e| format!("{}: {}", round.name, e))?;
+    Ok(RoundResult { company, snapshot })
+}
+
+#[wasm_bindgen(js_name = capTable)]
+pub fn cap_table(company: JsCompany) -> Result<JsCapTable, JsError> {
+    let company: Company = from_js(company.into(), "company")?;
+    to_js::<Vec<CapTableRow>, _>(&calculate_cap_table_summary(&company))
+}
+
+// Every holder's payout from a sale at `exit_valuation`, in waterfall order.
+#[wasm_bindgen(js_name = exitWaterfall)]
+pub fn exit_waterfall(
+    company: JsCompany,
+    exit_valuation: JsDecimal,
+    exit_type: JsExitType,
+    exit_date: JsDate,
+) -> Result<JsExitScenario, JsError> {
+    let company: Company = from_js(company.into(), "company")?;
+    let exit_valuation: Decimal = from_js(exit_valuation.into(), "exit_valuation")?;
+    let exit_type: ExitType = from_js(exit_type.into(), "exit_type")?;
+    let exit_date: NaiveDate = from_js(exit_date.into(), "exit_date")?;
+    to_js::<ExitScenario, _>(&company.calculate_exit_scenario(exit_valuation, exit_type, exit_date))
+}
+
+// A whole scenario file, as the equity-scenarios command runs it.
+#[wasm_bindgen(js_name = runScenario)]
+pub fn run_scenario_js(scenario: JsScenarioFile) -> Result<JsScenarioReport, JsError> {
+    let scenario: ScenarioFile = from_js(scenario.into(), "scenario")?;
+    let report = run_scenario(&scenario).map_err(|e| JsError::new(&e))?;
+    to_js::<ScenarioReport, _>(&report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{date, round};
+    use crate::models::{DealType, LiquidationPreference, Participation, Seniority};
+    use crate::money::{dec, HUNDRED};
+
+    fn founders() -> Vec<FounderSpec> {
+        vec![
+            FounderSpec {
+                name: "Ada".to_string(),
+                ownership: dec!(60),
+            },
+            FounderSpec {
+                name: "Bob".to_string(),
+                ownership: dec!(40),
+            },
+        ]
+    }
+
+    fn acme() -> Company {
+        new_company("Acme".to_string(), date(2023, 1, 1), founders()).unwrap()
+    }
+
+    fn seed() -> FundingRound {
+        let mut seed = round(
+            "Seed",
+            date(2023, 6, 1),
+            dec!(1_000_000),
+            dec!(4_000_000),
+            DealType::PreferredStock,
+        );
+        seed.liquidation_preference = Some(LiquidationPreference {
+            multiple: Decimal::ONE,
+            participation: Participation::NonParticipating,
+            seniority: Seniority::Senior,
+        });
+        seed
+    }
+
+    #[test]
+    fn create_company_splits_the_founding_shares() {
+        let company = acme();
+        assert_eq!(company.name, "Acme");
+        assert_eq!(company.founding_date(), Some(date(2023, 1, 1)));
+        assert_eq!(company.founders[0].shares, 6_000_000);
+        assert_eq!(company.founders[1].shares, 4_000_000);
+    }
+
+    #[test]
+    fn simulate_round_returns_the_company_after_it() {
+        let before = acme();
+        let result = close_round(before.clone(), seed()).unwrap();
+
+        assert_eq!(before.funding_rounds.len(), 0);
+        assert_eq!(result.company.funding_rounds.len(), 1);
+        assert_eq!(result.snapshot.round_name, "Seed");
+        assert_eq!(result.snapshot.price_per_share, dec!(0.4));
+        assert_eq!(result.snapshot.post_round_shares, 12_500_000);
+    }
+
+    #[test]
+    fn simulate_round_rejects_bad_terms_before_closing() {
+        let mut unnamed = seed();
+        unnamed.investors[0].investor = String::new();
+        assert_eq!(
+            close_round(acme(), unnamed).unwrap_err(),
+            "Seed: Every investor needs a name"
+        );
+
+        let mut short = seed();
+        short.investors[0].amount = dec!(600_000);
+        short.esop_target = Some(HUNDRED);
+        let error = close_round(acme(), short).unwrap_err();
+        assert_eq!(
+            error,
+            "Seed: Target pool size must be between 0% and 100%; \
+             Investor checks total $600.0K but the round is $1.0M"
+        );
+
+        let mut free = seed();
+        free.valuation = Decimal::ZERO;
+        assert_eq!(
+            close_round(acme(), free).unwrap_err(),
+            "Seed: Pre-money valuation must be positive"
+        );
+    }
+
+    #[test]
+    fn cap_table_and_waterfall_price_the_closed_round() {
+        let company = close_round(acme(), seed()).unwrap().company;
+
+        let rows = calculate_cap_table_summary(&company);
+        let investor = rows.iter().find(|r| r.holder == "Seed Investor").unwrap();
+        assert_eq!(investor.as_converted_shares, 2_500_000);
+        assert_eq!(investor.fully_diluted_percentage, dec!(20));
+
+        // A $2M sale pays the 1x preference's $1M first, and converting to
+        // common would only bring in 20% of $2M
+        let exit = company.calculate_exit_scenario(
+            dec!(2_000_000),
+            ExitType::Acquisition,
+            date(2026, 1, 1),
+        );
+        let payout = exit
+            .distribution
+            .iter()
+            .find(|p| p.holder == "Seed Investor")
+            .unwrap();
+        assert_eq!(payout.payout_amount, dec!(1_000_000));
+        assert!(!payout.converted_to_common);
+    }
+
+    #[test]
+    fn run_scenario_takes_the_file_as_json() {
+        let scenario: ScenarioFile = serde_json::from_str(
+            r#"{
+                "name": "Acme",
+                "founded": "2023-01-01",
+                "founders": [{"name": "Ada", "ownership": "60"}, {"name": "Bob", "ownership": "40"}],
+                "rounds": [{"name": "Seed", "close_date": "2023-06-01",
+                            "amount": "1000000", "pre_money": "4000000"}],
+                "exit": {"valuations": ["10000000"]}
+            }"#,
+        )
+        .unwrap();
+        let report = run_scenario(&scenario).unwrap();
+        assert_eq!(report.rounds.len(), 1);
+        assert_eq!(report.rounds[0].snapshot.post_round_shares, 12_500_000);
+        assert_eq!(report.exits.len(), 1);
+        assert_eq!(report.exits[0].exit_valuation, dec!(10_000_000));
+    }
+}
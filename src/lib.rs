@@ -1,6 +1,25 @@
+// The engine builds on its own; the browser app sits behind the `ui` feature
+// and the JavaScript bindings behind `js`.
+#[cfg(feature = "ui")]
 pub mod app;
+#[cfg(feature = "ui")]
 pub mod components;
+pub mod csv;
+pub mod document;
+#[cfg(test)]
+mod fixtures;
+#[cfg(feature = "js")]
+pub mod js_api;
 pub mod models;
+pub mod money;
+pub mod ocf;
+pub mod scenario_file;
+pub mod share_link;
+#[cfg(feature = "ui")]
+pub mod storage;
 pub mod utils;
+pub mod validation;
+pub mod waterfall;
 
+#[cfg(feature = "ui")]
 pub use app::App;
@@ -1,59 +1,408 @@
+use crate::money::{
+    dec, percentage_of, reconcile_shares, whole_shares, Decimal, MathematicalOps, HUNDRED,
+};
+use crate::utils::{
+    calculate_equity_percentage, calculate_pool_top_up, calculate_post_money_valuation,
+    calculate_weighted_average_price, months_between, resolve_round_terms,
+};
+use crate::validation::ValidationError;
+use crate::waterfall::distribute;
+use chrono::{Months, NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
+pub const FOUNDING_SHARES: u64 = 10_000_000;
+pub const ESOP_POOL_HOLDER: &str = "ESOP Pool";
+pub const COMMON_CLASS: &str = "Common";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
 pub struct Founder {
     pub name: String,
-    pub initial_ownership: f64, // percentage
-    pub current_ownership: f64, // percentage
+    pub initial_ownership: Decimal, // percentage
+    pub current_ownership: Decimal, // percentage
     pub shares: u64,
+    pub vesting: Option<VestingSchedule>, // None means fully vested
+    pub departure_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub enum VestingCadence {
+    Monthly,
+    Quarterly,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub enum Acceleration {
+    None,
+    SingleTrigger { percentage: Decimal }, // of unvested shares, on change of control
+    DoubleTrigger { percentage: Decimal }, // on change of control plus termination
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct VestingSchedule {
+    pub start_date: NaiveDate,
+    pub cliff_months: u32,
+    pub total_months: u32,
+    pub cadence: VestingCadence,
+    pub acceleration: Acceleration,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
 pub enum DealType {
     Equity,
-    ConvertibleNote,
-    SAFE,
+    ConvertibleNote(NoteTerms),
+    SAFE(SafeTerms),
     PreferredStock,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct SafeTerms {
+    pub valuation_cap: Decimal, // post-money, 0 for uncapped
+    pub discount_rate: Decimal, // percentage off the next round price
+    pub mfn: bool,
+}
+
+// A SAFE that has been signed but not yet converted into shares.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct Safe {
+    pub holder: String,
+    pub round_name: String,
+    pub investment: Decimal, // in USD
+    pub terms: SafeTerms,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub enum InterestType {
+    Simple,
+    Compounding, // annually
+}
+
+// What happens to a note that reaches maturity without a qualified financing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub enum MaturityBehavior {
+    ConvertAtCap,
+    Repay,
+    Extend { months: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct NoteTerms {
+    pub interest_rate: Decimal, // annual percentage
+    pub interest_type: InterestType,
+    pub issue_date: NaiveDate,
+    pub maturity_date: NaiveDate,
+    pub valuation_cap: Decimal,       // pre-money, 0 for uncapped
+    pub discount_rate: Decimal,       // percentage off the next round price
+    pub qualified_financing: Decimal, // minimum round size that triggers conversion
+    pub maturity_behavior: MaturityBehavior,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct ConvertibleNote {
+    pub holder: String,
+    pub round_name: String,
+    pub principal: Decimal, // in USD
+    pub terms: NoteTerms,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub enum NoteOutcome {
+    Converted { shares: u64 },
+    Repaid { amount: Decimal },
+    Extended { maturity_date: NaiveDate },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct NoteSettlement {
+    pub holder: String,
+    pub round_name: String,
+    pub date: NaiveDate, // the maturity date it settled on
+    pub outcome: NoteOutcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub enum Participation {
+    NonParticipating,
+    Participating,
+    CappedParticipating { cap_multiple: Decimal }, // total return cap, as a multiple of investment
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub enum Seniority {
+    Senior,    // stacked: paid before every earlier class
+    PariPassu, // shares rank with the most senior earlier class
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct LiquidationPreference {
+    pub multiple: Decimal,
+    pub participation: Participation,
+    pub seniority: Seniority,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub enum AntiDilution {
+    None,
+    FullRatchet,
+    BroadBasedWeightedAverage,
+    NarrowBasedWeightedAverage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct ShareClass {
+    pub name: String,
+    pub original_issue_price: Decimal,
+    pub conversion_price: Decimal, // starts at the issue price, lowered by anti-dilution
+    pub preference: Option<LiquidationPreference>, // None shares in the residual as common
+    pub seniority_rank: u32,       // higher ranks are paid first, equal ranks pari passu
+    pub anti_dilution: AntiDilution,
+    pub votes_per_share: Decimal, // per as-converted share, 0 for non-voting
+}
+
+// A down-round adjustment to a protected class's conversion price.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct AntiDilutionAdjustment {
+    pub round_name: String,
+    pub share_class: String,
+    pub previous_conversion_price: Decimal,
+    pub new_conversion_price: Decimal,
+    pub additional_shares: u64, // as-converted shares issued to the class
+    pub founder_dilution: Decimal, // percentage points founders lost to the adjustment
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
 pub struct FundingRound {
     pub name: String,
-    pub amount: f64,      // in USD
-    pub valuation: f64,   // pre-money valuation
-    pub equity_sold: f64, // percentage of company sold
+    pub close_date: NaiveDate,
+    pub amount: Decimal,          // in USD
+    pub valuation: Decimal,       // pre-money valuation
+    pub equity_sold: Decimal,     // percentage of company sold
+    pub price_per_share: Decimal, // in USD, against the fully-diluted shares after conversions
+    pub input: RoundInput,        // which two terms were given; the rest are derived
     pub deal_type: DealType,
-    pub investors: Vec<String>,
-    pub esop_allocation: f64, // percentage for employee stock options
-    pub anti_dilution: bool,
+    pub investors: Vec<InvestorAllocation>,
+    pub esop_allocation: Decimal, // percentage for employee stock options
+    pub esop_target: Option<Decimal>, // post-money pool percentage, topped up in the pre-money
+    pub anti_dilution: AntiDilution, // protection for the class this round creates
+    pub liquidation_preference: Option<LiquidationPreference>, // preferred stock rounds only
+    pub pro_rata_rights: bool,    // whether this round's investors may follow on
+    pub pro_rata: Vec<ProRataElection>, // existing investors taking up their pro rata
+}
+
+// The pair of round terms the user set. Price per share and pre-money are
+// tied together by the share count, so each pair pins the round down.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub enum RoundInput {
+    AmountAndPreMoney,
+    AmountAndPrice,
+    AmountAndOwnership,
+    PreMoneyAndOwnership,
+    PriceAndOwnership,
+}
+
+// One investor's check within a round.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct InvestorAllocation {
+    pub investor: String,
+    pub amount: Decimal, // in USD
+}
+
+// An investor known to the company, however many rounds they have joined.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct Investor {
+    pub name: String,
+    pub pro_rata_rights: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub enum ProRataParticipation {
+    Full,
+    Partial { amount: Decimal }, // in USD, up to the entitlement
+    Declined,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct ProRataElection {
+    pub investor: String,
+    pub participation: ProRataParticipation,
+}
+
+// Everything one investor holds, summed across rounds and classes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct InvestorPosition {
+    pub name: String,
+    pub shares: u64,        // as converted
+    pub invested: Decimal,  // in USD
+    pub ownership: Decimal, // percentage, fully diluted
+    pub rounds: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
 pub struct ESOPPool {
-    pub total_allocation: f64, // percentage
-    pub allocated: f64,        // percentage
-    pub reserved: f64,         // percentage
+    pub total_allocation: Decimal, // percentage, outstanding options plus the unallocated pool
+    pub allocated: Decimal,        // percentage, granted and outstanding
+    pub reserved: Decimal,         // percentage, promised but not yet board approved
+    pub grants: Vec<OptionGrant>,
+}
+
+// An option grant made out of the pool. Approved grants move their
+// outstanding options out of the unallocated pool; promised grants only
+// reserve pool shares until the board signs off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct OptionGrant {
+    pub grantee: String,
+    pub shares: u64,
+    pub strike_price: Decimal, // in USD per share
+    pub grant_date: NaiveDate,
+    pub vesting: Option<VestingSchedule>, // None means fully vested
+    pub board_approved: bool,
+    pub exercised_shares: u64,
+    pub forfeited_shares: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub enum HolderKind {
+    Founder,
+    Investor,
+    OptionPool,   // unallocated
+    OptionHolder, // granted, unexercised options
+    Employee,     // shares from exercised options
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct Holding {
+    pub holder: String,
+    pub kind: HolderKind,
+    pub share_class: String,
+    pub shares: u64,
+    pub invested: Decimal, // in USD, the basis for liquidation preferences
+}
+
+// Every share the company has issued, by holder. Preferred holdings are
+// counted as converted to common, so all ownership percentages are derived
+// from these counts against the fully-diluted total.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct ShareLedger {
+    pub holdings: Vec<Holding>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
 pub struct Company {
     pub name: String,
-    pub total_shares: u64,
+    pub total_shares: u64, // fully diluted, kept in sync with the ledger
+    #[serde(default = "default_founding_share_count")]
+    pub founding_share_count: u64, // split among the founders by initial ownership
     pub founders: Vec<Founder>,
     pub funding_rounds: Vec<FundingRound>,
     pub esop_pool: ESOPPool,
     pub exit_scenarios: Vec<ExitScenario>,
+    pub ledger: ShareLedger,
+    pub share_classes: Vec<ShareClass>, // Common first, then preferred in issue order
+    pub anti_dilution_adjustments: Vec<AntiDilutionAdjustment>,
+    pub round_history: Vec<OwnershipSnapshot>, // one snapshot per round, in order
+    pub safes: Vec<Safe>,                      // outstanding, converted at the next priced round
+    pub notes: Vec<ConvertibleNote>,           // outstanding, converted at a qualified financing
+    pub investors: Vec<Investor>,              // everyone who has invested, in order of first check
+    pub events: Vec<CompanyEvent>, // in the order recorded; everything above is replayed from it
+    #[serde(default)]
+    pub note_settlements: Vec<NoteSettlement>, // notes that reached maturity, in the order they did
 }
 
+// One dated change to the company's shares. Share counts are ledger counts,
+// so preferred shares are counted as converted.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct CompanyEvent {
+    pub date: NaiveDate,
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub enum EventKind {
+    Founding,                       // founders' shares, from their initial ownership
+    ClassAuthorization(ShareClass), // a class created outside a round, such as an imported one
+    Issuance {
+        holder: String,
+        kind: HolderKind,
+        share_class: String,
+        shares: u64,
+        invested: Decimal, // in USD
+    },
+    RoundClose(FundingRound),
+    Grant(OptionGrant),
+    Exercise {
+        grant: usize, // position among the grants, in the order granted
+        shares: u64,
+    },
+    Termination {
+        grant: usize,
+    },
+    Transfer {
+        from: String,
+        to: String,
+        to_kind: HolderKind,
+        share_class: String,
+        shares: u64,
+    },
+    Repurchase {
+        holder: String,
+        share_class: String,
+        shares: u64,
+        price_per_share: Decimal, // in USD
+    },
+    Split {
+        numerator: u64, // every `denominator` shares become `numerator`
+        denominator: u64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
 pub struct ExitScenario {
     pub name: String,
-    pub exit_valuation: f64, // in USD
+    pub exit_valuation: Decimal, // in USD
     pub exit_type: ExitType,
+    pub exit_date: NaiveDate,
     pub founder_payouts: Vec<FounderPayout>,
+    pub distribution: Vec<HolderPayout>, // every holder, in waterfall order
+    #[serde(default)]
+    pub note_settlements: Vec<NoteSettlement>, // notes that matured before the exit
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
 pub enum ExitType {
     IPO,
     Acquisition,
@@ -62,21 +411,100 @@ pub enum ExitType {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
 pub struct FounderPayout {
     pub founder_name: String,
-    pub ownership_at_exit: f64, // percentage
+    pub ownership_at_exit: Decimal, // percentage
     pub shares_at_exit: u64,
-    pub payout_amount: f64,   // in USD
-    pub dilution_impact: f64, // percentage points lost
+    pub payout_amount: Decimal, // in USD, for vested and accelerated shares
+    pub dilution_impact: Decimal, // percentage points lost
+    pub vested_shares: u64,
+    pub accelerated_shares: u64,
+    pub unvested_shares: u64, // still subject to vesting, or forfeited on departure
+    pub unvested_payout: Decimal, // in USD, held back until the unvested shares vest
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct HolderPayout {
+    pub holder: String,
+    pub kind: HolderKind,
+    pub share_class: String,
+    pub shares: u64,
+    pub preference_amount: Decimal,    // in USD, paid ahead of common
+    pub participation_amount: Decimal, // in USD, share of the residual
+    pub payout_amount: Decimal,        // in USD
+    pub converted_to_common: bool,
+}
+
+// One line of the cap table. Options and the unallocated pool count toward
+// the fully-diluted total but have no shares outstanding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct CapTableRow {
+    pub holder: String,
+    pub kind: HolderKind,
+    pub share_class: String,
+    pub outstanding_shares: u64,
+    pub as_converted_shares: u64,
+    pub outstanding_percentage: Decimal, // of issued shares, as converted
+    pub fully_diluted_percentage: Decimal,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
 pub struct OwnershipSnapshot {
     pub round_name: String,
-    pub founder_ownership: Vec<(String, f64)>, // (name, percentage)
-    pub investor_ownership: Vec<(String, f64)>, // (name, percentage)
-    pub esop_ownership: f64,                   // percentage
-    pub total_valuation: f64,                  // post-money
+    pub founder_ownership: Vec<(String, Decimal)>, // (name, percentage)
+    pub investor_ownership: Vec<(String, Decimal)>, // (name, percentage)
+    pub esop_ownership: Decimal,                   // percentage
+    pub total_valuation: Decimal,                  // post-money
+    pub effective_pre_money: Decimal,              // pre-money less any pre-money pool top-up
+    pub pool_shares_added: u64,
+    pub anti_dilution_adjustments: Vec<AntiDilutionAdjustment>,
+    pub price_per_share: Decimal, // in USD, 0 for rounds that issue no shares
+    pub pre_round_shares: u64,    // fully diluted
+    #[serde(default)]
+    pub priced_shares: u64, // fully diluted after conversions and the pool; the price divides these
+    pub shares_issued: SharesIssued,
+    pub post_round_shares: u64, // fully diluted
+}
+
+// New shares a round put on the cap table, by where they came from.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct SharesIssued {
+    pub new_money: u64,
+    pub safe_conversion: u64,
+    pub note_conversion: u64,
+    pub option_pool: u64,
+    pub anti_dilution: u64,
+}
+
+// A named copy of the whole model, saved so it can be reopened later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct Scenario {
+    pub name: String,
+    pub saved_at: NaiveDateTime,
+    pub company: Company,
+}
+
+// Saved scenarios, most recently saved first. Names are unique.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct ScenarioLibrary {
+    pub scenarios: Vec<Scenario>,
+}
+
+fn default_founding_share_count() -> u64 {
+    FOUNDING_SHARES
+}
+
+// A fixed date, so a default company is the same whenever it is built; a new
+// model in the app is founded on the day it is started.
+pub fn default_founding_date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, 1).expect("a valid date")
 }
 
 impl Default for Company {
@@ -84,103 +512,2600 @@ impl Default for Company {
         Self {
             name: "My Startup".to_string(),
             total_shares: 10_000_000, // 10M shares
+            founding_share_count: FOUNDING_SHARES,
             founders: vec![
                 Founder {
                     name: "Founder 1".to_string(),
-                    initial_ownership: 50.0,
-                    current_ownership: 50.0,
+                    initial_ownership: dec!(50),
+                    current_ownership: dec!(50),
                     shares: 5_000_000,
+                    vesting: None,
+                    departure_date: None,
                 },
                 Founder {
                     name: "Founder 2".to_string(),
-                    initial_ownership: 50.0,
-                    current_ownership: 50.0,
+                    initial_ownership: dec!(50),
+                    current_ownership: dec!(50),
                     shares: 5_000_000,
+                    vesting: None,
+                    departure_date: None,
                 },
             ],
             funding_rounds: vec![],
             esop_pool: ESOPPool {
-                total_allocation: 0.0,
-                allocated: 0.0,
-                reserved: 0.0,
+                total_allocation: Decimal::ZERO,
+                allocated: Decimal::ZERO,
+                reserved: Decimal::ZERO,
+                grants: vec![],
             },
             exit_scenarios: vec![],
+            ledger: ShareLedger {
+                holdings: vec![
+                    Holding {
+                        holder: "Founder 1".to_string(),
+                        kind: HolderKind::Founder,
+                        share_class: COMMON_CLASS.to_string(),
+                        shares: 5_000_000,
+                        invested: Decimal::ZERO,
+                    },
+                    Holding {
+                        holder: "Founder 2".to_string(),
+                        kind: HolderKind::Founder,
+                        share_class: COMMON_CLASS.to_string(),
+                        shares: 5_000_000,
+                        invested: Decimal::ZERO,
+                    },
+                ],
+            },
+            share_classes: vec![ShareClass::common()],
+            anti_dilution_adjustments: vec![],
+            round_history: vec![],
+            safes: vec![],
+            notes: vec![],
+            investors: vec![],
+            events: vec![CompanyEvent {
+                date: default_founding_date(),
+                kind: EventKind::Founding,
+            }],
+            note_settlements: vec![],
         }
     }
 }
 
-impl Company {
-    pub fn calculate_ownership_after_round(&mut self, round: &FundingRound) -> OwnershipSnapshot {
-        let pre_round_valuation = round.valuation;
-        let post_round_valuation = pre_round_valuation + round.amount;
+impl SafeTerms {
+    // MFN holders take any term that is better than their own.
+    pub fn adopt_better_terms(&mut self, other: &SafeTerms) {
+        if other.valuation_cap > Decimal::ZERO
+            && (self.valuation_cap <= Decimal::ZERO || other.valuation_cap < self.valuation_cap)
+        {
+            self.valuation_cap = other.valuation_cap;
+        }
+        if other.discount_rate > self.discount_rate {
+            self.discount_rate = other.discount_rate;
+        }
+    }
+}
+
+impl Safe {
+    // Lower of the cap price (cap over the post-money company capitalization)
+    // and the discounted price of the priced round.
+    pub fn conversion_price(&self, round_price: Decimal, capitalization: Decimal) -> Decimal {
+        let mut price = round_price * (Decimal::ONE - self.terms.discount_rate / HUNDRED);
+        if self.terms.valuation_cap > Decimal::ZERO && capitalization > Decimal::ZERO {
+            price = price.min(self.terms.valuation_cap / capitalization);
+        }
+        price
+    }
+}
+
+impl ShareClass {
+    pub fn common() -> Self {
+        Self {
+            name: COMMON_CLASS.to_string(),
+            original_issue_price: Decimal::ZERO,
+            conversion_price: Decimal::ZERO,
+            preference: None,
+            seniority_rank: 0,
+            anti_dilution: AntiDilution::None,
+            votes_per_share: Decimal::ONE,
+        }
+    }
+
+    pub fn is_preferred(&self) -> bool {
+        self.name != COMMON_CLASS
+    }
+
+    // Common shares each preferred share converts into.
+    pub fn conversion_ratio(&self) -> Decimal {
+        if self.conversion_price > Decimal::ZERO {
+            self.original_issue_price / self.conversion_price
+        } else {
+            Decimal::ONE
+        }
+    }
+
+    // Preferred shares actually issued behind an as-converted count.
+    pub fn outstanding_shares(&self, as_converted_shares: u64) -> u64 {
+        whole_shares(Decimal::from(as_converted_shares) / self.conversion_ratio())
+    }
+}
+
+// "Seed Round" becomes "Series Seed Preferred", "Series A" becomes
+// "Series A Preferred".
+pub fn preferred_class_name(round_name: &str) -> String {
+    let series = round_name.trim().trim_end_matches(" Round").trim();
+    if series.starts_with("Series ") {
+        format!("{} Preferred", series)
+    } else {
+        format!("Series {} Preferred", series)
+    }
+}
+
+impl ExitType {
+    pub fn is_change_of_control(&self) -> bool {
+        matches!(self, ExitType::Acquisition | ExitType::Merger)
+    }
+}
+
+impl VestingSchedule {
+    // Shares vested by `as_of`: nothing before the cliff, then whole
+    // cadence periods since the start date, capped at the full grant.
+    pub fn vested_shares(&self, total_shares: u64, as_of: NaiveDate) -> u64 {
+        let months = months_between(self.start_date, as_of);
+        if months < self.cliff_months {
+            return 0;
+        }
+        if self.total_months == 0 || months >= self.total_months {
+            return total_shares;
+        }
+
+        let period = match self.cadence {
+            VestingCadence::Monthly => 1,
+            VestingCadence::Quarterly => 3,
+        };
+        let vested_months = months / period * period;
+        (total_shares as u128 * vested_months as u128 / self.total_months as u128) as u64
+    }
+
+    // Unvested shares that accelerate at a change of control. A double
+    // trigger needs the founder to be let go within a year of the deal.
+    pub fn accelerated_shares(
+        &self,
+        unvested_shares: u64,
+        exit_date: NaiveDate,
+        departure_date: Option<NaiveDate>,
+    ) -> u64 {
+        let percentage = match self.acceleration {
+            Acceleration::None => Decimal::ZERO,
+            Acceleration::SingleTrigger { percentage } => percentage,
+            Acceleration::DoubleTrigger { percentage } => {
+                let terminated = departure_date
+                    .is_some_and(|date| date >= exit_date && months_between(exit_date, date) < 12);
+                if terminated {
+                    percentage
+                } else {
+                    Decimal::ZERO
+                }
+            }
+        };
+        whole_shares(
+            Decimal::from(unvested_shares) * percentage.clamp(Decimal::ZERO, HUNDRED) / HUNDRED,
+        )
+    }
+}
+
+impl ShareLedger {
+    pub fn issue(&mut self, holder: &str, kind: HolderKind, shares: u64) {
+        self.issue_in_class(holder, kind, COMMON_CLASS, shares, Decimal::ZERO);
+    }
+
+    pub fn issue_in_class(
+        &mut self,
+        holder: &str,
+        kind: HolderKind,
+        share_class: &str,
+        shares: u64,
+        invested: Decimal,
+    ) {
+        if shares == 0 {
+            return;
+        }
+
+        match self
+            .holdings
+            .iter_mut()
+            .find(|h| h.holder == holder && h.kind == kind && h.share_class == share_class)
+        {
+            Some(holding) => {
+                holding.shares += shares;
+                holding.invested += invested;
+            }
+            None => self.holdings.push(Holding {
+                holder: holder.to_string(),
+                kind,
+                share_class: share_class.to_string(),
+                shares,
+                invested,
+            }),
+        }
+    }
+
+    pub fn set_shares(&mut self, holder: &str, kind: HolderKind, shares: u64) {
+        self.remove(holder);
+        self.issue(holder, kind, shares);
+    }
+
+    pub fn remove(&mut self, holder: &str) {
+        self.holdings.retain(|h| h.holder != holder);
+    }
+
+    // Take shares back from a holding, dropping it once it is empty.
+    pub fn cancel(&mut self, holder: &str, kind: HolderKind, shares: u64) {
+        if let Some(holding) = self
+            .holdings
+            .iter_mut()
+            .find(|h| h.holder == holder && h.kind == kind)
+        {
+            holding.shares = holding.shares.saturating_sub(shares);
+        }
+        self.holdings.retain(|h| h.shares > 0);
+    }
+
+    // Take shares of a class out of a holder's issued shares, returning the
+    // kind of holding they came from and the investment that goes with them.
+    pub fn withdraw(
+        &mut self,
+        holder: &str,
+        share_class: &str,
+        shares: u64,
+    ) -> Result<(HolderKind, Decimal), String> {
+        let Some(holding) = self.holdings.iter_mut().find(|h| {
+            h.holder == holder
+                && h.share_class == share_class
+                && !matches!(h.kind, HolderKind::OptionPool | HolderKind::OptionHolder)
+        }) else {
+            return Err(format!("{} holds no {} shares", holder, share_class));
+        };
+        if shares > holding.shares {
+            return Err(format!(
+                "{} holds only {} {} shares",
+                holder, holding.shares, share_class
+            ));
+        }
+
+        let invested = holding.invested * Decimal::from(shares) / Decimal::from(holding.shares);
+        holding.shares -= shares;
+        holding.invested -= invested;
+        let kind = holding.kind.clone();
+        self.holdings.retain(|h| h.shares > 0);
+        Ok((kind, invested))
+    }
+
+    pub fn rename(&mut self, old_name: &str, new_name: &str) {
+        for holding in &mut self.holdings {
+            if holding.holder == old_name {
+                holding.holder = new_name.to_string();
+            }
+        }
+    }
+
+    pub fn shares_of(&self, holder: &str) -> u64 {
+        self.holdings
+            .iter()
+            .filter(|h| h.holder == holder)
+            .map(|h| h.shares)
+            .sum()
+    }
+
+    pub fn shares_of_kind(&self, kind: HolderKind) -> u64 {
+        self.holdings
+            .iter()
+            .filter(|h| h.kind == kind)
+            .map(|h| h.shares)
+            .sum()
+    }
+
+    pub fn fully_diluted_shares(&self) -> u64 {
+        self.holdings.iter().map(|h| h.shares).sum()
+    }
+
+    pub fn percentage(&self, shares: u64) -> Decimal {
+        percentage_of(shares, self.fully_diluted_shares())
+    }
+
+    pub fn ownership_of(&self, holder: &str) -> Decimal {
+        self.percentage(self.shares_of(holder))
+    }
+
+    pub fn holders_of_kind(&self, kind: HolderKind) -> Vec<&Holding> {
+        self.holdings.iter().filter(|h| h.kind == kind).collect()
+    }
+
+    pub fn shares_in_class(&self, share_class: &str) -> u64 {
+        self.holdings
+            .iter()
+            .filter(|h| h.share_class == share_class)
+            .map(|h| h.shares)
+            .sum()
+    }
+}
+
+impl SharesIssued {
+    pub fn total(&self) -> u64 {
+        self.new_money
+            + self.safe_conversion
+            + self.note_conversion
+            + self.option_pool
+            + self.anti_dilution
+    }
+}
+
+impl FundingRound {
+    pub fn allocated_amount(&self) -> Decimal {
+        self.investors.iter().map(|a| a.amount).sum()
+    }
+
+    // The price the round was struck at, when it was entered as a price. SAFEs,
+    // notes and a pool top-up coming in ahead of it then move the pre-money
+    // rather than the price.
+    pub fn fixed_price(&self) -> Option<Decimal> {
+        let priced = matches!(self.deal_type, DealType::Equity | DealType::PreferredStock);
+        let by_price = matches!(
+            self.input,
+            RoundInput::AmountAndPrice | RoundInput::PriceAndOwnership
+        );
+        (priced && by_price).then_some(self.price_per_share)
+    }
+}
+
+impl EventKind {
+    pub fn rename_holder(&mut self, old_name: &str, new_name: &str) {
+        let holders = match self {
+            EventKind::Issuance { holder, .. } | EventKind::Repurchase { holder, .. } => {
+                vec![holder]
+            }
+            EventKind::Transfer { from, to, .. } => vec![from, to],
+            _ => vec![],
+        };
+        for holder in holders {
+            if holder == old_name {
+                *holder = new_name.to_string();
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            EventKind::Founding => "Company founded".to_string(),
+            EventKind::ClassAuthorization(class) => format!("{} authorized", class.name),
+            EventKind::Issuance {
+                holder,
+                share_class,
+                shares,
+                ..
+            } => format!("{} {} shares issued to {}", shares, share_class, holder),
+            EventKind::RoundClose(round) => format!("{} closed", round.name),
+            EventKind::Grant(grant) => {
+                format!("{} options granted to {}", grant.shares, grant.grantee)
+            }
+            EventKind::Exercise { grant, shares } => {
+                format!("{} options exercised under grant {}", shares, grant + 1)
+            }
+            EventKind::Termination { grant } => format!("Grant {} terminated", grant + 1),
+            EventKind::Transfer {
+                from,
+                to,
+                share_class,
+                shares,
+                ..
+            } => format!(
+                "{} {} shares transferred from {} to {}",
+                shares, share_class, from, to
+            ),
+            EventKind::Repurchase {
+                holder,
+                share_class,
+                shares,
+                price_per_share,
+            } => format!(
+                "{} {} shares repurchased from {} at ${:.4}",
+                shares, share_class, holder, price_per_share
+            ),
+            EventKind::Split {
+                numerator,
+                denominator,
+            } => format!("{}-for-{} stock split", numerator, denominator),
+        }
+    }
+}
+
+impl ConvertibleNote {
+    // Principal plus interest accrued from the issue date, on an actual/365 basis.
+    pub fn accrued_amount(&self, as_of: NaiveDate) -> Decimal {
+        let days = Decimal::from((as_of - self.terms.issue_date).num_days().max(0));
+        let years = days / dec!(365);
+        let rate = self.terms.interest_rate / HUNDRED;
+
+        match self.terms.interest_type {
+            InterestType::Simple => self.principal * (Decimal::ONE + rate * years),
+            InterestType::Compounding => self.principal * (Decimal::ONE + rate).powd(years),
+        }
+    }
+
+    // Lower of the cap price (cap over the pre-money shares) and the
+    // discounted price of the financing.
+    pub fn conversion_price(&self, round_price: Decimal, pre_money_shares: Decimal) -> Decimal {
+        let mut price = round_price * (Decimal::ONE - self.terms.discount_rate / HUNDRED);
+        if self.terms.valuation_cap > Decimal::ZERO && pre_money_shares > Decimal::ZERO {
+            price = price.min(self.terms.valuation_cap / pre_money_shares);
+        }
+        price
+    }
+
+    pub fn converts_in(&self, round: &FundingRound) -> bool {
+        round.amount >= self.terms.qualified_financing
+    }
+}
+
+impl OptionGrant {
+    pub fn outstanding_shares(&self) -> u64 {
+        self.shares
+            .saturating_sub(self.exercised_shares + self.forfeited_shares)
+    }
+
+    // Options that have vested by `as_of` and are still unexercised.
+    pub fn exercisable_shares(&self, as_of: NaiveDate) -> u64 {
+        let vested = match &self.vesting {
+            Some(schedule) => schedule.vested_shares(self.shares, as_of),
+            None => self.shares,
+        };
+        vested
+            .saturating_sub(self.exercised_shares)
+            .min(self.outstanding_shares())
+    }
+}
 
-        // Calculate new total shares after dilution
-        let new_shares =
-            (self.total_shares as f64 * post_round_valuation / pre_round_valuation) as u64;
-        let shares_sold = (new_shares as f64 * round.equity_sold / 100.0) as u64;
+impl Company {
+    // Copy share counts and percentages from the ledger back onto the
+    // founder records and the pool summary so every view reads the same numbers.
+    pub fn sync_from_ledger(&mut self) {
+        self.total_shares = self.ledger.fully_diluted_shares();
 
-        // Update founder ownership
         for founder in &mut self.founders {
-            let old_shares = founder.shares;
-            let new_shares =
-                (old_shares as f64 * pre_round_valuation / post_round_valuation) as u64;
-            founder.shares = new_shares;
-            founder.current_ownership = (new_shares as f64 / new_shares as f64) * 100.0;
+            founder.shares = self.ledger.shares_of(&founder.name);
+            founder.current_ownership = self.ledger.percentage(founder.shares);
+        }
+
+        let unallocated = self.ledger.shares_of_kind(HolderKind::OptionPool);
+        let granted = self.ledger.shares_of_kind(HolderKind::OptionHolder);
+        let promised: u64 = self
+            .esop_pool
+            .grants
+            .iter()
+            .filter(|g| !g.board_approved)
+            .map(|g| g.outstanding_shares())
+            .sum();
+
+        self.esop_pool.total_allocation = self.ledger.percentage(unallocated + granted);
+        self.esop_pool.allocated = self.ledger.percentage(granted);
+        self.esop_pool.reserved = self.ledger.percentage(promised);
+    }
+
+    // Unallocated pool shares not already promised to someone.
+    pub fn available_pool_shares(&self) -> u64 {
+        let promised: u64 = self
+            .esop_pool
+            .grants
+            .iter()
+            .filter(|g| !g.board_approved)
+            .map(|g| g.outstanding_shares())
+            .sum();
+        self.ledger
+            .shares_of_kind(HolderKind::OptionPool)
+            .saturating_sub(promised)
+    }
+
+    pub fn grant_options(&mut self, grant: OptionGrant) -> Result<(), String> {
+        if grant.grantee.trim().is_empty() {
+            return Err("Grantee name is required".to_string());
+        }
+        if grant.shares == 0 {
+            return Err("Grant must be for at least one option".to_string());
+        }
+        if grant.strike_price < Decimal::ZERO {
+            return Err("Strike price cannot be negative".to_string());
+        }
+
+        self.record_event(CompanyEvent {
+            date: grant.grant_date,
+            kind: EventKind::Grant(grant),
+        })
+    }
+
+    // Approval takes effect from the grant date, so the grant is replayed as
+    // approved from the start.
+    pub fn approve_grant(&mut self, index: usize) {
+        let mut approved = self.clone();
+        let Some(grant) = approved
+            .events
+            .iter_mut()
+            .filter_map(|e| match &mut e.kind {
+                EventKind::Grant(grant) => Some(grant),
+                _ => None,
+            })
+            .nth(index)
+        else {
+            return;
+        };
+        if grant.board_approved {
+            return;
+        }
+        grant.board_approved = true;
+        if approved.recompute().is_ok() {
+            *self = approved;
+        }
+    }
+
+    // Exercise vested options into common shares held by the grantee.
+    pub fn exercise_options(
+        &mut self,
+        index: usize,
+        shares: u64,
+        as_of: NaiveDate,
+    ) -> Result<(), String> {
+        self.record_event(CompanyEvent {
+            date: as_of,
+            kind: EventKind::Exercise {
+                grant: index,
+                shares,
+            },
+        })
+    }
+
+    // The grantee leaves on `as_of`: unvested options go back to the pool.
+    pub fn terminate_grant(&mut self, index: usize, as_of: NaiveDate) {
+        let _ = self.record_event(CompanyEvent {
+            date: as_of,
+            kind: EventKind::Termination { grant: index },
+        });
+    }
+
+    fn move_to_options(&mut self, grantee: &str, shares: u64) {
+        self.ledger
+            .cancel(ESOP_POOL_HOLDER, HolderKind::OptionPool, shares);
+        self.ledger.issue(grantee, HolderKind::OptionHolder, shares);
+    }
+
+    // The founding split is what every later round is priced against, so
+    // founder edits are replayed from the founding, as round edits are.
+    fn edit_founders(&mut self, edit: impl FnOnce(&mut Vec<Founder>)) -> Result<(), String> {
+        let mut updated = self.clone();
+        edit(&mut updated.founders);
+        updated.recompute()?;
+        *self = updated;
+        Ok(())
+    }
+
+    pub fn add_founder(&mut self, name: &str) -> Result<(), String> {
+        self.edit_founders(|founders| {
+            founders.push(Founder {
+                name: name.to_string(),
+                initial_ownership: Decimal::ZERO,
+                current_ownership: Decimal::ZERO,
+                shares: 0,
+                vesting: None,
+                departure_date: None,
+            })
+        })
+    }
+
+    pub fn remove_founder(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.founders.len() {
+            return Err("No such founder".to_string());
+        }
+        self.edit_founders(|founders| {
+            founders.remove(index);
+        })
+    }
+
+    pub fn rename_founder(&mut self, index: usize, name: &str) {
+        if let Some(founder) = self.founders.get_mut(index) {
+            self.ledger.rename(&founder.name, name);
+            for event in &mut self.events {
+                event.kind.rename_holder(&founder.name, name);
+            }
+            founder.name = name.to_string();
         }
+    }
 
-        // Update ESOP pool
-        if round.esop_allocation > 0.0 {
-            self.esop_pool.total_allocation += round.esop_allocation;
+    // Founding split is expressed against the company's founding share count.
+    pub fn set_founder_ownership(
+        &mut self,
+        index: usize,
+        ownership: Decimal,
+    ) -> Result<(), String> {
+        if index >= self.founders.len() {
+            return Err("No such founder".to_string());
         }
+        self.edit_founders(|founders| founders[index].initial_ownership = ownership)
+    }
 
-        // Calculate ownership breakdown
-        let founder_ownership: Vec<(String, f64)> = self
+    // Each founder's share of the founding shares, reconciled so the split
+    // ties out to the share. Counts are first taken to a millionth of a share,
+    // so an ownership that is itself a rounded ratio of share counts gives
+    // those counts back.
+    pub fn founding_shares(&self) -> Vec<u64> {
+        let counts: Vec<Decimal> = self
             .founders
             .iter()
-            .map(|f| (f.name.clone(), f.current_ownership))
+            .map(|f| {
+                (Decimal::from(self.founding_share_count) * f.initial_ownership / HUNDRED)
+                    .round_dp(6)
+            })
             .collect();
+        reconcile_shares(&counts)
+    }
 
-        let investor_ownership: Vec<(String, f64)> = round
-            .investors
+    pub fn ownership_snapshot(
+        &self,
+        round_name: &str,
+        total_valuation: Decimal,
+    ) -> OwnershipSnapshot {
+        let founder_ownership: Vec<(String, Decimal)> = self
+            .founders
             .iter()
-            .map(|name| {
-                (
-                    name.clone(),
-                    round.equity_sold / round.investors.len() as f64,
-                )
-            })
+            .map(|f| (f.name.clone(), f.current_ownership))
+            .collect();
+
+        let investor_ownership: Vec<(String, Decimal)> = self
+            .ledger
+            .holders_of_kind(HolderKind::Investor)
+            .into_iter()
+            .map(|h| (h.holder.clone(), self.ledger.percentage(h.shares)))
             .collect();
 
         OwnershipSnapshot {
-            round_name: round.name.clone(),
+            round_name: round_name.to_string(),
             founder_ownership,
             investor_ownership,
             esop_ownership: self.esop_pool.total_allocation,
-            total_valuation: post_round_valuation,
+            total_valuation,
+            effective_pre_money: Decimal::ZERO,
+            pool_shares_added: 0,
+            anti_dilution_adjustments: self
+                .anti_dilution_adjustments
+                .iter()
+                .filter(|a| a.round_name == round_name)
+                .cloned()
+                .collect(),
+            price_per_share: Decimal::ZERO,
+            pre_round_shares: self.total_shares,
+            priced_shares: self.total_shares,
+            shares_issued: SharesIssued::default(),
+            post_round_shares: self.total_shares,
         }
     }
 
-    pub fn calculate_exit_scenario(&self, exit_valuation: f64) -> ExitScenario {
-        let mut founder_payouts = Vec::new();
+    // Issues the round and writes back the terms it was issued at: the price
+    // and pre-money against the shares after SAFEs, notes and any pool top-up.
+    pub fn calculate_ownership_after_round(
+        &mut self,
+        round: &mut FundingRound,
+    ) -> Result<OwnershipSnapshot, ValidationError> {
+        self.settle_matured_notes(round.close_date);
 
-        for founder in &self.founders {
-            let payout_amount = (founder.current_ownership / 100.0) * exit_valuation;
-            let dilution_impact = founder.initial_ownership - founder.current_ownership;
+        // Price and ownership together fix the shares sold against the count
+        // before the round, so there is no room left for anything converting
+        // ahead of it.
+        let converts_ahead = !self.safes.is_empty()
+            || self.notes.iter().any(|note| note.converts_in(round))
+            || round.esop_target.is_some();
+        if round.input == RoundInput::PriceAndOwnership
+            && round.fixed_price().is_some()
+            && converts_ahead
+        {
+            return Err(ValidationError::PriceAndOwnershipWithConversions);
+        }
 
-            founder_payouts.push(FounderPayout {
-                founder_name: founder.name.clone(),
-                ownership_at_exit: founder.current_ownership,
-                shares_at_exit: founder.shares,
-                payout_amount,
+        self.register_investors(round);
+
+        let snapshot = match &round.deal_type {
+            DealType::SAFE(terms) => self.issue_safes(round, terms),
+            DealType::ConvertibleNote(terms) => self.issue_notes(round, terms),
+            _ => self.issue_priced_round(round),
+        };
+        self.round_history.push(snapshot.clone());
+        Ok(snapshot)
+    }
+
+    pub fn add_round(&mut self, round: FundingRound) -> Result<(), String> {
+        self.edit_rounds(|rounds| rounds.push(round))
+    }
+
+    pub fn update_round(&mut self, index: usize, round: FundingRound) -> Result<(), String> {
+        if index >= self.funding_rounds.len() {
+            return Err("No such round".to_string());
+        }
+        self.edit_rounds(|rounds| rounds[index] = round)
+    }
+
+    pub fn remove_round(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.funding_rounds.len() {
+            return Err("No such round".to_string());
+        }
+        self.edit_rounds(|rounds| {
+            rounds.remove(index);
+        })
+    }
+
+    // Rounds replay in close-date order, so only rounds closing on the same
+    // day can swap places.
+    pub fn move_round(&mut self, from: usize, to: usize) -> Result<(), String> {
+        if from >= self.funding_rounds.len() || to >= self.funding_rounds.len() {
+            return Err("No such round".to_string());
+        }
+        let (moved, other) = (&self.funding_rounds[from], &self.funding_rounds[to]);
+        if moved.close_date != other.close_date {
+            return Err(format!(
+                "{} closes on {} and {} on {}; change the close date to reorder them",
+                moved.name, moved.close_date, other.name, other.close_date
+            ));
+        }
+
+        // The round goes just before or after the one whose place it takes
+        let positions: Vec<usize> = self
+            .events
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| matches!(e.kind, EventKind::RoundClose(_)))
+            .map(|(i, _)| i)
+            .collect();
+        let mut moved = self.clone();
+        let event = moved.events.remove(positions[from]);
+        moved.events.insert(positions[to], event);
+        moved.recompute()?;
+        *self = moved;
+        Ok(())
+    }
+
+    // Apply an edit to the round list and rebuild from it, leaving the
+    // company untouched if any round no longer works out. Rounds keep their
+    // places in the event log, new ones go on the end, and the log is then
+    // put back in date order.
+    fn edit_rounds(&mut self, edit: impl FnOnce(&mut Vec<FundingRound>)) -> Result<(), String> {
+        let mut rounds = self.funding_rounds.clone();
+        edit(&mut rounds);
+
+        let mut rebuilt = self.clone();
+        let mut rounds = rounds.into_iter().map(|round| CompanyEvent {
+            date: round.close_date,
+            kind: EventKind::RoundClose(round),
+        });
+        let mut events = Vec::new();
+        for event in rebuilt.events.drain(..) {
+            match event.kind {
+                EventKind::RoundClose(_) => events.extend(rounds.next()),
+                _ => events.push(event),
+            }
+        }
+        events.extend(rounds);
+        events.sort_by_key(|e| e.date);
+        rebuilt.events = events;
+
+        rebuilt.recompute()?;
+        *self = rebuilt;
+        Ok(())
+    }
+
+    // The fully-diluted count on `date`, with every event dated on or before
+    // it replayed: what a round closing that day is priced against.
+    pub fn fully_diluted_shares_on(&self, date: NaiveDate) -> Result<u64, String> {
+        let mut earlier = self.clone();
+        earlier.events.retain(|e| e.date <= date);
+        earlier.recompute()?;
+        Ok(earlier.ledger.fully_diluted_shares())
+    }
+
+    // Add an event to the log after everything dated on or before it and
+    // replay, leaving the company untouched if it cannot happen.
+    pub fn record_event(&mut self, event: CompanyEvent) -> Result<(), String> {
+        let mut updated = self.clone();
+        let position = updated.events.partition_point(|e| e.date <= event.date);
+
+        // A backdated grant moves every later grant along one place
+        if let EventKind::Grant(_) = event.kind {
+            let inserted = updated.events[..position]
+                .iter()
+                .filter(|e| matches!(e.kind, EventKind::Grant(_)))
+                .count();
+            for later in &mut updated.events[position..] {
+                match &mut later.kind {
+                    EventKind::Exercise { grant, .. } | EventKind::Termination { grant }
+                        if *grant >= inserted =>
+                    {
+                        *grant += 1
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        updated.events.insert(position, event);
+        updated.recompute()?;
+        *self = updated;
+        Ok(())
+    }
+
+    pub fn founding_date(&self) -> Option<NaiveDate> {
+        self.events
+            .iter()
+            .find(|e| matches!(e.kind, EventKind::Founding))
+            .map(|e| e.date)
+    }
+
+    pub fn set_founding_date(&mut self, date: NaiveDate) -> Result<(), String> {
+        let mut updated = self.clone();
+        for event in &mut updated.events {
+            if let EventKind::Founding = event.kind {
+                event.date = date;
+            }
+        }
+        updated.recompute()?;
+        *self = updated;
+        Ok(())
+    }
+
+    // Rebuild everything from the event log, which is kept in date order.
+    // Each round's derived terms are priced afresh against the share count
+    // at its place in the sequence.
+    pub fn recompute(&mut self) -> Result<(), String> {
+        if let Some(founded) = self.founding_date() {
+            if let Some(early) = self.events.iter().find(|e| e.date < founded) {
+                return Err(format!(
+                    "{} on {} is before the company was founded on {}",
+                    early.kind.describe(),
+                    early.date,
+                    founded
+                ));
+            }
+        }
+
+        self.ledger = ShareLedger::default();
+        self.funding_rounds.clear();
+        self.esop_pool.grants.clear();
+        self.share_classes = vec![ShareClass::common()];
+        self.anti_dilution_adjustments.clear();
+        self.round_history.clear();
+        self.safes.clear();
+        self.notes.clear();
+        self.note_settlements.clear();
+        self.investors.clear();
+        self.sync_from_ledger();
+
+        for event in self.events.clone() {
+            self.apply_event(&event)?;
+        }
+        Ok(())
+    }
+
+    // The company as it stood at the end of `date`, replayed from the events
+    // dated on or before it, with any notes that matured since the last round
+    // settled.
+    pub fn as_of(&self, date: NaiveDate) -> Result<Company, String> {
+        let mut company = self.clone();
+        company.events.retain(|e| e.date <= date);
+        company.recompute()?;
+        company.settle_matured_notes(date);
+        Ok(company)
+    }
+
+    fn apply_event(&mut self, event: &CompanyEvent) -> Result<(), String> {
+        match &event.kind {
+            EventKind::Founding => {
+                for (founder, shares) in self.founders.iter().zip(self.founding_shares()) {
+                    self.ledger
+                        .issue(&founder.name, HolderKind::Founder, shares);
+                }
+            }
+            EventKind::ClassAuthorization(class) => {
+                if self.share_class(&class.name).is_some() {
+                    return Err(format!("There is already a {} share class", class.name));
+                }
+                self.share_classes.push(class.clone());
+            }
+            EventKind::Issuance {
+                holder,
+                kind,
+                share_class,
+                shares,
+                invested,
+            } => {
+                if self.share_class(share_class).is_none() {
+                    return Err(format!("There is no {} share class", share_class));
+                }
+                self.ledger
+                    .issue_in_class(holder, kind.clone(), share_class, *shares, *invested);
+                if *kind == HolderKind::Investor {
+                    self.add_investor(holder);
+                }
+            }
+            EventKind::RoundClose(round) => {
+                let mut round = round.clone();
+                resolve_round_terms(&mut round, self.ledger.fully_diluted_shares())
+                    .map_err(|e| format!("{}: {}", round.name, e))?;
+                self.calculate_ownership_after_round(&mut round)
+                    .map_err(|e| format!("{}: {}", round.name, e))?;
+                self.funding_rounds.push(round);
+            }
+            EventKind::Grant(grant) => self.issue_grant(grant.clone())?,
+            EventKind::Exercise { grant, shares } => {
+                self.apply_exercise(*grant, *shares, event.date)?
+            }
+            EventKind::Termination { grant } => self.apply_termination(*grant, event.date)?,
+            EventKind::Transfer {
+                from,
+                to,
+                to_kind,
+                share_class,
+                shares,
+            } => {
+                let (_, invested) = self.ledger.withdraw(from, share_class, *shares)?;
+                self.ledger
+                    .issue_in_class(to, to_kind.clone(), share_class, *shares, invested);
+                if *to_kind == HolderKind::Investor {
+                    self.add_investor(to);
+                }
+            }
+            // Repurchased shares are retired
+            EventKind::Repurchase {
+                holder,
+                share_class,
+                shares,
+                ..
+            } => {
+                self.ledger.withdraw(holder, share_class, *shares)?;
+            }
+            EventKind::Split {
+                numerator,
+                denominator,
+            } => self.apply_split(*numerator, *denominator)?,
+        }
+        self.sync_from_ledger();
+        Ok(())
+    }
+
+    // Put a grant on the ledger, with any exercises it already carries.
+    fn issue_grant(&mut self, grant: OptionGrant) -> Result<(), String> {
+        let needed = if grant.board_approved {
+            grant.outstanding_shares() + grant.exercised_shares
+        } else {
+            grant.outstanding_shares()
+        };
+        let available = self.available_pool_shares();
+        if needed > available {
+            return Err(format!(
+                "Grant of {} options to {} exceeds the {} available in the pool",
+                needed, grant.grantee, available
+            ));
+        }
+
+        if grant.board_approved {
+            self.move_to_options(&grant.grantee, grant.outstanding_shares());
+            self.ledger.cancel(
+                ESOP_POOL_HOLDER,
+                HolderKind::OptionPool,
+                grant.exercised_shares,
+            );
+            self.ledger.issue_in_class(
+                &grant.grantee,
+                HolderKind::Employee,
+                COMMON_CLASS,
+                grant.exercised_shares,
+                grant.strike_price * Decimal::from(grant.exercised_shares),
+            );
+        }
+        self.esop_pool.grants.push(grant);
+        Ok(())
+    }
+
+    fn apply_exercise(
+        &mut self,
+        index: usize,
+        shares: u64,
+        as_of: NaiveDate,
+    ) -> Result<(), String> {
+        let Some(grant) = self.esop_pool.grants.get_mut(index) else {
+            return Err("No such grant".to_string());
+        };
+        if !grant.board_approved {
+            return Err("Grant has not been approved by the board".to_string());
+        }
+        let exercisable = grant.exercisable_shares(as_of);
+        if shares > exercisable {
+            return Err(format!(
+                "Only {} options are vested and unexercised",
+                exercisable
+            ));
+        }
+
+        grant.exercised_shares += shares;
+        let (grantee, strike_price) = (grant.grantee.clone(), grant.strike_price);
+        self.ledger
+            .cancel(&grantee, HolderKind::OptionHolder, shares);
+        self.ledger.issue_in_class(
+            &grantee,
+            HolderKind::Employee,
+            COMMON_CLASS,
+            shares,
+            strike_price * Decimal::from(shares),
+        );
+        Ok(())
+    }
+
+    fn apply_termination(&mut self, index: usize, as_of: NaiveDate) -> Result<(), String> {
+        let Some(grant) = self.esop_pool.grants.get_mut(index) else {
+            return Err("No such grant".to_string());
+        };
+        let forfeited = grant
+            .outstanding_shares()
+            .saturating_sub(grant.exercisable_shares(as_of));
+        grant.forfeited_shares += forfeited;
+
+        if grant.board_approved {
+            let grantee = grant.grantee.clone();
+            self.ledger
+                .cancel(&grantee, HolderKind::OptionHolder, forfeited);
+            self.ledger
+                .issue(ESOP_POOL_HOLDER, HolderKind::OptionPool, forfeited);
+        }
+        Ok(())
+    }
+
+    // Every holding, grant and class price scales by the split ratio. Share
+    // counts round down per holder, as fractional shares are paid out in cash.
+    fn apply_split(&mut self, numerator: u64, denominator: u64) -> Result<(), String> {
+        if numerator == 0 || denominator == 0 {
+            return Err("Split ratio must be positive".to_string());
+        }
+        let scale = |shares: u64| (shares as u128 * numerator as u128 / denominator as u128) as u64;
+        let ratio = Decimal::from(numerator) / Decimal::from(denominator);
+
+        for holding in &mut self.ledger.holdings {
+            holding.shares = scale(holding.shares);
+        }
+        for class in &mut self.share_classes {
+            class.original_issue_price /= ratio;
+            class.conversion_price /= ratio;
+        }
+        for grant in &mut self.esop_pool.grants {
+            grant.shares = scale(grant.shares);
+            grant.exercised_shares = scale(grant.exercised_shares);
+            grant.forfeited_shares = scale(grant.forfeited_shares);
+            grant.strike_price /= ratio;
+        }
+        Ok(())
+    }
+
+    fn issue_notes(&mut self, round: &FundingRound, terms: &NoteTerms) -> OwnershipSnapshot {
+        for allocation in &round.investors {
+            self.notes.push(ConvertibleNote {
+                holder: allocation.investor.clone(),
+                round_name: round.name.clone(),
+                principal: allocation.amount,
+                terms: terms.clone(),
+            });
+        }
+
+        let mut snapshot = self.ownership_snapshot(&round.name, round.valuation + round.amount);
+        snapshot.effective_pre_money = round.valuation;
+        snapshot
+    }
+
+    // Apply each note's maturity behaviour to notes that matured before
+    // `as_of` without having converted in a qualified financing, and record
+    // what happened to each.
+    pub fn settle_matured_notes(&mut self, as_of: NaiveDate) {
+        let pre_money_shares = Decimal::from(self.ledger.fully_diluted_shares());
+        let mut outstanding = Vec::new();
+
+        for mut note in self.notes.drain(..) {
+            if note.terms.maturity_date >= as_of {
+                outstanding.push(note);
+                continue;
+            }
+
+            let date = note.terms.maturity_date;
+            let outcome = match note.terms.maturity_behavior {
+                MaturityBehavior::ConvertAtCap
+                    if note.terms.valuation_cap > Decimal::ZERO
+                        && pre_money_shares > Decimal::ZERO =>
+                {
+                    let amount = note.accrued_amount(note.terms.maturity_date);
+                    let price = note.terms.valuation_cap / pre_money_shares;
+                    let shares = whole_shares(amount / price);
+                    self.ledger.issue_in_class(
+                        &note.holder,
+                        HolderKind::Investor,
+                        COMMON_CLASS,
+                        shares,
+                        amount,
+                    );
+                    NoteOutcome::Converted { shares }
+                }
+                // An uncapped note, or one with no shares to price against,
+                // has no price to convert at, so it is repaid.
+                MaturityBehavior::ConvertAtCap | MaturityBehavior::Repay => NoteOutcome::Repaid {
+                    amount: note.accrued_amount(note.terms.maturity_date),
+                },
+                MaturityBehavior::Extend { months } => {
+                    while note.terms.maturity_date < as_of && months > 0 {
+                        note.terms.maturity_date = note
+                            .terms
+                            .maturity_date
+                            .checked_add_months(Months::new(months))
+                            .unwrap_or(NaiveDate::MAX);
+                    }
+                    let maturity_date = note.terms.maturity_date;
+                    outstanding.push(note.clone());
+                    NoteOutcome::Extended { maturity_date }
+                }
+            };
+
+            self.note_settlements.push(NoteSettlement {
+                holder: note.holder,
+                round_name: note.round_name,
+                date,
+                outcome,
+            });
+        }
+
+        self.notes = outstanding;
+        self.sync_from_ledger();
+    }
+
+    // SAFEs do not issue shares when signed. They sit on the company until the
+    // next priced round, and any MFN holders pick up better terms from this one.
+    fn issue_safes(&mut self, round: &FundingRound, terms: &SafeTerms) -> OwnershipSnapshot {
+        for safe in &mut self.safes {
+            if safe.terms.mfn {
+                safe.terms.adopt_better_terms(terms);
+            }
+        }
+
+        for allocation in &round.investors {
+            self.safes.push(Safe {
+                holder: allocation.investor.clone(),
+                round_name: round.name.clone(),
+                investment: allocation.amount,
+                terms: terms.clone(),
+            });
+        }
+
+        let post_money = if terms.valuation_cap > Decimal::ZERO {
+            terms.valuation_cap
+        } else {
+            round.valuation + round.amount
+        };
+        let mut snapshot = self.ownership_snapshot(&round.name, post_money);
+        snapshot.effective_pre_money = post_money - round.amount;
+        snapshot
+    }
+
+    // A priced round is an issuance against the ledger: outstanding SAFEs and
+    // notes convert first (with any pre-money pool top-up), investors buy new
+    // shares at pre-money / fully-diluted shares including the conversions, and
+    // any post-money ESOP allocation is issued to the pool so it ends at that
+    // percentage of the new shares. A round entered by price keeps its price
+    // and takes its pre-money from the shares it was issued against; any other
+    // round keeps its pre-money and takes its price from them.
+    fn issue_priced_round(&mut self, round: &mut FundingRound) -> OwnershipSnapshot {
+        let share_class = match round.deal_type {
+            DealType::PreferredStock => preferred_class_name(&round.name),
+            _ => COMMON_CLASS.to_string(),
+        };
+
+        let shares_before = self.ledger.fully_diluted_shares();
+        let mut shares_issued = self.convert_instruments(round, &share_class);
+        let pre_money_pool_shares = shares_issued.option_pool;
+        let priced_shares = self.ledger.fully_diluted_shares();
+
+        match round.fixed_price() {
+            Some(price) => round.valuation = price * Decimal::from(priced_shares),
+            None => round.price_per_share = round.valuation / Decimal::from(priced_shares),
+        }
+        round.equity_sold = calculate_equity_percentage(
+            round.amount,
+            calculate_post_money_valuation(round.valuation, round.amount),
+        );
+        let round_price = round.price_per_share;
+        let post_round_valuation = round.valuation + round.amount;
+        // Each investor buys with their own check at the round price
+        let allocation_shares = reconcile_shares(
+            &round
+                .investors
+                .iter()
+                .map(|a| a.amount / round_price)
+                .collect::<Vec<_>>(),
+        );
+        let investor_shares: u64 = allocation_shares.iter().sum();
+
+        let adjustments_before = self.anti_dilution_adjustments.len();
+        self.apply_anti_dilution(round, round_price, investor_shares);
+        shares_issued.anti_dilution = self.anti_dilution_adjustments[adjustments_before..]
+            .iter()
+            .map(|a| a.additional_shares)
+            .sum();
+        shares_issued.new_money = investor_shares;
+
+        if let DealType::PreferredStock = round.deal_type {
+            self.add_share_class(
+                &share_class,
+                round_price,
+                round.liquidation_preference.as_ref(),
+                &round.anti_dilution,
+            );
+        }
+
+        for (allocation, shares) in round.investors.iter().zip(allocation_shares) {
+            self.ledger.issue_in_class(
+                &allocation.investor,
+                HolderKind::Investor,
+                &share_class,
+                shares,
+                allocation.amount,
+            );
+        }
+
+        let mut post_money_pool_shares = 0;
+        if round.esop_allocation > Decimal::ZERO && round.esop_allocation < HUNDRED {
+            let issued = Decimal::from(self.ledger.fully_diluted_shares());
+            let pool_fraction = round.esop_allocation / HUNDRED;
+            post_money_pool_shares =
+                whole_shares(issued * pool_fraction / (Decimal::ONE - pool_fraction));
+            self.ledger.issue(
+                ESOP_POOL_HOLDER,
+                HolderKind::OptionPool,
+                post_money_pool_shares,
+            );
+        }
+
+        self.sync_from_ledger();
+        let mut snapshot = self.ownership_snapshot(&round.name, post_round_valuation);
+        snapshot.pool_shares_added = pre_money_pool_shares + post_money_pool_shares;
+        // A pool created in the pre-money is paid for by the existing holders,
+        // so the value they actually keep is the pre-money less the new pool.
+        snapshot.effective_pre_money =
+            round.valuation - Decimal::from(pre_money_pool_shares) * round_price;
+        shares_issued.option_pool += post_money_pool_shares;
+        snapshot.price_per_share = round_price;
+        snapshot.pre_round_shares = shares_before;
+        snapshot.priced_shares = priced_shares;
+        snapshot.shares_issued = shares_issued;
+        snapshot
+    }
+
+    // A senior class ranks above every existing class; a pari passu class
+    // shares the rank of the most senior one. Preferred votes as converted.
+    fn add_share_class(
+        &mut self,
+        name: &str,
+        original_issue_price: Decimal,
+        preference: Option<&LiquidationPreference>,
+        anti_dilution: &AntiDilution,
+    ) {
+        let top_rank = self
+            .share_classes
+            .iter()
+            .map(|c| c.seniority_rank)
+            .max()
+            .unwrap_or(0);
+        let seniority_rank = match preference.map(|p| &p.seniority) {
+            Some(Seniority::Senior) => top_rank + 1,
+            Some(Seniority::PariPassu) => top_rank.max(1),
+            None => 0,
+        };
+
+        self.share_classes.push(ShareClass {
+            name: name.to_string(),
+            original_issue_price,
+            conversion_price: original_issue_price,
+            preference: preference.cloned(),
+            seniority_rank,
+            anti_dilution: anti_dilution.clone(),
+            votes_per_share: Decimal::ONE,
+        });
+    }
+
+    // Investors who come in outside a round hold no pro-rata rights.
+    fn add_investor(&mut self, name: &str) {
+        if !self.investors.iter().any(|i| i.name == name) {
+            self.investors.push(Investor {
+                name: name.to_string(),
+                pro_rata_rights: false,
+            });
+        }
+    }
+
+    // A returning investor keeps any pro-rata rights already won; joining a
+    // round that grants them adds them.
+    fn register_investors(&mut self, round: &FundingRound) {
+        for allocation in &round.investors {
+            match self
+                .investors
+                .iter_mut()
+                .find(|i| i.name == allocation.investor)
+            {
+                Some(investor) => investor.pro_rata_rights |= round.pro_rata_rights,
+                None => self.investors.push(Investor {
+                    name: allocation.investor.clone(),
+                    pro_rata_rights: round.pro_rata_rights,
+                }),
+            }
+        }
+    }
+
+    pub fn investor_positions(&self) -> Vec<InvestorPosition> {
+        self.investors
+            .iter()
+            .map(|investor| {
+                let holdings = self
+                    .ledger
+                    .holdings
+                    .iter()
+                    .filter(|h| h.kind == HolderKind::Investor && h.holder == investor.name);
+                let shares = holdings.clone().map(|h| h.shares).sum();
+                let invested = holdings.map(|h| h.invested).sum();
+
+                InvestorPosition {
+                    name: investor.name.clone(),
+                    shares,
+                    invested,
+                    ownership: self.ledger.percentage(shares),
+                    rounds: self
+                        .funding_rounds
+                        .iter()
+                        .filter(|r| r.investors.iter().any(|a| a.investor == investor.name))
+                        .map(|r| r.name.clone())
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    // The check that keeps an investor's fully-diluted stake level through a
+    // round of `round_amount`, or nothing without pro-rata rights.
+    pub fn pro_rata_entitlement(&self, investor: &str, round_amount: Decimal) -> Decimal {
+        let has_rights = self
+            .investors
+            .iter()
+            .any(|i| i.name == investor && i.pro_rata_rights);
+        if !has_rights {
+            return Decimal::ZERO;
+        }
+        let shares = self
+            .ledger
+            .holdings
+            .iter()
+            .filter(|h| h.kind == HolderKind::Investor && h.holder == investor)
+            .map(|h| h.shares)
+            .sum();
+        self.ledger.percentage(shares) / HUNDRED * round_amount
+    }
+
+    // Write the round's pro-rata elections into its investor allocations.
+    // Full takes the whole entitlement, partial is capped at it, and a
+    // declined investor drops out of the round.
+    pub fn apply_pro_rata(&self, round: &mut FundingRound) {
+        for election in &round.pro_rata {
+            let entitlement = self.pro_rata_entitlement(&election.investor, round.amount);
+            let amount = match election.participation {
+                ProRataParticipation::Full => entitlement,
+                ProRataParticipation::Partial { amount } => {
+                    amount.clamp(Decimal::ZERO, entitlement)
+                }
+                ProRataParticipation::Declined => Decimal::ZERO,
+            };
+
+            round.investors.retain(|a| a.investor != election.investor);
+            if amount > Decimal::ZERO {
+                round.investors.push(InvestorAllocation {
+                    investor: election.investor.clone(),
+                    amount,
+                });
+            }
+        }
+    }
+
+    pub fn share_class(&self, name: &str) -> Option<&ShareClass> {
+        self.share_classes.iter().find(|c| c.name == name)
+    }
+
+    // A round priced below a protected class's conversion price lowers that
+    // price, raising its conversion ratio, and the class's holders' as-converted
+    // counts grow to match.
+    fn apply_anti_dilution(&mut self, round: &FundingRound, round_price: Decimal, new_shares: u64) {
+        let broad_base = self.ledger.fully_diluted_shares();
+        let narrow_base: u64 = self
+            .share_classes
+            .iter()
+            .filter(|c| c.is_preferred())
+            .map(|c| self.ledger.shares_in_class(&c.name))
+            .sum();
+
+        for class_index in 0..self.share_classes.len() {
+            let class = &self.share_classes[class_index];
+            if round_price >= class.conversion_price {
+                continue;
+            }
+
+            let previous_price = class.conversion_price;
+            let new_price = match class.anti_dilution {
+                AntiDilution::None => continue,
+                AntiDilution::FullRatchet => round_price,
+                AntiDilution::BroadBasedWeightedAverage => calculate_weighted_average_price(
+                    previous_price,
+                    broad_base,
+                    round.amount,
+                    new_shares,
+                ),
+                AntiDilution::NarrowBasedWeightedAverage => calculate_weighted_average_price(
+                    previous_price,
+                    narrow_base,
+                    round.amount,
+                    new_shares,
+                ),
+            };
+            let class_name = class.name.clone();
+
+            let founders_before = self
+                .ledger
+                .percentage(self.ledger.shares_of_kind(HolderKind::Founder));
+
+            let ratio = previous_price / new_price;
+            let (holders, counts): (Vec<String>, Vec<Decimal>) = self
+                .ledger
+                .holdings
+                .iter()
+                .filter(|h| h.share_class == class_name)
+                .map(|h| {
+                    (
+                        h.holder.clone(),
+                        Decimal::from(h.shares) * (ratio - Decimal::ONE),
+                    )
+                })
+                .unzip();
+            let extras: Vec<(String, u64)> =
+                holders.into_iter().zip(reconcile_shares(&counts)).collect();
+            for (holder, shares) in &extras {
+                self.ledger.issue_in_class(
+                    holder,
+                    HolderKind::Investor,
+                    &class_name,
+                    *shares,
+                    Decimal::ZERO,
+                );
+            }
+
+            let founders_after = self
+                .ledger
+                .percentage(self.ledger.shares_of_kind(HolderKind::Founder));
+
+            self.share_classes[class_index].conversion_price = new_price;
+            self.anti_dilution_adjustments.push(AntiDilutionAdjustment {
+                round_name: round.name.clone(),
+                share_class: class_name,
+                previous_conversion_price: previous_price,
+                new_conversion_price: new_price,
+                additional_shares: extras.iter().map(|(_, shares)| shares).sum(),
+                founder_dilution: founders_before - founders_after,
+            });
+        }
+    }
+
+    // SAFEs and qualifying notes convert together, alongside any pre-money
+    // pool top-up. Post-money SAFE cap prices are measured against the company
+    // capitalization including every conversion share and the pool increase,
+    // notes' cap prices against the pre-money shares, and the round price
+    // against the pre-money shares including the conversions and the pool, so
+    // iterate until the share counts settle.
+    fn convert_instruments(&mut self, round: &FundingRound, share_class: &str) -> SharesIssued {
+        let (notes, remaining): (Vec<ConvertibleNote>, Vec<ConvertibleNote>) = self
+            .notes
+            .drain(..)
+            .partition(|note| note.converts_in(round));
+        self.notes = remaining;
+
+        let existing_shares = Decimal::from(self.ledger.fully_diluted_shares());
+        let existing_pool = Decimal::from(self.ledger.shares_of_kind(HolderKind::OptionPool));
+        let note_amounts: Vec<Decimal> = notes
+            .iter()
+            .map(|note| note.accrued_amount(round.close_date))
+            .collect();
+        let mut conversion_shares = vec![Decimal::ZERO; self.safes.len() + notes.len()];
+        let mut pool_shares = Decimal::ZERO;
+
+        for _ in 0..100 {
+            let pre_pool_shares = existing_shares + conversion_shares.iter().sum::<Decimal>();
+            // A fixed price values the company at whatever it is issued against
+            let valuation = match round.fixed_price() {
+                Some(price) => price * (pre_pool_shares + pool_shares),
+                None => round.valuation,
+            };
+            let next_pool = match round.esop_target {
+                Some(target) => calculate_pool_top_up(
+                    target,
+                    valuation,
+                    round.amount,
+                    existing_pool,
+                    pre_pool_shares,
+                ),
+                None => Decimal::ZERO,
+            };
+            let capitalization = pre_pool_shares + next_pool;
+            let round_price = round
+                .fixed_price()
+                .unwrap_or(round.valuation / capitalization);
+
+            let safe_shares = self
+                .safes
+                .iter()
+                .map(|safe| safe.investment / safe.conversion_price(round_price, capitalization));
+            let note_shares = notes
+                .iter()
+                .zip(&note_amounts)
+                .map(|(note, amount)| amount / note.conversion_price(round_price, existing_shares));
+            let next: Vec<Decimal> = safe_shares.chain(note_shares).collect();
+
+            let change = next
+                .iter()
+                .zip(&conversion_shares)
+                .map(|(a, b)| (a - b).abs())
+                .fold((next_pool - pool_shares).abs(), Decimal::max);
+            conversion_shares = next;
+            pool_shares = next_pool;
+
+            if change < dec!(0.5) {
+                break;
+            }
+        }
+
+        let safe_count = self.safes.len();
+        let converted: Vec<(String, Decimal)> = self
+            .safes
+            .drain(..)
+            .map(|safe| (safe.holder, safe.investment))
+            .chain(notes.into_iter().map(|note| note.holder).zip(note_amounts))
+            .collect();
+        let mut issued = SharesIssued::default();
+        for (index, ((holder, invested), shares)) in converted
+            .iter()
+            .zip(reconcile_shares(&conversion_shares))
+            .enumerate()
+        {
+            self.ledger.issue_in_class(
+                holder,
+                HolderKind::Investor,
+                share_class,
+                shares,
+                *invested,
+            );
+            if index < safe_count {
+                issued.safe_conversion += shares;
+            } else {
+                issued.note_conversion += shares;
+            }
+        }
+
+        // The nearest whole share to the target, rather than the share below it
+        issued.option_pool = whole_shares(pool_shares.round());
+        self.ledger
+            .issue(ESOP_POOL_HOLDER, HolderKind::OptionPool, issued.option_pool);
+        issued
+    }
+
+    // Founders who left before the exit keep only what had vested when they
+    // left; the rest is repurchased and drops out of the distribution. Founders
+    // still at the company vest to the exit date, accelerate on a change of
+    // control, and any remaining unvested shares are paid out only as they vest.
+    // A founder let go at closing is let go after the change of control, so
+    // accelerates first and forfeits only what is still unvested.
+    pub fn calculate_exit_scenario(
+        &self,
+        exit_valuation: Decimal,
+        exit_type: ExitType,
+        exit_date: NaiveDate,
+    ) -> ExitScenario {
+        let mut vesting_status = Vec::new();
+        let mut outstanding = self.clone();
+        outstanding.settle_matured_notes(exit_date);
+
+        for founder in &self.founders {
+            let departed = founder.departure_date.filter(|date| *date <= exit_date);
+            let left_before_exit = departed.is_some_and(|date| date < exit_date);
+            let (vested, accelerated) = match &founder.vesting {
+                Some(schedule) => {
+                    let vested =
+                        schedule.vested_shares(founder.shares, departed.unwrap_or(exit_date));
+                    let accelerated = if !left_before_exit && exit_type.is_change_of_control() {
+                        schedule.accelerated_shares(
+                            founder.shares - vested,
+                            exit_date,
+                            founder.departure_date,
+                        )
+                    } else {
+                        0
+                    };
+                    (vested, accelerated)
+                }
+                None => (founder.shares, 0),
+            };
+            let unvested = founder.shares - vested - accelerated;
+
+            if departed.is_some() && unvested > 0 {
+                for holding in outstanding
+                    .ledger
+                    .holdings
+                    .iter_mut()
+                    .filter(|h| h.kind == HolderKind::Founder && h.holder == founder.name)
+                {
+                    holding.shares = holding.shares.saturating_sub(unvested);
+                }
+            }
+
+            vesting_status.push((vested, accelerated, unvested, departed.is_some()));
+        }
+
+        let distribution = distribute(&outstanding, exit_valuation, exit_date);
+        let mut founder_payouts = Vec::new();
+
+        for (founder, (vested, accelerated, unvested, departed)) in
+            self.founders.iter().zip(vesting_status)
+        {
+            let total_payout: Decimal = distribution
+                .iter()
+                .filter(|p| p.kind == HolderKind::Founder && p.holder == founder.name)
+                .map(|p| p.payout_amount)
+                .sum();
+            let paid_shares = if departed {
+                vested + accelerated
+            } else {
+                founder.shares
+            };
+            let per_share = if paid_shares > 0 {
+                total_payout / Decimal::from(paid_shares)
+            } else {
+                Decimal::ZERO
+            };
+            let dilution_impact = founder.initial_ownership - founder.current_ownership;
+
+            founder_payouts.push(FounderPayout {
+                founder_name: founder.name.clone(),
+                ownership_at_exit: founder.current_ownership,
+                shares_at_exit: founder.shares,
+                payout_amount: per_share * Decimal::from(vested + accelerated),
                 dilution_impact,
+                vested_shares: vested,
+                accelerated_shares: accelerated,
+                unvested_shares: unvested,
+                unvested_payout: if departed {
+                    Decimal::ZERO
+                } else {
+                    per_share * Decimal::from(unvested)
+                },
             });
         }
 
         ExitScenario {
-            name: format!("${:.1}M Exit", exit_valuation / 1_000_000.0),
+            name: format!("${:.1}M Exit", exit_valuation / dec!(1_000_000)),
             exit_valuation,
-            exit_type: ExitType::Acquisition,
+            exit_type,
+            exit_date,
             founder_payouts,
+            distribution,
+            note_settlements: outstanding.note_settlements,
+        }
+    }
+}
+
+impl ScenarioLibrary {
+    // Saving under a name already in use replaces that scenario.
+    pub fn save(
+        &mut self,
+        name: &str,
+        company: &Company,
+        saved_at: NaiveDateTime,
+    ) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Scenario name is required".to_string());
+        }
+
+        self.scenarios.retain(|s| s.name != name);
+        self.scenarios.insert(
+            0,
+            Scenario {
+                name: name.to_string(),
+                saved_at,
+                company: company.clone(),
+            },
+        );
+        Ok(())
+    }
+
+    // Saves under `name`, or "name (2)", "name (3)" and so on when that is
+    // taken, so nothing already saved is replaced. Returns the name used.
+    pub fn save_new(
+        &mut self,
+        name: &str,
+        company: &Company,
+        saved_at: NaiveDateTime,
+    ) -> Result<String, String> {
+        let name = name.trim();
+        let free_name = (1..)
+            .map(|n| match n {
+                1 => name.to_string(),
+                n => format!("{} ({})", name, n),
+            })
+            .find(|candidate| self.open(candidate).is_none())
+            .expect("some name is free");
+        self.save(&free_name, company, saved_at)?;
+        Ok(free_name)
+    }
+
+    pub fn open(&self, name: &str) -> Option<&Company> {
+        self.scenarios
+            .iter()
+            .find(|s| s.name == name)
+            .map(|s| &s.company)
+    }
+
+    // The copy takes the first free "(copy)" name and returns it.
+    pub fn duplicate(&mut self, name: &str, saved_at: NaiveDateTime) -> Result<String, String> {
+        let company = self
+            .open(name)
+            .cloned()
+            .ok_or_else(|| format!("There is no scenario named {}", name))?;
+
+        let copy_name = (1..)
+            .map(|n| match n {
+                1 => format!("{} (copy)", name),
+                n => format!("{} (copy {})", name, n),
+            })
+            .find(|candidate| self.open(candidate).is_none())
+            .expect("some copy name is free");
+        self.save(&copy_name, &company, saved_at)?;
+        Ok(copy_name)
+    }
+
+    pub fn delete(&mut self, name: &str) {
+        self.scenarios.retain(|s| s.name != name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{company, date, round};
+
+    // $500K at 10% simple, issued 2021 and due a year later.
+    fn note(maturity_behavior: MaturityBehavior) -> Company {
+        let terms = NoteTerms {
+            interest_rate: dec!(10),
+            interest_type: InterestType::Simple,
+            issue_date: date(2021, 1, 1),
+            maturity_date: date(2022, 1, 1),
+            valuation_cap: dec!(5_000_000),
+            discount_rate: Decimal::ZERO,
+            qualified_financing: dec!(1_000_000),
+            maturity_behavior,
+        };
+        let mut company = company();
+        let bridge = round(
+            "Bridge",
+            date(2021, 1, 1),
+            dec!(500_000),
+            dec!(4_000_000),
+            DealType::ConvertibleNote(terms),
+        );
+        company.add_round(bridge).unwrap();
+        company
+    }
+
+    #[test]
+    fn matured_note_is_repaid_without_a_later_round() {
+        let company = note(MaturityBehavior::Repay)
+            .as_of(date(2023, 1, 1))
+            .unwrap();
+
+        assert!(company.notes.is_empty());
+        let settlement = &company.note_settlements[0];
+        assert_eq!(settlement.date, date(2022, 1, 1));
+        assert!(
+            matches!(settlement.outcome, NoteOutcome::Repaid { amount } if amount == dec!(550_000))
+        );
+    }
+
+    #[test]
+    fn matured_note_converts_at_its_cap_before_an_exit() {
+        let company = note(MaturityBehavior::ConvertAtCap);
+        assert_eq!(company.notes.len(), 1);
+
+        // $550K at $0.50 a share
+        let exit = company.calculate_exit_scenario(
+            dec!(10_000_000),
+            ExitType::Acquisition,
+            date(2023, 1, 1),
+        );
+        assert!(matches!(
+            exit.note_settlements[0].outcome,
+            NoteOutcome::Converted { shares: 1_100_000 }
+        ));
+        let holder = exit
+            .distribution
+            .iter()
+            .find(|p| p.holder == "Bridge Investor")
+            .unwrap();
+        assert_eq!(holder.shares, 1_100_000);
+        assert_eq!(holder.share_class, COMMON_CLASS);
+    }
+
+    // Founder 1 is halfway through four-year vesting at the start of 2022,
+    // with full double-trigger acceleration.
+    fn founder_let_go(departure_date: NaiveDate) -> FounderPayout {
+        let mut company = company();
+        company.founders[0].vesting = Some(VestingSchedule {
+            start_date: date(2020, 1, 1),
+            cliff_months: 12,
+            total_months: 48,
+            cadence: VestingCadence::Monthly,
+            acceleration: Acceleration::DoubleTrigger {
+                percentage: HUNDRED,
+            },
+        });
+        company.founders[0].departure_date = Some(departure_date);
+        let exit = company.calculate_exit_scenario(
+            dec!(10_000_000),
+            ExitType::Acquisition,
+            date(2022, 1, 1),
+        );
+        exit.founder_payouts[0].clone()
+    }
+
+    #[test]
+    fn founder_let_go_at_closing_accelerates() {
+        let payout = founder_let_go(date(2022, 1, 1));
+        assert_eq!(payout.vested_shares, 2_500_000);
+        assert_eq!(payout.accelerated_shares, 2_500_000);
+        assert_eq!(payout.unvested_shares, 0);
+        assert_eq!(payout.payout_amount, dec!(5_000_000));
+
+        let later = founder_let_go(date(2022, 1, 2));
+        assert_eq!(later.accelerated_shares, 2_500_000);
+        assert_eq!(later.payout_amount, dec!(5_000_000));
+    }
+
+    #[test]
+    fn founder_who_left_before_closing_forfeits_unvested_shares() {
+        let payout = founder_let_go(date(2021, 12, 31));
+        assert_eq!(payout.accelerated_shares, 0);
+        assert_eq!(payout.unvested_shares, 2_604_167);
+        assert_eq!(payout.unvested_payout, Decimal::ZERO);
+    }
+
+    #[test]
+    fn note_before_maturity_stays_outstanding() {
+        let company = note(MaturityBehavior::Repay)
+            .as_of(date(2021, 6, 1))
+            .unwrap();
+
+        assert_eq!(company.notes.len(), 1);
+        assert!(company.note_settlements.is_empty());
+    }
+
+    #[test]
+    fn save_new_keeps_what_is_already_saved() {
+        let saved_at = date(2024, 3, 1).and_hms_opt(9, 30, 0).unwrap();
+        let mut library = ScenarioLibrary::default();
+        let first = company();
+        let second = Company {
+            name: "Second".to_string(),
+            ..company()
+        };
+
+        assert_eq!(
+            library.save_new("Backup", &first, saved_at),
+            Ok("Backup".to_string())
+        );
+        assert_eq!(
+            library.save_new("Backup", &second, saved_at),
+            Ok("Backup (2)".to_string())
+        );
+        assert_eq!(library.open("Backup").unwrap().name, first.name);
+        assert_eq!(library.open("Backup (2)").unwrap().name, "Second");
+    }
+
+    #[test]
+    fn founder_edits_reprice_later_rounds() {
+        let mut company = company();
+        company
+            .add_round(round(
+                "Seed",
+                date(2021, 1, 1),
+                dec!(1_000_000),
+                dec!(4_000_000),
+                DealType::PreferredStock,
+            ))
+            .unwrap();
+        assert_eq!(company.ledger.shares_of("Seed Investor"), 2_500_000);
+
+        company.add_founder("Founder 3").unwrap();
+        company.set_founder_ownership(2, dec!(25)).unwrap();
+        assert_eq!(company.founders[2].shares, 2_500_000);
+        assert_eq!(company.round_history[0].price_per_share, dec!(0.32));
+        assert_eq!(company.ledger.shares_of("Seed Investor"), 3_125_000);
+
+        company.remove_founder(2).unwrap();
+        assert_eq!(company.ledger.shares_of("Founder 3"), 0);
+        assert_eq!(company.ledger.shares_of("Seed Investor"), 2_500_000);
+        assert!(company.remove_founder(2).is_err());
+    }
+
+    #[test]
+    fn round_shares_tie_out_to_the_share() {
+        let mut seed = round(
+            "Seed",
+            date(2021, 1, 1),
+            dec!(1_000_000),
+            dec!(4_000_000),
+            DealType::PreferredStock,
+        );
+        seed.investors = ["Fund A", "Fund B", "Fund C"]
+            .iter()
+            .zip([dec!(333_333.33), dec!(333_333.33), dec!(333_333.34)])
+            .map(|(investor, amount)| InvestorAllocation {
+                investor: investor.to_string(),
+                amount,
+            })
+            .collect();
+        let mut company = company();
+        company.add_round(seed).unwrap();
+
+        // $1M at $0.40 is 2.5M shares; the largest remainder takes the odd one
+        let shares: Vec<u64> = ["Fund A", "Fund B", "Fund C"]
+            .iter()
+            .map(|investor| company.ledger.shares_of(investor))
+            .collect();
+        assert_eq!(shares, vec![833_333, 833_333, 833_334]);
+        assert_eq!(company.ledger.fully_diluted_shares(), 12_500_000);
+        assert_eq!(company.round_history[0].shares_issued.new_money, 2_500_000);
+    }
+
+    // A $1M SAFE, then a $2M Series A at $20M pre-money.
+    fn safe_then_series_a(valuation_cap: Decimal, discount_rate: Decimal) -> Company {
+        let terms = SafeTerms {
+            valuation_cap,
+            discount_rate,
+            mfn: false,
+        };
+        let mut company = company();
+        company
+            .add_round(round(
+                "Pre-Seed",
+                date(2021, 1, 1),
+                dec!(1_000_000),
+                dec!(4_000_000),
+                DealType::SAFE(terms),
+            ))
+            .unwrap();
+        assert_eq!(company.safes.len(), 1);
+
+        let series_a = round(
+            "Series A",
+            date(2022, 1, 1),
+            dec!(2_000_000),
+            dec!(20_000_000),
+            DealType::PreferredStock,
+        );
+        company.add_round(series_a).unwrap();
+        company
+    }
+
+    #[test]
+    fn safe_converts_at_its_post_money_cap() {
+        let company = safe_then_series_a(dec!(5_000_000), Decimal::ZERO);
+
+        // $1M of a $5M post-money cap is a fifth of the 12.5M shares before
+        // the new money
+        assert!(company.safes.is_empty());
+        let shares = company.ledger.shares_of("Pre-Seed Investor");
+        assert!((2_499_999..=2_500_000).contains(&shares), "{}", shares);
+        let converted = company
+            .ledger
+            .holdings
+            .iter()
+            .find(|h| h.holder == "Pre-Seed Investor")
+            .unwrap();
+        assert_eq!(converted.share_class, preferred_class_name("Series A"));
+        assert_eq!(converted.invested, dec!(1_000_000));
+    }
+
+    #[test]
+    fn uncapped_safe_converts_at_its_discount() {
+        let company = safe_then_series_a(Decimal::ZERO, dec!(20));
+
+        // At 80% of the round price the SAFE buys a sixteenth of the
+        // 10,666,667 shares before the new money
+        let shares = company.ledger.shares_of("Pre-Seed Investor");
+        assert!((666_666..=666_667).contains(&shares), "{}", shares);
+    }
+
+    #[test]
+    fn each_round_records_one_price_and_its_share_counts() {
+        let company = safe_then_series_a(dec!(5_000_000), Decimal::ZERO);
+        let (safe_round, series_a) = (&company.round_history[0], &company.round_history[1]);
+
+        // A SAFE sells no shares, so it has no price and moves no counts
+        assert_eq!(safe_round.price_per_share, Decimal::ZERO);
+        assert_eq!(safe_round.pre_round_shares, 10_000_000);
+        assert_eq!(safe_round.post_round_shares, 10_000_000);
+
+        // $20M over the 12.5M shares after the SAFE converts is $1.60, and the
+        // round records the price its investors paid
+        assert_eq!(series_a.price_per_share.round_dp(4), dec!(1.6));
+        assert_eq!(
+            company.funding_rounds[1].price_per_share,
+            series_a.price_per_share
+        );
+        assert_eq!(series_a.pre_round_shares, 10_000_000);
+        let issued = &series_a.shares_issued;
+        assert!(
+            (2_499_999..=2_500_000).contains(&issued.safe_conversion),
+            "{}",
+            issued.safe_conversion
+        );
+        assert!(
+            (1_249_999..=1_250_000).contains(&issued.new_money),
+            "{}",
+            issued.new_money
+        );
+        assert_eq!(issued.note_conversion + issued.option_pool, 0);
+        assert_eq!(
+            series_a.post_round_shares,
+            series_a.pre_round_shares + issued.safe_conversion + issued.new_money
+        );
+        assert_eq!(
+            series_a.post_round_shares,
+            company.ledger.fully_diluted_shares()
+        );
+
+        // Replaying the recorded terms lands on the same round
+        let mut replayed = company.clone();
+        replayed.edit_rounds(|_| {}).unwrap();
+        let recorded = &replayed.funding_rounds[1];
+        assert_eq!(recorded.price_per_share, series_a.price_per_share);
+        assert_eq!(recorded.valuation, dec!(20_000_000));
+        assert_eq!(
+            replayed.round_history[1].post_round_shares,
+            series_a.post_round_shares
+        );
+    }
+
+    #[test]
+    fn round_entered_by_price_issues_at_that_price_after_a_safe() {
+        let mut company = company();
+        company
+            .add_round(round(
+                "Pre-Seed",
+                date(2021, 1, 1),
+                dec!(1_000_000),
+                Decimal::ZERO,
+                DealType::SAFE(SafeTerms {
+                    valuation_cap: dec!(5_000_000),
+                    discount_rate: Decimal::ZERO,
+                    mfn: false,
+                }),
+            ))
+            .unwrap();
+        let mut series_a = round(
+            "Series A",
+            date(2022, 1, 1),
+            dec!(2_000_000),
+            Decimal::ZERO,
+            DealType::PreferredStock,
+        );
+        series_a.input = RoundInput::AmountAndPrice;
+        series_a.price_per_share = dec!(1.00);
+        company.add_round(series_a).unwrap();
+
+        // The SAFE converts into 2.5M shares ahead of the round, so $1.00 over
+        // 12.5M shares is a $12.5M pre-money rather than the $10M the founders'
+        // shares alone would give
+        let snapshot = company.round_history.last().unwrap();
+        assert_eq!(snapshot.price_per_share, dec!(1.00));
+        assert_eq!(snapshot.shares_issued.new_money, 2_000_000);
+        let issued = company.funding_rounds.last().unwrap();
+        assert_eq!(issued.price_per_share, dec!(1.00));
+        assert!(
+            (issued.valuation - dec!(12_500_000)).abs() <= dec!(1),
+            "{}",
+            issued.valuation
+        );
+        assert_eq!(issued.equity_sold.round_dp(2), dec!(13.79));
+    }
+
+    #[test]
+    fn price_and_ownership_cannot_both_hold_over_a_safe() {
+        let mut company = safe_then_series_a(dec!(5_000_000), Decimal::ZERO);
+        let mut company_with_safe = company.clone();
+        company_with_safe
+            .add_round(round(
+                "Bridge",
+                date(2022, 6, 1),
+                dec!(500_000),
+                dec!(20_000_000),
+                DealType::SAFE(SafeTerms {
+                    valuation_cap: dec!(25_000_000),
+                    discount_rate: Decimal::ZERO,
+                    mfn: false,
+                }),
+            ))
+            .unwrap();
+
+        let mut series_b = round(
+            "Series B",
+            date(2023, 1, 1),
+            dec!(2_000_000),
+            Decimal::ZERO,
+            DealType::PreferredStock,
+        );
+        series_b.input = RoundInput::PriceAndOwnership;
+        series_b.price_per_share = dec!(2.00);
+        series_b.equity_sold = dec!(10);
+
+        let error = company_with_safe.add_round(series_b.clone()).unwrap_err();
+        assert!(error.starts_with("Series B: SAFEs, notes"), "{}", error);
+        assert_eq!(company_with_safe.funding_rounds.len(), 3);
+
+        // With nothing converting the pair prices the round on its own
+        company.add_round(series_b).unwrap();
+        assert_eq!(
+            company.round_history.last().unwrap().price_per_share,
+            dec!(2.00)
+        );
+    }
+
+    #[test]
+    fn note_converts_with_interest_in_a_qualified_round() {
+        let mut company = note(MaturityBehavior::Repay);
+        let close_date = date(2021, 7, 2);
+        let accrued = company.notes[0].accrued_amount(close_date);
+        let series_a = round(
+            "Series A",
+            close_date,
+            dec!(2_000_000),
+            dec!(20_000_000),
+            DealType::PreferredStock,
+        );
+        company.add_round(series_a).unwrap();
+
+        // The cap prices the note at $5M over the 10M pre-money shares
+        assert!(company.notes.is_empty());
+        assert_eq!(
+            company.ledger.shares_of("Bridge Investor"),
+            whole_shares(accrued / dec!(0.5))
+        );
+        assert_eq!(
+            company.round_history[1].shares_issued.note_conversion,
+            whole_shares(accrued / dec!(0.5))
+        );
+    }
+
+    #[test]
+    fn note_stays_outstanding_through_an_unqualified_round() {
+        let mut company = note(MaturityBehavior::Repay);
+        company
+            .add_round(round(
+                "Angel",
+                date(2021, 7, 2),
+                dec!(500_000),
+                dec!(4_000_000),
+                DealType::PreferredStock,
+            ))
+            .unwrap();
+
+        assert_eq!(company.notes.len(), 1);
+        assert_eq!(company.ledger.shares_of("Bridge Investor"), 0);
+    }
+
+    // A Series A at $0.80 a share protected by `policy`, then a Series B
+    // priced from `series_b_pre_money` over the 12.5M shares after the A.
+    fn down_round(policy: AntiDilution, series_b_pre_money: Decimal) -> Company {
+        let mut series_a = round(
+            "Series A",
+            date(2021, 1, 1),
+            dec!(2_000_000),
+            dec!(8_000_000),
+            DealType::PreferredStock,
+        );
+        series_a.anti_dilution = policy;
+        let mut company = company();
+        company.add_round(series_a).unwrap();
+        company
+            .add_round(round(
+                "Series B",
+                date(2022, 1, 1),
+                dec!(1_000_000),
+                series_b_pre_money,
+                DealType::PreferredStock,
+            ))
+            .unwrap();
+        company
+    }
+
+    #[test]
+    fn full_ratchet_reprices_to_the_down_round() {
+        let company = down_round(AntiDilution::FullRatchet, dec!(5_000_000));
+
+        // $0.80 to $0.40 doubles the A's 2.5M shares
+        let adjustment = &company.anti_dilution_adjustments[0];
+        assert_eq!(adjustment.previous_conversion_price, dec!(0.8));
+        assert_eq!(adjustment.new_conversion_price, dec!(0.4));
+        assert_eq!(adjustment.additional_shares, 2_500_000);
+        assert_eq!(company.ledger.shares_of("Series A Investor"), 5_000_000);
+        assert_eq!(
+            company.round_history[1].shares_issued.anti_dilution,
+            2_500_000
+        );
+    }
+
+    #[test]
+    fn broad_based_weighted_average_counts_every_share() {
+        let company = down_round(AntiDilution::BroadBasedWeightedAverage, dec!(5_000_000));
+
+        // 0.80 * (12.5M + 1.25M) / (12.5M + 2.5M) = 0.7333..., so the A's
+        // 2.5M shares become 2.5M * 15 / 13.75
+        let adjustment = &company.anti_dilution_adjustments[0];
+        assert_eq!(adjustment.new_conversion_price.round_dp(6), dec!(0.733333));
+        assert_eq!(adjustment.additional_shares, 227_272);
+    }
+
+    #[test]
+    fn narrow_based_weighted_average_counts_preferred_only() {
+        let company = down_round(AntiDilution::NarrowBasedWeightedAverage, dec!(5_000_000));
+
+        // 0.80 * (2.5M + 1.25M) / (2.5M + 2.5M) = 0.60
+        let adjustment = &company.anti_dilution_adjustments[0];
+        assert_eq!(adjustment.new_conversion_price, dec!(0.6));
+        assert_eq!(adjustment.additional_shares, 833_333);
+    }
+
+    #[test]
+    fn up_round_issues_no_protection_shares() {
+        let company = down_round(AntiDilution::FullRatchet, dec!(15_000_000));
+
+        assert!(company.anti_dilution_adjustments.is_empty());
+        assert_eq!(company.ledger.shares_of("Series A Investor"), 2_500_000);
+        assert_eq!(company.round_history[1].shares_issued.anti_dilution, 0);
+    }
+
+    // A seed round that leaves a 10% post-money pool.
+    fn company_with_pool() -> Company {
+        let mut seed = round(
+            "Seed",
+            date(2021, 1, 1),
+            dec!(1_000_000),
+            dec!(4_000_000),
+            DealType::PreferredStock,
+        );
+        seed.esop_allocation = dec!(10);
+        let mut company = company();
+        company.add_round(seed).unwrap();
+        company
+    }
+
+    fn grant(grantee: &str, shares: u64, board_approved: bool) -> OptionGrant {
+        OptionGrant {
+            grantee: grantee.to_string(),
+            shares,
+            strike_price: dec!(0.1),
+            grant_date: date(2021, 6, 1),
+            vesting: Some(VestingSchedule {
+                start_date: date(2021, 6, 1),
+                cliff_months: 12,
+                total_months: 48,
+                cadence: VestingCadence::Monthly,
+                acceleration: Acceleration::None,
+            }),
+            board_approved,
+            exercised_shares: 0,
+            forfeited_shares: 0,
+        }
+    }
+
+    #[test]
+    fn grants_come_out_of_the_pool() {
+        let mut company = company_with_pool();
+        let pool = company.available_pool_shares();
+        let fully_diluted = company.ledger.fully_diluted_shares();
+        assert_eq!(pool, company.ledger.shares_of_kind(HolderKind::OptionPool));
+        assert!(pool > 100_000);
+
+        company.grant_options(grant("Eve", 100_000, true)).unwrap();
+        assert_eq!(company.available_pool_shares(), pool - 100_000);
+        assert_eq!(company.ledger.shares_of("Eve"), 100_000);
+        assert_eq!(company.ledger.fully_diluted_shares(), fully_diluted);
+    }
+
+    #[test]
+    fn grants_cannot_exhaust_the_pool() {
+        let mut company = company_with_pool();
+        let pool = company.available_pool_shares();
+
+        let error = company
+            .grant_options(grant("Eve", pool + 1, true))
+            .unwrap_err();
+        assert_eq!(
+            error,
+            format!(
+                "Grant of {} options to Eve exceeds the {} available in the pool",
+                pool + 1,
+                pool
+            )
+        );
+
+        // Unapproved grants already hold their options back
+        company.grant_options(grant("Eve", pool, false)).unwrap();
+        assert_eq!(company.available_pool_shares(), 0);
+        assert!(company.grant_options(grant("Finn", 1, true)).is_err());
+        assert!(company.grant_options(grant("Finn", 0, true)).is_err());
+    }
+
+    #[test]
+    fn only_vested_options_exercise_and_the_rest_return_on_termination() {
+        let mut company = company_with_pool();
+        let pool = company.available_pool_shares();
+        company.grant_options(grant("Eve", 480_000, true)).unwrap();
+
+        // Two years in, half has vested
+        assert!(company
+            .exercise_options(0, 240_001, date(2023, 6, 1))
+            .is_err());
+        company
+            .exercise_options(0, 240_000, date(2023, 6, 1))
+            .unwrap();
+        let exercised = company
+            .ledger
+            .holdings
+            .iter()
+            .find(|h| h.holder == "Eve" && h.kind == HolderKind::Employee)
+            .unwrap();
+        assert_eq!(exercised.shares, 240_000);
+        assert_eq!(exercised.invested, dec!(24_000));
+
+        company.terminate_grant(0, date(2023, 6, 1));
+        assert_eq!(company.esop_pool.grants[0].forfeited_shares, 240_000);
+        assert_eq!(company.available_pool_shares(), pool - 240_000);
+    }
+
+    fn preferred_round(name: &str, close_date: NaiveDate, seniority: Seniority) -> FundingRound {
+        let mut preferred = round(
+            name,
+            close_date,
+            dec!(1_000_000),
+            dec!(4_000_000),
+            DealType::PreferredStock,
+        );
+        preferred.liquidation_preference = Some(LiquidationPreference {
+            multiple: Decimal::ONE,
+            participation: Participation::NonParticipating,
+            seniority,
+        });
+        preferred
+    }
+
+    #[test]
+    fn each_preferred_round_gets_its_own_class_ranked_by_seniority() {
+        let mut company = company();
+        for (name, year, seniority) in [
+            ("Series A", 2021, Seniority::Senior),
+            ("Series B", 2022, Seniority::Senior),
+            ("Series C", 2023, Seniority::PariPassu),
+        ] {
+            company
+                .add_round(preferred_round(name, date(year, 1, 1), seniority))
+                .unwrap();
+        }
+
+        let ranks: Vec<(String, u32)> = company
+            .share_classes
+            .iter()
+            .map(|c| (c.name.clone(), c.seniority_rank))
+            .collect();
+        assert_eq!(
+            ranks,
+            vec![
+                (COMMON_CLASS.to_string(), 0),
+                (preferred_class_name("Series A"), 1),
+                (preferred_class_name("Series B"), 2),
+                (preferred_class_name("Series C"), 2),
+            ]
+        );
+
+        let series_a = company
+            .share_class(&preferred_class_name("Series A"))
+            .unwrap();
+        assert_eq!(series_a.original_issue_price, dec!(0.4));
+        assert_eq!(series_a.conversion_price, dec!(0.4));
+        assert_eq!(series_a.votes_per_share, Decimal::ONE);
+        assert_eq!(
+            company
+                .ledger
+                .shares_in_class(&preferred_class_name("Series A")),
+            2_500_000
+        );
+    }
+
+    #[test]
+    fn common_rounds_add_no_class() {
+        let mut company = company();
+        company
+            .add_round(round(
+                "Friends",
+                date(2021, 1, 1),
+                dec!(1_000_000),
+                dec!(4_000_000),
+                DealType::Equity,
+            ))
+            .unwrap();
+
+        assert_eq!(company.share_classes.len(), 1);
+        assert_eq!(company.ledger.shares_in_class(COMMON_CLASS), 12_500_000);
+    }
+
+    fn allocation(investor: &str, amount: Decimal) -> InvestorAllocation {
+        InvestorAllocation {
+            investor: investor.to_string(),
+            amount,
         }
     }
+
+    #[test]
+    fn each_investor_buys_with_their_own_check() {
+        let mut seed = round(
+            "Seed",
+            date(2021, 1, 1),
+            dec!(1_000_000),
+            dec!(4_000_000),
+            DealType::PreferredStock,
+        );
+        seed.investors = vec![
+            allocation("Lead", dec!(750_000)),
+            allocation("Angel", dec!(250_000)),
+        ];
+        let mut company = company();
+        company.add_round(seed).unwrap();
+
+        // At $0.40 a share
+        let bought = |holder: &str| {
+            let holding = company
+                .ledger
+                .holdings
+                .iter()
+                .find(|h| h.holder == holder)
+                .unwrap();
+            (holding.shares, holding.invested)
+        };
+        assert_eq!(bought("Lead"), (1_875_000, dec!(750_000)));
+        assert_eq!(bought("Angel"), (625_000, dec!(250_000)));
+    }
+
+    // Lead buys 20% of the company in a seed round that grants pro-rata rights.
+    fn company_with_lead() -> Company {
+        let mut seed = round(
+            "Seed",
+            date(2021, 1, 1),
+            dec!(1_000_000),
+            dec!(4_000_000),
+            DealType::PreferredStock,
+        );
+        seed.investors = vec![allocation("Lead", dec!(1_000_000))];
+        seed.pro_rata_rights = true;
+        let mut company = company();
+        company.add_round(seed).unwrap();
+        company
+    }
+
+    #[test]
+    fn pro_rata_entitlement_is_fully_diluted_stake_times_the_round() {
+        let company = company_with_lead();
+
+        assert_eq!(
+            company.pro_rata_entitlement("Lead", dec!(5_000_000)),
+            dec!(1_000_000)
+        );
+        assert_eq!(
+            company.pro_rata_entitlement("Founder 1", dec!(5_000_000)),
+            Decimal::ZERO
+        );
+        assert_eq!(
+            company.pro_rata_entitlement("Stranger", dec!(5_000_000)),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn pro_rata_elections_set_the_allocation() {
+        let company = company_with_lead();
+        let elect = |participation: ProRataParticipation| {
+            let mut series_a = round(
+                "Series A",
+                date(2022, 1, 1),
+                dec!(5_000_000),
+                dec!(20_000_000),
+                DealType::PreferredStock,
+            );
+            series_a.investors.push(allocation("Lead", dec!(3_000_000)));
+            series_a.pro_rata = vec![ProRataElection {
+                investor: "Lead".to_string(),
+                participation,
+            }];
+            company.apply_pro_rata(&mut series_a);
+            series_a
+                .investors
+                .iter()
+                .find(|a| a.investor == "Lead")
+                .map(|a| a.amount)
+        };
+
+        assert_eq!(elect(ProRataParticipation::Full), Some(dec!(1_000_000)));
+        assert_eq!(
+            elect(ProRataParticipation::Partial {
+                amount: dec!(400_000)
+            }),
+            Some(dec!(400_000))
+        );
+        assert_eq!(
+            elect(ProRataParticipation::Partial {
+                amount: dec!(2_000_000)
+            }),
+            Some(dec!(1_000_000))
+        );
+        assert_eq!(elect(ProRataParticipation::Declined), None);
+    }
+
+    #[test]
+    fn returning_investor_is_one_investor_across_rounds() {
+        let mut company = company_with_lead();
+        let mut series_a = round(
+            "Series A",
+            date(2022, 1, 1),
+            dec!(5_000_000),
+            dec!(20_000_000),
+            DealType::PreferredStock,
+        );
+        series_a.investors = vec![
+            allocation("New Fund", dec!(4_000_000)),
+            allocation("Lead", dec!(1_000_000)),
+        ];
+        company.add_round(series_a).unwrap();
+
+        assert_eq!(company.investors.len(), 2);
+        let lead = company
+            .investor_positions()
+            .into_iter()
+            .find(|p| p.name == "Lead")
+            .unwrap();
+        // 2.5M seed shares at $0.40 and 625K at $1.60
+        assert_eq!(lead.shares, 3_125_000);
+        assert_eq!(lead.invested, dec!(2_000_000));
+        assert_eq!(lead.rounds, vec!["Seed", "Series A"]);
+        assert!(company
+            .investors
+            .iter()
+            .any(|i| i.name == "Lead" && i.pro_rata_rights));
+        assert!(company
+            .investors
+            .iter()
+            .any(|i| i.name == "New Fund" && !i.pro_rata_rights));
+    }
 }
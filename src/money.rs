@@ -0,0 +1,114 @@
+// Money, prices and percentages are exact decimals rather than floats, and
+// share counts are whole numbers. Every decimal share count is rounded down
+// per holder; the shares that rounding leaves over go one at a time to the
+// largest remainders, so a group always receives exactly its total.
+pub use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+pub use rust_decimal::{Decimal, MathematicalOps};
+pub use rust_decimal_macros::dec;
+
+pub const HUNDRED: Decimal = Decimal::ONE_HUNDRED;
+
+// The whole shares a decimal count supports. Negative counts are nothing.
+pub fn whole_shares(count: Decimal) -> u64 {
+    count.floor().to_u64().unwrap_or(0)
+}
+
+// Split `total` whole shares in proportion to `weights`.
+pub fn allocate_shares(total: u64, weights: &[Decimal]) -> Vec<u64> {
+    let total_weight: Decimal = weights.iter().filter(|w| **w > Decimal::ZERO).sum();
+    if total_weight <= Decimal::ZERO {
+        return vec![0; weights.len()];
+    }
+
+    let exact: Vec<Decimal> = weights
+        .iter()
+        .map(|w| (*w).max(Decimal::ZERO) * Decimal::from(total) / total_weight)
+        .collect();
+    largest_remainder(&exact, total)
+}
+
+// Whole shares for each of a group's decimal counts, with the group as a
+// whole getting the whole shares its combined count supports.
+pub fn reconcile_shares(counts: &[Decimal]) -> Vec<u64> {
+    let total = whole_shares(counts.iter().filter(|c| **c > Decimal::ZERO).sum());
+    largest_remainder(counts, total)
+}
+
+// Round every count down, then hand the shares still owed to reach `total`
+// out one each, largest fractional part first and ties to the earlier holder.
+fn largest_remainder(exact: &[Decimal], total: u64) -> Vec<u64> {
+    let mut allocated: Vec<u64> = exact.iter().map(|e| whole_shares(*e)).collect();
+
+    let leftover = total.saturating_sub(allocated.iter().sum());
+    let mut by_remainder: Vec<usize> = (0..exact.len())
+        .filter(|&i| exact[i] > Decimal::ZERO)
+        .collect();
+    by_remainder.sort_by(|&a, &b| exact[b].fract().cmp(&exact[a].fract()));
+    for &i in by_remainder.iter().take(leftover as usize) {
+        allocated[i] += 1;
+    }
+    allocated
+}
+
+// `shares` as a percentage of `total`.
+pub fn percentage_of(shares: u64, total: u64) -> Decimal {
+    if total == 0 {
+        return Decimal::ZERO;
+    }
+    Decimal::from(shares) * HUNDRED / Decimal::from(total)
+}
+
+// Read a number typed into the UI.
+pub fn parse_decimal(input: &str) -> Option<Decimal> {
+    input.trim().parse::<Decimal>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leftover_shares_go_to_the_largest_remainders() {
+        let shares = reconcile_shares(&[dec!(1.2), dec!(2.7), dec!(3.1)]);
+        assert_eq!(shares, vec![1, 3, 3]);
+    }
+
+    #[test]
+    fn tied_remainders_go_to_the_earlier_holder() {
+        let shares = reconcile_shares(&[dec!(0.5), dec!(0.5), dec!(0.5), dec!(1.5)]);
+        assert_eq!(shares, vec![1, 1, 0, 1]);
+    }
+
+    #[test]
+    fn group_gets_the_whole_shares_its_total_supports() {
+        let thirds = vec![dec!(1_000_000) / dec!(3); 3];
+        let shares = reconcile_shares(&thirds);
+        assert_eq!(
+            shares.iter().sum::<u64>(),
+            whole_shares(thirds.iter().sum())
+        );
+        assert!(shares.iter().all(|s| (333_333..=333_334).contains(s)));
+    }
+
+    #[test]
+    fn negative_counts_get_nothing() {
+        assert_eq!(reconcile_shares(&[dec!(-1.5), dec!(2.5)]), vec![0, 2]);
+        assert_eq!(whole_shares(dec!(-3)), 0);
+    }
+
+    #[test]
+    fn allocation_splits_the_total_exactly() {
+        assert_eq!(
+            allocate_shares(10, &[Decimal::ONE, Decimal::ONE, Decimal::ONE]),
+            vec![4, 3, 3]
+        );
+        assert_eq!(
+            allocate_shares(7, &[dec!(2), Decimal::ZERO, dec!(-1)]),
+            vec![7, 0, 0]
+        );
+        assert_eq!(
+            allocate_shares(10, &[Decimal::ZERO, Decimal::ZERO]),
+            vec![0, 0]
+        );
+    }
+}
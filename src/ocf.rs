@@ -0,0 +1,1410 @@
+use crate::models::{
+    AntiDilution, Company, CompanyEvent, DealType, EventKind, Founder, FundingRound, HolderKind,
+    InterestType, InvestorAllocation, LiquidationPreference, MaturityBehavior, NoteTerms,
+    OptionGrant, Participation, RoundInput, SafeTerms, Seniority, ShareClass, COMMON_CLASS,
+    ESOP_POOL_HOLDER,
+};
+use crate::money::{parse_decimal, percentage_of, whole_shares, Decimal, ToPrimitive, HUNDRED};
+use chrono::{Months, NaiveDate};
+use serde_json::{json, Value};
+
+// Open Cap Table Format, the Open Cap Table Coalition's JSON objects for
+// exchanging cap tables. A company is written as one bundle holding the
+// issuer, its stakeholders, stock classes and option plan, and a transaction
+// for every security outstanding today. Reading takes that bundle, OCF files
+// (`{"file_type": ..., "items": [...]}`) or bare arrays of objects, as many as
+// make up the cap table, and replays the outstanding positions as events.
+//
+// OCF has nowhere to put vesting schedules, anti-dilution protection,
+// pro-rata rights or a note's maturity behaviour, and positions are not tied
+// to the rounds that sold them, so imported shares come in as direct
+// issuances, fully vested and unprotected. Uncapped participation and a
+// note's qualified financing have no field either; they go in a comment and
+// the trigger's description, and are read back from there.
+pub const OCF_VERSION: &str = "1.2.0";
+
+const PLAN_ID: &str = "plan-1";
+const PLAN_NAME: &str = "Equity Incentive Plan";
+const OPTION_TERM_MONTHS: u32 = 120; // not modelled; the usual ten years
+const DEFAULT_NOTE_TERM_MONTHS: u32 = 24;
+const PARTICIPATING_COMMENT: &str = "Participates with common after its preference, uncapped";
+const QUALIFIED_FINANCING: &str = "Equity financing of at least $";
+
+pub fn export_ocf(company: &Company) -> Result<String, String> {
+    let founded = company
+        .founding_date()
+        .ok_or_else(|| "The company has no founding date".to_string())?;
+
+    let holders = stakeholders(company);
+    let stakeholder_id = |name: &str| {
+        let index = holders.iter().position(|(holder, _)| holder == name);
+        format!("stakeholder-{}", index.unwrap_or(0) + 1)
+    };
+    let class_id = |name: &str| {
+        let index = company.share_classes.iter().position(|c| c.name == name);
+        format!("class-{}", index.unwrap_or(0) + 1)
+    };
+    let common_id = class_id(COMMON_CLASS);
+
+    let issuer = json!({
+        "object_type": "ISSUER",
+        "id": "issuer",
+        "legal_name": company.name,
+        "formation_date": founded.to_string(),
+        "country_of_formation": "US",
+    });
+
+    let stakeholder_objects: Vec<Value> = holders
+        .iter()
+        .map(|(name, relationship)| {
+            json!({
+                "object_type": "STAKEHOLDER",
+                "id": stakeholder_id(name),
+                "name": { "legal_name": name },
+                "stakeholder_type": if *relationship == "INVESTOR" { "INSTITUTION" } else { "INDIVIDUAL" },
+                "current_relationship": relationship,
+            })
+        })
+        .collect();
+
+    let stock_classes: Vec<Value> = company
+        .share_classes
+        .iter()
+        .enumerate()
+        .map(|(index, class)| {
+            // Common is authorized for everything that can become common; a
+            // preferred class for what its round sold
+            let authorized = if class.is_preferred() {
+                class.outstanding_shares(company.ledger.shares_in_class(&class.name))
+            } else {
+                company.total_shares
+            };
+            let mut object = json!({
+                "object_type": "STOCK_CLASS",
+                "id": class_id(&class.name),
+                "name": class.name,
+                "class_type": if class.is_preferred() { "PREFERRED" } else { "COMMON" },
+                "default_id_prefix": id_prefix(class, index),
+                "initial_shares_authorized": authorized.to_string(),
+                "votes_per_share": numeric(class.votes_per_share),
+                "seniority": class.seniority_rank.to_string(),
+                "price_per_share": money(class.original_issue_price),
+            });
+            if class.is_preferred() {
+                object["conversion_rights"] = json!([{
+                    "type": "STOCK_CLASS_CONVERSION_RIGHT",
+                    "conversion_mechanism": {
+                        "type": "RATIO_CONVERSION",
+                        "conversion_price": money(class.conversion_price),
+                        "ratio": { "numerator": numeric(class.conversion_ratio()), "denominator": "1" },
+                        "rounding_type": "NORMAL",
+                    },
+                    "converts_to_stock_class_id": common_id,
+                }]);
+            }
+            // OCF has no participation flag, so uncapped participation is
+            // noted in a comment rather than given a cap
+            if let Some(preference) = &class.preference {
+                object["liquidation_preference_multiple"] = json!(numeric(preference.multiple));
+                match preference.participation {
+                    Participation::NonParticipating => {}
+                    Participation::Participating => {
+                        object["comments"] = json!([PARTICIPATING_COMMENT])
+                    }
+                    Participation::CappedParticipating { cap_multiple } => {
+                        object["participation_cap_multiple"] = json!(numeric(cap_multiple))
+                    }
+                }
+            }
+            object
+        })
+        .collect();
+
+    let mut transactions = Vec::new();
+    let mut next_security = 0;
+    let mut security = |prefix: &str| {
+        next_security += 1;
+        (
+            format!("security-{}", next_security),
+            format!("{}{}", prefix, next_security),
+        )
+    };
+
+    // Shares: one issuance per holding, dated when the holder first appears
+    for holding in &company.ledger.holdings {
+        if matches!(
+            holding.kind,
+            HolderKind::OptionPool | HolderKind::OptionHolder
+        ) {
+            continue;
+        }
+        let index = company
+            .share_classes
+            .iter()
+            .position(|c| c.name == holding.share_class)
+            .ok_or_else(|| format!("There is no {} share class", holding.share_class))?;
+        let class = &company.share_classes[index];
+        let quantity = class.outstanding_shares(holding.shares);
+        let share_price = holding
+            .invested
+            .checked_div(Decimal::from(quantity))
+            .unwrap_or_default();
+        let (security_id, custom_id) = security(&id_prefix(class, index));
+
+        let mut issuance = json!({
+            "object_type": "TX_STOCK_ISSUANCE",
+            "id": format!("tx-{}", security_id),
+            "security_id": security_id,
+            "custom_id": custom_id,
+            "date": first_appearance(company, &holding.holder).unwrap_or(founded).to_string(),
+            "stakeholder_id": stakeholder_id(&holding.holder),
+            "stock_class_id": class_id(&holding.share_class),
+            "share_price": money(share_price),
+            "quantity": quantity.to_string(),
+            "security_law_exemptions": [],
+        });
+        if holding.kind == HolderKind::Employee {
+            issuance["stock_plan_id"] = json!(PLAN_ID);
+        }
+        transactions.push(issuance);
+    }
+
+    // Options: what is left of each approved grant. Exercised shares are
+    // already stock above; promised grants are not yet securities.
+    for grant in &company.esop_pool.grants {
+        if !grant.board_approved || grant.outstanding_shares() == 0 {
+            continue;
+        }
+        let (security_id, custom_id) = security("EO-");
+        let expiration = grant
+            .grant_date
+            .checked_add_months(Months::new(OPTION_TERM_MONTHS))
+            .unwrap_or(grant.grant_date);
+        transactions.push(json!({
+            "object_type": "TX_EQUITY_COMPENSATION_ISSUANCE",
+            "id": format!("tx-{}", security_id),
+            "security_id": security_id,
+            "custom_id": custom_id,
+            "date": grant.grant_date.to_string(),
+            "stakeholder_id": stakeholder_id(&grant.grantee),
+            "stock_plan_id": PLAN_ID,
+            "stock_class_id": common_id,
+            "compensation_type": "OPTION",
+            "quantity": grant.outstanding_shares().to_string(),
+            "exercise_price": money(grant.strike_price),
+            "expiration_date": expiration.to_string(),
+            "termination_exercise_windows": [],
+            "security_law_exemptions": [],
+        }));
+    }
+
+    for safe in &company.safes {
+        let mut mechanism = json!({
+            "type": "SAFE_CONVERSION",
+            "conversion_mfn": safe.terms.mfn,
+            "conversion_timing": "POST_MONEY",
+        });
+        add_conversion_terms(
+            &mut mechanism,
+            safe.terms.discount_rate,
+            safe.terms.valuation_cap,
+        );
+        let (security_id, custom_id) = security("SAFE-");
+        transactions.push(json!({
+            "object_type": "TX_CONVERTIBLE_ISSUANCE",
+            "id": format!("tx-{}", security_id),
+            "security_id": security_id,
+            "custom_id": custom_id,
+            "date": round_date(company, &safe.round_name).unwrap_or(founded).to_string(),
+            "stakeholder_id": stakeholder_id(&safe.holder),
+            "investment_amount": money(safe.investment),
+            "convertible_type": "SAFE",
+            "conversion_triggers": [conversion_trigger(
+                "AUTOMATIC_ON_CONDITION",
+                &mechanism,
+                json!({ "trigger_condition": "Next equity financing" }),
+            )],
+            "security_law_exemptions": [],
+        }));
+    }
+
+    for note in &company.notes {
+        let compounding = note.terms.interest_type == InterestType::Compounding;
+        let mut mechanism = json!({
+            "type": "CONVERTIBLE_NOTE_CONVERSION",
+            "interest_rates": [{
+                "rate": numeric(note.terms.interest_rate / HUNDRED),
+                "accrual_start_date": note.terms.issue_date.to_string(),
+            }],
+            "day_count_convention": "ACTUAL_365",
+            "interest_payout": "DEFERRED",
+            "interest_accrual_period": if compounding { "ANNUAL" } else { "DAILY" },
+            "compounding_type": if compounding { "COMPOUNDING" } else { "SIMPLE" },
+            "conversion_timing": "PRE_MONEY",
+        });
+        add_conversion_terms(
+            &mut mechanism,
+            note.terms.discount_rate,
+            note.terms.valuation_cap,
+        );
+        let qualified = format!(
+            "{}{}",
+            QUALIFIED_FINANCING,
+            numeric(note.terms.qualified_financing)
+        );
+        let (security_id, custom_id) = security("CN-");
+        transactions.push(json!({
+            "object_type": "TX_CONVERTIBLE_ISSUANCE",
+            "id": format!("tx-{}", security_id),
+            "security_id": security_id,
+            "custom_id": custom_id,
+            "date": note.terms.issue_date.to_string(),
+            "stakeholder_id": stakeholder_id(&note.holder),
+            "investment_amount": money(note.principal),
+            "convertible_type": "NOTE",
+            "conversion_triggers": [
+                conversion_trigger(
+                    "AUTOMATIC_ON_CONDITION",
+                    &mechanism,
+                    json!({ "trigger_condition": qualified }),
+                ),
+                conversion_trigger(
+                    "AUTOMATIC_ON_DATE",
+                    &mechanism,
+                    json!({ "trigger_date": note.terms.maturity_date.to_string() }),
+                ),
+            ],
+            "security_law_exemptions": [],
+        }));
+    }
+
+    // The plan reserves the unallocated pool, outstanding options and the
+    // shares already exercised out of it
+    let reserved = company.ledger.shares_of_kind(HolderKind::OptionPool)
+        + company.ledger.shares_of_kind(HolderKind::OptionHolder)
+        + company.ledger.shares_of_kind(HolderKind::Employee);
+    let mut stock_plans = Vec::new();
+    if reserved > 0 {
+        stock_plans.push(json!({
+            "object_type": "STOCK_PLAN",
+            "id": PLAN_ID,
+            "plan_name": PLAN_NAME,
+            "initial_shares_reserved": reserved.to_string(),
+            "stock_class_ids": [common_id],
+        }));
+    }
+
+    let bundle = json!({
+        "ocf_version": OCF_VERSION,
+        "issuer": issuer,
+        "stakeholders": stakeholder_objects,
+        "stock_classes": stock_classes,
+        "stock_plans": stock_plans,
+        "transactions": transactions,
+    });
+    serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())
+}
+
+// Everyone who holds or is owed securities, founders first, with the
+// relationship that brought them in.
+fn stakeholders(company: &Company) -> Vec<(String, &'static str)> {
+    let mut holders: Vec<(String, &'static str)> = Vec::new();
+    let mut add = |name: &str, relationship: &'static str| {
+        if !holders.iter().any(|(holder, _)| holder == name) {
+            holders.push((name.to_string(), relationship));
+        }
+    };
+
+    for founder in &company.founders {
+        add(&founder.name, "FOUNDER");
+    }
+    for holding in &company.ledger.holdings {
+        match holding.kind {
+            HolderKind::Founder => add(&holding.holder, "FOUNDER"),
+            HolderKind::Investor => add(&holding.holder, "INVESTOR"),
+            HolderKind::OptionHolder | HolderKind::Employee => add(&holding.holder, "EMPLOYEE"),
+            HolderKind::OptionPool => {}
+        }
+    }
+    for grant in &company.esop_pool.grants {
+        if grant.board_approved && grant.outstanding_shares() > 0 {
+            add(&grant.grantee, "EMPLOYEE");
+        }
+    }
+    for safe in &company.safes {
+        add(&safe.holder, "INVESTOR");
+    }
+    for note in &company.notes {
+        add(&note.holder, "INVESTOR");
+    }
+    holders
+}
+
+// "CS-" for common, "P1-", "P2-" and so on for preferred in issue order.
+fn id_prefix(class: &ShareClass, index: usize) -> String {
+    if class.is_preferred() {
+        format!("P{}-", index)
+    } else {
+        "CS-".to_string()
+    }
+}
+
+// When a holder first shows up in the event log.
+fn first_appearance(company: &Company, holder: &str) -> Option<NaiveDate> {
+    company
+        .events
+        .iter()
+        .find(|event| match &event.kind {
+            EventKind::Founding => company.founders.iter().any(|f| f.name == holder),
+            EventKind::Issuance {
+                holder: issued_to, ..
+            } => issued_to == holder,
+            EventKind::Transfer { to, .. } => to == holder,
+            EventKind::RoundClose(round) => round.investors.iter().any(|a| a.investor == holder),
+            EventKind::Grant(grant) => grant.grantee == holder,
+            _ => false,
+        })
+        .map(|event| event.date)
+}
+
+fn round_date(company: &Company, round_name: &str) -> Option<NaiveDate> {
+    company
+        .funding_rounds
+        .iter()
+        .find(|r| r.name == round_name)
+        .map(|r| r.close_date)
+}
+
+fn add_conversion_terms(mechanism: &mut Value, discount_rate: Decimal, valuation_cap: Decimal) {
+    if discount_rate > Decimal::ZERO {
+        mechanism["conversion_discount"] = json!(numeric(discount_rate / HUNDRED));
+    }
+    if valuation_cap > Decimal::ZERO {
+        mechanism["conversion_valuation_cap"] = money(valuation_cap);
+    }
+}
+
+fn conversion_trigger(trigger_type: &str, mechanism: &Value, detail: Value) -> Value {
+    let mut trigger = json!({
+        "type": trigger_type,
+        "trigger_id": trigger_type.to_lowercase(),
+        "conversion_right": {
+            "type": "CONVERTIBLE_CONVERSION_RIGHT",
+            "conversion_mechanism": mechanism,
+            "converts_to_future_round": true,
+        },
+    });
+    if let (Some(trigger), Some(detail)) = (trigger.as_object_mut(), detail.as_object()) {
+        trigger.extend(detail.clone());
+    }
+    trigger
+}
+
+// OCF numbers are strings, so no precision is lost on the way.
+fn numeric(value: Decimal) -> String {
+    value.normalize().to_string()
+}
+
+fn money(amount: Decimal) -> Value {
+    json!({ "amount": numeric(amount), "currency": "USD" })
+}
+
+struct Stakeholder {
+    id: String,
+    name: String,
+    founder: bool,
+    employee: bool,
+}
+
+// A stock or option position still outstanding after the transactions that
+// draw it down.
+struct Position {
+    label: String,
+    security_id: String,
+    date: NaiveDate,
+    stakeholder_id: String,
+    class_id: Option<String>,
+    from_plan: bool,
+    quantity: Decimal,
+    price: Decimal, // share price for stock, exercise price for options
+}
+
+struct Convertible {
+    label: String,
+    security_id: String,
+    date: NaiveDate,
+    stakeholder_id: String,
+    amount: Decimal,
+    deal_type: DealType,
+    outstanding: bool,
+}
+
+// Read one or more OCF files into a company. Files may come in any order;
+// the company is put together once every object has been read.
+pub fn import_ocf(files: &[String]) -> Result<Company, String> {
+    let mut objects = Vec::new();
+    for file in files {
+        let value: Value =
+            serde_json::from_str(file).map_err(|e| format!("The file is not valid JSON: {}", e))?;
+        collect_objects(value, &mut objects);
+    }
+
+    let mut issuer = None;
+    let mut stakeholders = Vec::new();
+    let mut classes: Vec<(String, ShareClass)> = Vec::new(); // by OCF id
+    let mut plans: Vec<(String, Decimal)> = Vec::new(); // shares reserved, by OCF id
+    let mut stock = Vec::new();
+    let mut options = Vec::new();
+    let mut convertibles = Vec::new();
+    let mut drawdowns: Vec<(String, Option<Decimal>)> = Vec::new(); // by security id
+    let mut pool_adjustments: Vec<(NaiveDate, String, Decimal)> = Vec::new();
+
+    for object in &objects {
+        let object_type = text(object, "object_type")?
+            // The older name for equity compensation
+            .replace("TX_PLAN_SECURITY_", "TX_EQUITY_COMPENSATION_");
+        let mut read = || -> Result<(), String> {
+            match object_type.as_str() {
+                "ISSUER" => {
+                    issuer = Some((
+                        text(object, "legal_name")?.to_string(),
+                        optional_date(object, "formation_date")?,
+                    ))
+                }
+                "STAKEHOLDER" => stakeholders.push(read_stakeholder(object)?),
+                "STOCK_CLASS" => {
+                    classes.push((text(object, "id")?.to_string(), read_class(object)?))
+                }
+                "STOCK_PLAN" => plans.push((
+                    text(object, "id")?.to_string(),
+                    number(object, "initial_shares_reserved")?,
+                )),
+                "TX_STOCK_PLAN_POOL_ADJUSTMENT" => pool_adjustments.push((
+                    date(object, "date")?,
+                    text(object, "stock_plan_id")?.to_string(),
+                    number(object, "shares_reserved")?,
+                )),
+                "TX_STOCK_ISSUANCE" => {
+                    stock.push(read_position(object, "share_price")?);
+                }
+                "TX_EQUITY_COMPENSATION_ISSUANCE" => {
+                    options.push(read_position(object, "exercise_price")?);
+                }
+                "TX_CONVERTIBLE_ISSUANCE" => convertibles.push(read_convertible(object)?),
+                "TX_STOCK_CANCELLATION"
+                | "TX_STOCK_REPURCHASE"
+                | "TX_STOCK_TRANSFER"
+                | "TX_STOCK_CONVERSION"
+                | "TX_STOCK_RETRACTION"
+                | "TX_EQUITY_COMPENSATION_EXERCISE"
+                | "TX_EQUITY_COMPENSATION_CANCELLATION"
+                | "TX_EQUITY_COMPENSATION_RETRACTION"
+                | "TX_EQUITY_COMPENSATION_TRANSFER"
+                | "TX_EQUITY_COMPENSATION_RELEASE"
+                | "TX_CONVERTIBLE_CONVERSION"
+                | "TX_CONVERTIBLE_CANCELLATION"
+                | "TX_CONVERTIBLE_RETRACTION"
+                | "TX_CONVERTIBLE_TRANSFER" => drawdowns.push((
+                    text(object, "security_id")?.to_string(),
+                    optional_number(object, "quantity")?,
+                )),
+                other if other.starts_with("TX_") && !changes_no_positions(other) => {
+                    return Err(format!("{} transactions are not supported", other))
+                }
+                _ => {}
+            }
+            Ok(())
+        };
+        read().map_err(|e| format!("{}: {}", label(object), e))?;
+    }
+
+    // Transfers, exercises and conversions reissue what they take as new
+    // securities, which are in the files too
+    for (security_id, quantity) in drawdowns {
+        if let Some(position) = stock
+            .iter_mut()
+            .chain(options.iter_mut())
+            .find(|p| p.security_id == security_id)
+        {
+            position.quantity = match quantity {
+                Some(quantity) => (position.quantity - quantity).max(Decimal::ZERO),
+                None => Decimal::ZERO,
+            };
+        } else if let Some(convertible) = convertibles
+            .iter_mut()
+            .find(|c| c.security_id == security_id)
+        {
+            convertible.outstanding = false;
+        } else {
+            return Err(format!(
+                "A transaction refers to unknown security {}",
+                security_id
+            ));
+        }
+    }
+    pool_adjustments.sort_by_key(|(date, _, _)| *date);
+    for (_, plan_id, reserved) in pool_adjustments {
+        match plans.iter_mut().find(|(id, _)| *id == plan_id) {
+            Some(plan) => plan.1 = reserved,
+            None => {
+                return Err(format!(
+                    "A pool adjustment refers to unknown plan {}",
+                    plan_id
+                ))
+            }
+        }
+    }
+
+    rank_preferences(&mut classes);
+    let class_of = |position: &Position| -> Result<&ShareClass, String> {
+        match &position.class_id {
+            Some(id) => classes
+                .iter()
+                .find(|(class_id, _)| class_id == id)
+                .map(|(_, class)| class)
+                .ok_or_else(|| format!("{}: unknown stock class {}", position.label, id)),
+            None => Err(format!("{}: stock_class_id is missing", position.label)),
+        }
+    };
+    let holder_of = |label: &str, id: &str| -> Result<&Stakeholder, String> {
+        stakeholders
+            .iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| format!("{}: unknown stakeholder {}", label, id))
+    };
+    // Ledger counts are as converted to common
+    let ledger_shares = |position: &Position| -> Result<u64, String> {
+        Ok(whole_shares(
+            position.quantity * class_of(position)?.conversion_ratio(),
+        ))
+    };
+
+    let founded = issuer
+        .as_ref()
+        .and_then(|(_, formed)| *formed)
+        .into_iter()
+        .chain(stock.iter().chain(options.iter()).map(|p| p.date))
+        .chain(convertibles.iter().map(|c| c.date))
+        .min()
+        .ok_or_else(|| "The files hold no OCF issuer or transactions".to_string())?;
+
+    // Founders' common stock is their founding split, whenever it was issued
+    let mut founders = Vec::new();
+    let mut founder_shares = Vec::new();
+    for stakeholder in stakeholders.iter().filter(|s| s.founder) {
+        let mut shares = 0;
+        for position in stock.iter().filter(|p| p.stakeholder_id == stakeholder.id) {
+            if !position.from_plan && !class_of(position)?.is_preferred() {
+                shares += ledger_shares(position)?;
+            }
+        }
+        founders.push(stakeholder.name.clone());
+        founder_shares.push(shares);
+    }
+    let founding_share_count: u64 = founder_shares.iter().sum();
+
+    let mut events = vec![CompanyEvent {
+        date: founded,
+        kind: EventKind::Founding,
+    }];
+    for (_, class) in classes.iter().filter(|(_, c)| c.is_preferred()) {
+        events.push(CompanyEvent {
+            date: founded,
+            kind: EventKind::ClassAuthorization(class.clone()),
+        });
+    }
+
+    // The pool holds what the plans reserve less the stock already issued
+    // out of them, and at least every outstanding option
+    let mut plan_stock = 0;
+    for position in stock.iter().filter(|p| p.from_plan) {
+        plan_stock += ledger_shares(position)?;
+    }
+    let option_shares: u64 = options.iter().map(|p| whole_shares(p.quantity)).sum();
+    let reserved: Decimal = plans.iter().map(|(_, reserved)| *reserved).sum();
+    let pool = whole_shares(reserved)
+        .saturating_sub(plan_stock)
+        .max(option_shares);
+    if pool > 0 {
+        events.push(CompanyEvent {
+            date: founded,
+            kind: EventKind::Issuance {
+                holder: ESOP_POOL_HOLDER.to_string(),
+                kind: HolderKind::OptionPool,
+                share_class: COMMON_CLASS.to_string(),
+                shares: pool,
+                invested: Decimal::ZERO,
+            },
+        });
+    }
+
+    for position in &stock {
+        let holder = holder_of(&position.label, &position.stakeholder_id)?;
+        let class = class_of(position)?;
+        if holder.founder && !position.from_plan && !class.is_preferred() {
+            continue;
+        }
+        let kind = if position.from_plan {
+            HolderKind::Employee
+        } else if holder.founder {
+            HolderKind::Founder
+        } else if holder.employee {
+            HolderKind::Employee
+        } else {
+            HolderKind::Investor
+        };
+        let shares = ledger_shares(position)?;
+        if shares == 0 {
+            continue;
+        }
+        events.push(CompanyEvent {
+            date: position.date,
+            kind: EventKind::Issuance {
+                holder: holder.name.clone(),
+                kind,
+                share_class: class.name.clone(),
+                shares,
+                invested: position.price * position.quantity,
+            },
+        });
+    }
+
+    for position in &options {
+        let shares = whole_shares(position.quantity);
+        if shares == 0 {
+            continue;
+        }
+        events.push(CompanyEvent {
+            date: position.date,
+            kind: EventKind::Grant(OptionGrant {
+                grantee: holder_of(&position.label, &position.stakeholder_id)?
+                    .name
+                    .clone(),
+                shares,
+                strike_price: position.price,
+                grant_date: position.date,
+                vesting: None,
+                board_approved: true,
+                exercised_shares: 0,
+                forfeited_shares: 0,
+            }),
+        });
+    }
+
+    // Convertibles signed the same day on the same terms make one round. An
+    // uncapped round is recorded at the last price shares were sold at.
+    let total_shares = pool
+        + stock
+            .iter()
+            .map(|p| ledger_shares(p).unwrap_or(0))
+            .sum::<u64>();
+    let last_price = stock
+        .iter()
+        .filter(|p| p.price > Decimal::ZERO)
+        .max_by_key(|p| p.date)
+        .map(|p| p.price);
+    let mut rounds: Vec<FundingRound> = Vec::new();
+    for convertible in convertibles.iter().filter(|c| c.outstanding) {
+        let investor = holder_of(&convertible.label, &convertible.stakeholder_id)?
+            .name
+            .clone();
+        let allocation = InvestorAllocation {
+            investor,
+            amount: convertible.amount,
+        };
+        match rounds.iter_mut().find(|r| {
+            r.close_date == convertible.date && same_terms(&r.deal_type, &convertible.deal_type)
+        }) {
+            Some(round) => {
+                round.amount += allocation.amount;
+                round.investors.push(allocation);
+            }
+            None => {
+                let name = match convertible.deal_type {
+                    DealType::ConvertibleNote(_) => "Convertible Note",
+                    _ => "SAFE",
+                };
+                let count = rounds.iter().filter(|r| r.name.starts_with(name)).count();
+                rounds.push(convertible_round(
+                    format!("{} {}", name, count + 1),
+                    convertible,
+                    allocation,
+                ));
+            }
+        }
+    }
+    for mut round in rounds {
+        round.valuation = match &round.deal_type {
+            DealType::SAFE(terms) if terms.valuation_cap > round.amount => {
+                terms.valuation_cap - round.amount
+            }
+            DealType::SAFE(terms) if terms.valuation_cap > Decimal::ZERO => terms.valuation_cap,
+            DealType::ConvertibleNote(terms) if terms.valuation_cap > Decimal::ZERO => {
+                terms.valuation_cap
+            }
+            _ => last_price
+                .map(|price| price * Decimal::from(total_shares))
+                .filter(|valuation| *valuation > Decimal::ZERO)
+                .unwrap_or(round.amount),
+        };
+        events.push(CompanyEvent {
+            date: round.close_date,
+            kind: EventKind::RoundClose(round),
+        });
+    }
+    events.sort_by_key(|e| e.date);
+
+    let mut company = Company::default();
+    if let Some((name, _)) = issuer {
+        company.name = name;
+    }
+    company.founders = founders
+        .into_iter()
+        .zip(&founder_shares)
+        .map(|(name, shares)| Founder {
+            name,
+            initial_ownership: percentage_of(*shares, founding_share_count),
+            current_ownership: Decimal::ZERO,
+            shares: 0,
+            vesting: None,
+            departure_date: None,
+        })
+        .collect();
+    company.founding_share_count = founding_share_count;
+    company.events = events;
+    company.recompute()?;
+    Ok(company)
+}
+
+// Every OCF object in a file: a single object, an OCF file's items, or
+// anything nested in a bundle or array.
+fn collect_objects(value: Value, objects: &mut Vec<Value>) {
+    if value.get("object_type").is_some() {
+        objects.push(value);
+        return;
+    }
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                collect_objects(item, objects);
+            }
+        }
+        Value::Object(fields) => {
+            for (_, item) in fields {
+                collect_objects(item, objects);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Transactions that leave every position as it was.
+fn changes_no_positions(object_type: &str) -> bool {
+    object_type.ends_with("_ACCEPTANCE")
+        || object_type.starts_with("TX_VESTING_")
+        || object_type.ends_with("AUTHORIZED_SHARES_ADJUSTMENT")
+        || object_type == "TX_STOCK_PLAN_RETURN_TO_POOL"
+}
+
+fn read_stakeholder(object: &Value) -> Result<Stakeholder, String> {
+    let name = &object["name"];
+    let name = match name["legal_name"].as_str() {
+        Some(legal_name) => legal_name.to_string(),
+        None => [name["first_name"].as_str(), name["last_name"].as_str()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+    if name.trim().is_empty() {
+        return Err("name is missing".to_string());
+    }
+
+    let mut relationships: Vec<&str> = object["current_relationships"]
+        .as_array()
+        .map(|all| all.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    relationships.extend(object["current_relationship"].as_str());
+    let investor = relationships.contains(&"INVESTOR")
+        || object["stakeholder_type"].as_str() == Some("INSTITUTION");
+
+    Ok(Stakeholder {
+        id: text(object, "id")?.to_string(),
+        name,
+        founder: relationships.contains(&"FOUNDER"),
+        employee: !investor && !relationships.is_empty(),
+    })
+}
+
+// Every common class maps onto the one Common class; preferred classes keep
+// their names and terms.
+fn read_class(object: &Value) -> Result<ShareClass, String> {
+    if text(object, "class_type")? != "PREFERRED" {
+        return Ok(ShareClass::common());
+    }
+
+    let issue_price = monetary(object, "price_per_share")?.unwrap_or_default();
+    let mechanism = &object["conversion_rights"][0]["conversion_mechanism"];
+    let ratio = match (
+        optional_number(&mechanism["ratio"], "numerator")?,
+        optional_number(&mechanism["ratio"], "denominator")?,
+    ) {
+        (Some(numerator), Some(denominator)) => numerator.checked_div(denominator),
+        _ => None,
+    };
+    let conversion_price = match (monetary(mechanism, "conversion_price")?, ratio) {
+        (Some(price), _) => price,
+        (None, Some(ratio)) => issue_price.checked_div(ratio).unwrap_or(issue_price),
+        (None, None) => issue_price,
+    };
+
+    // Files from before the comment wrote uncapped participation as a cap of 0
+    let participating = object["comments"]
+        .as_array()
+        .is_some_and(|comments| comments.iter().any(|c| c == PARTICIPATING_COMMENT));
+    let preference = match optional_number(object, "liquidation_preference_multiple")? {
+        Some(multiple) => Some(LiquidationPreference {
+            multiple,
+            participation: match optional_number(object, "participation_cap_multiple")? {
+                None if participating => Participation::Participating,
+                None => Participation::NonParticipating,
+                Some(cap) if cap.is_zero() => Participation::Participating,
+                Some(cap_multiple) => Participation::CappedParticipating { cap_multiple },
+            },
+            seniority: Seniority::Senior,
+        }),
+        None => None,
+    };
+    let seniority_rank = match optional_number(object, "seniority")? {
+        Some(rank) => rank
+            .to_u32()
+            .ok_or_else(|| "seniority is not a whole number".to_string())?,
+        None => 1,
+    };
+
+    Ok(ShareClass {
+        name: text(object, "name")?.to_string(),
+        original_issue_price: issue_price,
+        conversion_price,
+        preference,
+        seniority_rank,
+        anti_dilution: AntiDilution::None,
+        votes_per_share: optional_number(object, "votes_per_share")?.unwrap_or(Decimal::ONE),
+    })
+}
+
+// A preferred class joining the rank of a class issued before it is pari
+// passu with that class; the first class at each rank stays senior.
+fn rank_preferences(classes: &mut [(String, ShareClass)]) {
+    let ranks: Vec<u32> = classes.iter().map(|(_, c)| c.seniority_rank).collect();
+    for (index, (_, class)) in classes.iter_mut().enumerate() {
+        let joins_earlier = ranks[..index].contains(&class.seniority_rank);
+        if let Some(preference) = &mut class.preference {
+            if joins_earlier {
+                preference.seniority = Seniority::PariPassu;
+            }
+        }
+    }
+}
+
+fn read_position(object: &Value, price_field: &str) -> Result<Position, String> {
+    Ok(Position {
+        label: label(object),
+        security_id: text(object, "security_id")?.to_string(),
+        date: date(object, "date")?,
+        stakeholder_id: text(object, "stakeholder_id")?.to_string(),
+        class_id: object["stock_class_id"].as_str().map(String::from),
+        from_plan: object["stock_plan_id"].is_string(),
+        quantity: number(object, "quantity")?,
+        price: monetary(object, price_field)?.unwrap_or_default(),
+    })
+}
+
+fn read_convertible(object: &Value) -> Result<Convertible, String> {
+    let issued = date(object, "date")?;
+    let triggers = object["conversion_triggers"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    let mechanism = triggers
+        .iter()
+        .map(|t| &t["conversion_right"]["conversion_mechanism"])
+        .find(|m| m.is_object())
+        .cloned()
+        .unwrap_or(Value::Null);
+    let discount_rate =
+        optional_number(&mechanism, "conversion_discount")?.unwrap_or_default() * HUNDRED;
+    let valuation_cap = monetary(&mechanism, "conversion_valuation_cap")?.unwrap_or_default();
+
+    let deal_type = match text(object, "convertible_type")? {
+        "SAFE" => DealType::SAFE(SafeTerms {
+            valuation_cap,
+            discount_rate,
+            mfn: mechanism["conversion_mfn"].as_bool().unwrap_or(false),
+        }),
+        "NOTE" => {
+            let maturity_date = match triggers.iter().find(|t| t["trigger_date"].is_string()) {
+                Some(trigger) => date(trigger, "trigger_date")?,
+                None => issued
+                    .checked_add_months(Months::new(DEFAULT_NOTE_TERM_MONTHS))
+                    .unwrap_or(issued),
+            };
+            let compounding = mechanism["compounding_type"].as_str() == Some("COMPOUNDING");
+            // Written into the condition's description on export
+            let qualified_financing = triggers
+                .iter()
+                .filter_map(|t| {
+                    t["trigger_condition"]
+                        .as_str()?
+                        .strip_prefix(QUALIFIED_FINANCING)
+                })
+                .find_map(parse_decimal)
+                .unwrap_or_default();
+            DealType::ConvertibleNote(NoteTerms {
+                interest_rate: optional_number(&mechanism["interest_rates"][0], "rate")?
+                    .unwrap_or_default()
+                    * HUNDRED,
+                interest_type: if compounding {
+                    InterestType::Compounding
+                } else {
+                    InterestType::Simple
+                },
+                issue_date: issued,
+                maturity_date,
+                valuation_cap,
+                discount_rate,
+                qualified_financing,
+                maturity_behavior: MaturityBehavior::ConvertAtCap,
+            })
+        }
+        other => return Err(format!("{} convertibles are not supported", other)),
+    };
+
+    Ok(Convertible {
+        label: label(object),
+        security_id: text(object, "security_id")?.to_string(),
+        date: issued,
+        stakeholder_id: text(object, "stakeholder_id")?.to_string(),
+        amount: monetary(object, "investment_amount")?
+            .ok_or_else(|| "investment_amount is missing".to_string())?,
+        deal_type,
+        outstanding: true,
+    })
+}
+
+fn same_terms(a: &DealType, b: &DealType) -> bool {
+    match (a, b) {
+        (DealType::SAFE(a), DealType::SAFE(b)) => a == b,
+        (DealType::ConvertibleNote(a), DealType::ConvertibleNote(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn convertible_round(
+    name: String,
+    convertible: &Convertible,
+    allocation: InvestorAllocation,
+) -> FundingRound {
+    FundingRound {
+        name,
+        close_date: convertible.date,
+        amount: allocation.amount,
+        valuation: Decimal::ZERO, // set once every check is in
+        equity_sold: Decimal::ZERO,
+        price_per_share: Decimal::ZERO,
+        input: RoundInput::AmountAndPreMoney,
+        deal_type: convertible.deal_type.clone(),
+        investors: vec![allocation],
+        esop_allocation: Decimal::ZERO,
+        esop_target: None,
+        anti_dilution: AntiDilution::None,
+        liquidation_preference: None,
+        pro_rata_rights: false,
+        pro_rata: vec![],
+    }
+}
+
+// "TX_STOCK_ISSUANCE tx-1", to say which object a problem is in.
+fn label(object: &Value) -> String {
+    format!(
+        "{} {}",
+        object["object_type"].as_str().unwrap_or("Object"),
+        object["id"].as_str().unwrap_or("without an id")
+    )
+}
+
+fn text<'a>(object: &'a Value, field: &str) -> Result<&'a str, String> {
+    object[field]
+        .as_str()
+        .ok_or_else(|| format!("{} is missing", field))
+}
+
+fn date(object: &Value, field: &str) -> Result<NaiveDate, String> {
+    text(object, field)?
+        .parse()
+        .map_err(|_| format!("{} is not a date", field))
+}
+
+fn optional_date(object: &Value, field: &str) -> Result<Option<NaiveDate>, String> {
+    match object[field] {
+        Value::Null => Ok(None),
+        _ => date(object, field).map(Some),
+    }
+}
+
+// OCF writes numbers as strings; plain JSON numbers are read too.
+fn optional_number(object: &Value, field: &str) -> Result<Option<Decimal>, String> {
+    let parsed = match &object[field] {
+        Value::Null => return Ok(None),
+        Value::String(text) => parse_decimal(text),
+        Value::Number(number) => parse_decimal(&number.to_string()),
+        _ => None,
+    };
+    parsed
+        .map(Some)
+        .ok_or_else(|| format!("{} is not a number", field))
+}
+
+fn number(object: &Value, field: &str) -> Result<Decimal, String> {
+    optional_number(object, field)?.ok_or_else(|| format!("{} is missing", field))
+}
+
+fn monetary(object: &Value, field: &str) -> Result<Option<Decimal>, String> {
+    let amount = &object[field];
+    if amount.is_null() {
+        return Ok(None);
+    }
+    match amount["currency"].as_str() {
+        None | Some("USD") => {}
+        Some(currency) => {
+            return Err(format!(
+                "{} is in {}; only USD amounts are supported",
+                field, currency
+            ))
+        }
+    }
+    optional_number(amount, "amount")?
+        .map(Some)
+        .ok_or_else(|| format!("{} has no amount", field))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{company, date, round};
+    use crate::money::dec;
+
+    fn preferred(
+        name: &str,
+        close_date: NaiveDate,
+        participation: Participation,
+        seniority: Seniority,
+    ) -> FundingRound {
+        let mut round = round(
+            name,
+            close_date,
+            dec!(2_000_000),
+            dec!(8_000_000),
+            DealType::PreferredStock,
+        );
+        round.liquidation_preference = Some(LiquidationPreference {
+            multiple: Decimal::ONE,
+            participation,
+            seniority,
+        });
+        round
+    }
+
+    fn exported_company() -> Company {
+        let mut company = company();
+        let mut series_a = preferred(
+            "Series A",
+            date(2021, 1, 1),
+            Participation::Participating,
+            Seniority::Senior,
+        );
+        series_a.esop_allocation = dec!(10);
+        company.add_round(series_a).unwrap();
+        company
+            .add_round(preferred(
+                "Series B",
+                date(2022, 1, 1),
+                Participation::CappedParticipating {
+                    cap_multiple: dec!(3),
+                },
+                Seniority::PariPassu,
+            ))
+            .unwrap();
+        company
+            .add_round(round(
+                "Bridge SAFE",
+                date(2023, 1, 1),
+                dec!(500_000),
+                Decimal::ZERO,
+                DealType::SAFE(SafeTerms {
+                    valuation_cap: dec!(30_000_000),
+                    discount_rate: dec!(20),
+                    mfn: false,
+                }),
+            ))
+            .unwrap();
+        company
+            .add_round(round(
+                "Bridge Note",
+                date(2023, 2, 1),
+                dec!(250_000),
+                dec!(25_000_000),
+                DealType::ConvertibleNote(NoteTerms {
+                    interest_rate: dec!(6),
+                    interest_type: InterestType::Simple,
+                    issue_date: date(2023, 2, 1),
+                    maturity_date: date(2025, 2, 1),
+                    valuation_cap: dec!(25_000_000),
+                    discount_rate: dec!(15),
+                    qualified_financing: dec!(2_000_000),
+                    maturity_behavior: MaturityBehavior::ConvertAtCap,
+                }),
+            ))
+            .unwrap();
+        company
+            .grant_options(OptionGrant {
+                grantee: "Engineer".to_string(),
+                shares: 50_000,
+                strike_price: dec!(0.25),
+                grant_date: date(2022, 6, 1),
+                vesting: None,
+                board_approved: true,
+                exercised_shares: 0,
+                forfeited_shares: 0,
+            })
+            .unwrap();
+        company
+    }
+
+    fn class<'a>(company: &'a Company, name: &str) -> &'a ShareClass {
+        company.share_class(name).unwrap()
+    }
+
+    #[test]
+    fn export_reads_back_as_the_same_cap_table() {
+        let company = exported_company();
+        let imported = import_ocf(&[export_ocf(&company).unwrap()]).unwrap();
+
+        assert_eq!(imported.total_shares, company.total_shares);
+        for holding in &company.ledger.holdings {
+            assert_eq!(
+                imported.ledger.shares_of(&holding.holder),
+                company.ledger.shares_of(&holding.holder),
+                "{}",
+                holding.holder
+            );
+        }
+        for name in ["Series A Preferred", "Series B Preferred"] {
+            let (exported, imported) = (class(&company, name), class(&imported, name));
+            assert_eq!(imported.preference, exported.preference, "{}", name);
+            assert_eq!(imported.seniority_rank, exported.seniority_rank, "{}", name);
+        }
+        assert_eq!(imported.safes[0].terms, company.safes[0].terms);
+        assert_eq!(imported.notes[0].terms.qualified_financing, dec!(2_000_000));
+        assert_eq!(imported.esop_pool.grants[0].shares, 50_000);
+    }
+
+    #[test]
+    fn export_writes_authorized_shares_and_participation() {
+        let company = exported_company();
+        let bundle: Value = serde_json::from_str(&export_ocf(&company).unwrap()).unwrap();
+        let classes = bundle["stock_classes"].as_array().unwrap();
+        let named = |name: &str| classes.iter().find(|c| c["name"] == name).unwrap();
+
+        // Common covers everything that can become common, not just the
+        // founders' shares
+        assert_eq!(
+            named(COMMON_CLASS)["initial_shares_authorized"],
+            json!(company.total_shares.to_string())
+        );
+        let series_a = named("Series A Preferred");
+        assert!(series_a.get("participation_cap_multiple").is_none());
+        assert_eq!(series_a["comments"], json!([PARTICIPATING_COMMENT]));
+        assert_eq!(
+            named("Series B Preferred")["participation_cap_multiple"],
+            json!("3")
+        );
+    }
+
+    #[test]
+    fn older_exports_with_a_zero_cap_still_participate() {
+        let mut bundle: Value =
+            serde_json::from_str(&export_ocf(&exported_company()).unwrap()).unwrap();
+        for class in bundle["stock_classes"].as_array_mut().unwrap() {
+            if class["name"] == "Series A Preferred" {
+                let class = class.as_object_mut().unwrap();
+                class.remove("comments");
+                class.insert("participation_cap_multiple".to_string(), json!("0"));
+            }
+        }
+        let imported = import_ocf(&[bundle.to_string()]).unwrap();
+        assert_eq!(
+            class(&imported, "Series A Preferred")
+                .preference
+                .as_ref()
+                .unwrap()
+                .participation,
+            Participation::Participating
+        );
+    }
+
+    // A cap table split into OCF files the way the format's samples are:
+    // one file per object kind, names given in parts, the older name for
+    // option issuances, and an exercise that reissues stock from the plan.
+    const STAKEHOLDERS_FILE: &str = r#"{
+        "file_type": "OCF_STAKEHOLDERS_FILE",
+        "items": [
+            {"object_type": "STAKEHOLDER", "id": "sh-founder",
+             "name": {"legal_name": "Jane Doe", "first_name": "Jane", "last_name": "Doe"},
+             "stakeholder_type": "INDIVIDUAL", "current_relationship": "FOUNDER"},
+            {"object_type": "STAKEHOLDER", "id": "sh-employee",
+             "name": {"first_name": "Sam", "last_name": "Lee"},
+             "stakeholder_type": "INDIVIDUAL", "current_relationships": ["EMPLOYEE"]},
+            {"object_type": "STAKEHOLDER", "id": "sh-fund",
+             "name": {"legal_name": "Seed Fund I, LP"},
+             "stakeholder_type": "INSTITUTION", "current_relationship": "INVESTOR"}
+        ]
+    }"#;
+
+    const STOCK_CLASSES_FILE: &str = r#"{
+        "file_type": "OCF_STOCK_CLASSES_FILE",
+        "items": [
+            {"object_type": "STOCK_CLASS", "id": "sc-common", "name": "Common Stock",
+             "class_type": "COMMON", "default_id_prefix": "CS-",
+             "initial_shares_authorized": "15000000", "votes_per_share": "1",
+             "seniority": "1", "par_value": {"amount": "0.0001", "currency": "USD"}},
+            {"object_type": "STOCK_CLASS", "id": "sc-seed", "name": "Series Seed Preferred",
+             "class_type": "PREFERRED", "default_id_prefix": "SS-",
+             "initial_shares_authorized": "1000000", "votes_per_share": "1",
+             "seniority": "2", "price_per_share": {"amount": "1.00", "currency": "USD"},
+             "liquidation_preference_multiple": "1",
+             "conversion_rights": [{"type": "STOCK_CLASS_CONVERSION_RIGHT",
+                 "conversion_mechanism": {"type": "RATIO_CONVERSION",
+                     "ratio": {"numerator": "1", "denominator": "1"},
+                     "rounding_type": "NORMAL"},
+                 "converts_to_stock_class_id": "sc-common"}]}
+        ]
+    }"#;
+
+    const STOCK_PLANS_FILE: &str = r#"{
+        "file_type": "OCF_STOCK_PLANS_FILE",
+        "items": [
+            {"object_type": "STOCK_PLAN", "id": "plan-2020", "plan_name": "2020 Stock Plan",
+             "initial_shares_reserved": "1000000", "stock_class_ids": ["sc-common"]}
+        ]
+    }"#;
+
+    const TRANSACTIONS_FILE: &str = r#"{
+        "file_type": "OCF_TRANSACTIONS_FILE",
+        "items": [
+            {"object_type": "TX_STOCK_ISSUANCE", "id": "tx-1", "security_id": "CS-1",
+             "custom_id": "CS-1", "date": "2020-01-15", "stakeholder_id": "sh-founder",
+             "stock_class_id": "sc-common",
+             "share_price": {"amount": "0.0001", "currency": "USD"},
+             "quantity": "8000000", "security_law_exemptions": []},
+            {"object_type": "TX_VESTING_START", "id": "tx-2", "security_id": "CS-1",
+             "date": "2020-01-15", "vesting_condition_id": "start"},
+            {"object_type": "TX_PLAN_SECURITY_ISSUANCE", "id": "tx-3", "security_id": "EO-1",
+             "custom_id": "EO-1", "date": "2020-06-01", "stakeholder_id": "sh-employee",
+             "stock_plan_id": "plan-2020", "stock_class_id": "sc-common",
+             "compensation_type": "OPTION", "quantity": "100000",
+             "exercise_price": {"amount": "0.10", "currency": "USD"},
+             "expiration_date": "2030-06-01", "termination_exercise_windows": [],
+             "security_law_exemptions": []},
+            {"object_type": "TX_EQUITY_COMPENSATION_EXERCISE", "id": "tx-4",
+             "security_id": "EO-1", "date": "2021-06-01", "quantity": "20000",
+             "resulting_security_ids": ["CS-2"]},
+            {"object_type": "TX_STOCK_ISSUANCE", "id": "tx-5", "security_id": "CS-2",
+             "custom_id": "CS-2", "date": "2021-06-01", "stakeholder_id": "sh-employee",
+             "stock_plan_id": "plan-2020", "stock_class_id": "sc-common",
+             "share_price": {"amount": "0.10", "currency": "USD"},
+             "quantity": "20000", "security_law_exemptions": []},
+            {"object_type": "TX_STOCK_ISSUANCE", "id": "tx-6", "security_id": "SS-1",
+             "custom_id": "SS-1", "date": "2021-09-01", "stakeholder_id": "sh-fund",
+             "stock_class_id": "sc-seed",
+             "share_price": {"amount": "1.00", "currency": "USD"},
+             "quantity": "1000000", "security_law_exemptions": []},
+            {"object_type": "TX_CONVERTIBLE_ISSUANCE", "id": "tx-7", "security_id": "SAFE-1",
+             "custom_id": "SAFE-1", "date": "2022-03-01", "stakeholder_id": "sh-fund",
+             "investment_amount": {"amount": "250000", "currency": "USD"},
+             "convertible_type": "SAFE",
+             "conversion_triggers": [{"type": "AUTOMATIC_ON_CONDITION", "trigger_id": "next-round",
+                 "trigger_condition": "Next equity financing",
+                 "conversion_right": {"type": "CONVERTIBLE_CONVERSION_RIGHT",
+                     "conversion_mechanism": {"type": "SAFE_CONVERSION",
+                         "conversion_mfn": false, "conversion_timing": "POST_MONEY",
+                         "conversion_valuation_cap": {"amount": "10000000", "currency": "USD"}},
+                     "converts_to_future_round": true}}],
+             "security_law_exemptions": []}
+        ]
+    }"#;
+
+    #[test]
+    fn sample_files_import_in_any_order() {
+        let files = [
+            TRANSACTIONS_FILE,
+            STOCK_PLANS_FILE,
+            STAKEHOLDERS_FILE,
+            STOCK_CLASSES_FILE,
+        ]
+        .map(String::from);
+        let company = import_ocf(&files).unwrap();
+
+        assert_eq!(company.founding_date(), Some(date(2020, 1, 15)));
+        assert_eq!(company.founders.len(), 1);
+        assert_eq!(company.founders[0].name, "Jane Doe");
+        assert_eq!(company.founders[0].shares, 8_000_000);
+
+        // The plan's 1M less the 20,000 exercised, with 80,000 still under option
+        let exercised: u64 = company
+            .ledger
+            .holdings
+            .iter()
+            .filter(|h| h.holder == "Sam Lee" && h.kind == HolderKind::Employee)
+            .map(|h| h.shares)
+            .sum();
+        assert_eq!(exercised, 20_000);
+        assert_eq!(company.ledger.shares_of("Sam Lee"), 100_000);
+        assert_eq!(company.esop_pool.grants[0].grantee, "Sam Lee");
+        assert_eq!(company.esop_pool.grants[0].outstanding_shares(), 80_000);
+        assert_eq!(
+            company.ledger.shares_of_kind(HolderKind::OptionPool)
+                + company.ledger.shares_of_kind(HolderKind::OptionHolder),
+            980_000
+        );
+
+        let seed = class(&company, "Series Seed Preferred");
+        assert_eq!(seed.original_issue_price, dec!(1.00));
+        assert_eq!(
+            seed.preference.as_ref().unwrap().participation,
+            Participation::NonParticipating
+        );
+        assert_eq!(company.ledger.shares_of("Seed Fund I, LP"), 1_000_000);
+
+        assert_eq!(company.safes.len(), 1);
+        assert_eq!(company.safes[0].terms.valuation_cap, dec!(10_000_000));
+        assert_eq!(company.total_shares, 10_000_000);
+    }
+
+    #[test]
+    fn sample_with_a_dangling_reference_is_refused() {
+        let transactions = TRANSACTIONS_FILE.replace("\"sh-fund\"", "\"sh-unknown\"");
+        let files = [
+            transactions,
+            STOCK_PLANS_FILE.to_string(),
+            STAKEHOLDERS_FILE.to_string(),
+            STOCK_CLASSES_FILE.to_string(),
+        ];
+        let error = import_ocf(&files).unwrap_err();
+        assert!(
+            error.ends_with("unknown stakeholder sh-unknown"),
+            "{}",
+            error
+        );
+    }
+}
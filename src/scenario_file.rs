@@ -0,0 +1,300 @@
+use crate::models::{
+    AntiDilution, CapTableRow, Company, DealType, ExitScenario, ExitType, Founder, FundingRound,
+    InvestorAllocation, LiquidationPreference, OwnershipSnapshot, Participation, ProRataElection,
+    RoundInput, Seniority,
+};
+use crate::money::Decimal;
+use crate::utils::{
+    calculate_cap_table_summary, default_exit_valuations, generate_exit_scenarios,
+    resolve_round_terms, simulate_funding_round,
+};
+use crate::validation::validate_funding_round;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+// A scenario written by hand, for running outside the browser: founders, the
+// rounds in order, and the exits to price. Rounds give any two of amount,
+// pre-money, price per share and equity sold; everything else has the same
+// defaults as a new round in the app.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct ScenarioFile {
+    pub name: String,
+    pub founded: NaiveDate,
+    pub founders: Vec<FounderSpec>,
+    #[serde(default)]
+    pub rounds: Vec<RoundSpec>,
+    #[serde(default)]
+    pub exit: Option<ExitSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct FounderSpec {
+    pub name: String,
+    pub ownership: Decimal, // percentage of the founding shares
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct RoundSpec {
+    pub name: String,
+    pub close_date: NaiveDate,
+    pub amount: Option<Decimal>,
+    pub pre_money: Option<Decimal>,
+    pub price_per_share: Option<Decimal>,
+    pub equity_sold: Option<Decimal>, // percentage
+    #[serde(default = "default_deal_type")]
+    pub deal_type: DealType,
+    #[serde(default)]
+    pub investors: Vec<InvestorAllocation>, // one investor for what pro rata leaves if empty
+    #[serde(default)]
+    pub esop_allocation: Decimal,
+    pub esop_target: Option<Decimal>,
+    #[serde(default = "default_anti_dilution")]
+    pub anti_dilution: AntiDilution,
+    pub liquidation_preference: Option<LiquidationPreference>, // 1x non-participating if unset
+    #[serde(default)]
+    pub pro_rata_rights: bool,
+    #[serde(default)]
+    pub pro_rata: Vec<ProRataElection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct ExitSpec {
+    #[serde(default = "default_exit_type")]
+    pub exit_type: ExitType,
+    pub date: Option<NaiveDate>, // the last round's close if unset
+    #[serde(default = "default_exit_valuations")]
+    pub valuations: Vec<Decimal>,
+}
+
+// The cap table after each round, then every exit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct ScenarioReport {
+    pub name: String,
+    pub rounds: Vec<RoundReport>,
+    pub exits: Vec<ExitScenario>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "js", derive(tsify::Tsify))]
+pub struct RoundReport {
+    pub round: FundingRound, // with its terms resolved
+    pub snapshot: OwnershipSnapshot,
+    pub cap_table: Vec<CapTableRow>,
+}
+
+fn default_deal_type() -> DealType {
+    DealType::PreferredStock
+}
+
+fn default_anti_dilution() -> AntiDilution {
+    AntiDilution::None
+}
+
+fn default_exit_type() -> ExitType {
+    ExitType::Acquisition
+}
+
+impl RoundSpec {
+    // The round as the engine takes it, priced against the company as it
+    // stands when the round closes, with existing investors' pro-rata
+    // elections written into the allocations.
+    fn to_round(&self, company: &Company) -> Result<FundingRound, String> {
+        let input = match (
+            self.amount.is_some(),
+            self.pre_money.is_some(),
+            self.price_per_share.is_some(),
+            self.equity_sold.is_some(),
+        ) {
+            (true, true, false, false) => RoundInput::AmountAndPreMoney,
+            (true, false, true, false) => RoundInput::AmountAndPrice,
+            (true, false, false, true) => RoundInput::AmountAndOwnership,
+            (false, true, false, true) => RoundInput::PreMoneyAndOwnership,
+            (false, false, true, true) => RoundInput::PriceAndOwnership,
+            _ => {
+                return Err(format!(
+                    "{}: give two of amount, pre_money, price_per_share and equity_sold; \
+                     pre_money and price_per_share fix each other, so not both",
+                    self.name
+                ))
+            }
+        };
+        let liquidation_preference = match (&self.liquidation_preference, &self.deal_type) {
+            (Some(preference), _) => Some(preference.clone()),
+            (None, DealType::PreferredStock) => Some(LiquidationPreference {
+                multiple: Decimal::ONE,
+                participation: Participation::NonParticipating,
+                seniority: Seniority::Senior,
+            }),
+            (None, _) => None,
+        };
+
+        let mut round = FundingRound {
+            name: self.name.clone(),
+            close_date: self.close_date,
+            amount: self.amount.unwrap_or_default(),
+            valuation: self.pre_money.unwrap_or_default(),
+            equity_sold: self.equity_sold.unwrap_or_default(),
+            price_per_share: self.price_per_share.unwrap_or_default(),
+            input,
+            deal_type: self.deal_type.clone(),
+            investors: self.investors.clone(),
+            esop_allocation: self.esop_allocation,
+            esop_target: self.esop_target,
+            anti_dilution: self.anti_dilution.clone(),
+            liquidation_preference,
+            pro_rata_rights: self.pro_rata_rights,
+            pro_rata: self.pro_rata.clone(),
+        };
+        resolve_round_terms(&mut round, company.ledger.fully_diluted_shares())
+            .map_err(|e| format!("{}: {}", self.name, e))?;
+        company.apply_pro_rata(&mut round);
+        let unallocated = round.amount - round.allocated_amount();
+        if self.investors.is_empty() && unallocated > Decimal::ZERO {
+            round.investors.push(InvestorAllocation {
+                investor: format!("{} Investors", self.name),
+                amount: unallocated,
+            });
+        }
+
+        validate_funding_round(&round).map_err(|errors| {
+            let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            format!("{}: {}", self.name, messages.join("; "))
+        })?;
+        Ok(round)
+    }
+}
+
+impl ScenarioFile {
+    pub fn company(&self) -> Result<Company, String> {
+        let mut company = Company {
+            name: self.name.clone(),
+            founders: self
+                .founders
+                .iter()
+                .map(|f| Founder {
+                    name: f.name.clone(),
+                    initial_ownership: f.ownership,
+                    current_ownership: Decimal::ZERO,
+                    shares: 0,
+                    vesting: None,
+                    departure_date: None,
+                })
+                .collect(),
+            ..Company::default()
+        };
+        company.set_founding_date(self.founded)?;
+        Ok(company)
+    }
+}
+
+// Close every round in date order, recording the cap table after each, then run
+// the exits against the final company.
+pub fn run_scenario(scenario: &ScenarioFile) -> Result<ScenarioReport, String> {
+    let mut company = scenario.company()?;
+    let mut rounds = Vec::new();
+
+    // Rounds close in date order whatever order the file lists them in, so the
+    // company after each one is the company as of its close
+    let mut specs: Vec<&RoundSpec> = scenario.rounds.iter().collect();
+    specs.sort_by_key(|spec| spec.close_date);
+    for spec in specs {
+        let round = spec.to_round(&company)?;
+        let snapshot = simulate_funding_round(&mut company, &round)
+            .map_err(|e| format!("{}: {}", spec.name, e))?;
+        rounds.push(RoundReport {
+            round,
+            snapshot,
+            cap_table: calculate_cap_table_summary(&company),
+        });
+    }
+
+    let last_date = scenario
+        .rounds
+        .iter()
+        .map(|r| r.close_date)
+        .max()
+        .unwrap_or(scenario.founded);
+    let exits = match &scenario.exit {
+        Some(exit) => generate_exit_scenarios(
+            &company,
+            exit.valuations.clone(),
+            exit.exit_type.clone(),
+            exit.date.unwrap_or(last_date),
+        ),
+        None => generate_exit_scenarios(
+            &company,
+            default_exit_valuations(),
+            default_exit_type(),
+            last_date,
+        ),
+    };
+
+    Ok(ScenarioReport {
+        name: scenario.name.clone(),
+        rounds,
+        exits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::dec;
+
+    fn scenario(rounds: &str) -> ScenarioFile {
+        serde_json::from_str(&format!(
+            r#"{{"name": "Acme", "founded": "2020-01-01",
+                "founders": [{{"name": "Ada", "ownership": "60"}}, {{"name": "Bob", "ownership": "40"}}],
+                "rounds": [{}]}}"#,
+            rounds
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn rounds_run_in_date_order() {
+        let report = run_scenario(&scenario(
+            r#"{"name": "Series A", "close_date": "2022-01-01", "amount": "5000000", "pre_money": "20000000"},
+               {"name": "Seed", "close_date": "2021-01-01", "amount": "1000000", "pre_money": "4000000"}"#,
+        ))
+        .unwrap();
+
+        let seed = &report.rounds[0];
+        assert_eq!(seed.round.name, "Seed");
+        assert_eq!(seed.snapshot.round_name, "Seed");
+        assert_eq!(seed.snapshot.price_per_share, dec!(0.4));
+        assert!(seed
+            .cap_table
+            .iter()
+            .all(|row| row.holder != "Series A Investors"));
+        assert_eq!(report.rounds[1].round.name, "Series A");
+        assert_eq!(report.rounds[1].snapshot.price_per_share, dec!(1.6));
+    }
+
+    #[test]
+    fn pro_rata_elections_take_their_entitlement() {
+        let report = run_scenario(&scenario(
+            r#"{"name": "Seed", "close_date": "2021-01-01", "amount": "1000000", "pre_money": "4000000",
+                "pro_rata_rights": true},
+               {"name": "Series A", "close_date": "2022-01-01", "amount": "5000000", "pre_money": "20000000",
+                "pro_rata": [{"investor": "Seed Investors", "participation": "Full"}]}"#,
+        ))
+        .unwrap();
+
+        let series_a = &report.rounds[1].round;
+        let allocation = |name: &str| {
+            series_a
+                .investors
+                .iter()
+                .find(|a| a.investor == name)
+                .map(|a| a.amount)
+        };
+        assert_eq!(allocation("Seed Investors"), Some(dec!(1000000)));
+        assert_eq!(allocation("Series A Investors"), Some(dec!(4000000)));
+    }
+}
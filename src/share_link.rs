@@ -0,0 +1,211 @@
+use crate::document::{import_document, ExitSettings, ModelDocument, SCHEMA_VERSION};
+use crate::models::{Company, CompanyEvent, Founder};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec_with_limit;
+use serde::{Deserialize, Serialize};
+
+// A scenario packed into a URL fragment: the inputs the model is replayed
+// from, deflated and written in URL-safe base64 behind a tag naming the
+// encoding. Everything derived from the event log is rebuilt when the link is
+// opened, which keeps links a fraction of the size of the whole document. The
+// inputs carry their schema version, so a schema change can migrate them as it
+// does documents; the tag only changes if the packing itself does.
+const LINK_ENCODING: &str = "v2";
+const DOCUMENT_ENCODING: &str = "v1"; // links made before, holding the whole document
+const COMPRESSION_LEVEL: u8 = 9;
+const MAX_DOCUMENT_BYTES: usize = 16 * 1024 * 1024; // guards against a link that inflates without end
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SharedModel {
+    version: u64,
+    name: String,
+    founding_share_count: u64,
+    founders: Vec<Founder>,
+    events: Vec<CompanyEvent>,
+    exit: ExitSettings,
+}
+
+pub fn encode_share_link(company: &Company, exit: &ExitSettings) -> Result<String, String> {
+    let shared = SharedModel {
+        version: SCHEMA_VERSION,
+        name: company.name.clone(),
+        founding_share_count: company.founding_share_count,
+        founders: company.founders.clone(),
+        events: company.events.clone(),
+        exit: exit.clone(),
+    };
+    let json = serde_json::to_string(&shared).map_err(|e| e.to_string())?;
+    Ok(pack(LINK_ENCODING, &json))
+}
+
+fn pack(encoding: &str, json: &str) -> String {
+    let packed = compress_to_vec(json.as_bytes(), COMPRESSION_LEVEL);
+    format!("{}.{}", encoding, URL_SAFE_NO_PAD.encode(packed))
+}
+
+// Unpack a fragment, with or without its leading '#'.
+pub fn decode_share_link(fragment: &str) -> Result<ModelDocument, String> {
+    let fragment = fragment.trim().trim_start_matches('#');
+    let Some((encoding, payload)) = fragment.split_once('.') else {
+        return Err("The link does not contain a scenario".to_string());
+    };
+    let cannot_open = |e: String| format!("The link's scenario cannot be opened: {}", e);
+    match encoding {
+        LINK_ENCODING => open_shared_model(&unpack(payload)?).map_err(cannot_open),
+        DOCUMENT_ENCODING => import_document(&unpack(payload)?).map_err(cannot_open),
+        _ => Err(match encoding.strip_prefix('v').map(str::parse::<u32>) {
+            Some(Ok(_)) => format!(
+                "The link was made by a different version of the app (link format {})",
+                encoding
+            ),
+            _ => "The link does not contain a scenario".to_string(),
+        }),
+    }
+}
+
+fn unpack(payload: &str) -> Result<String, String> {
+    let corrupt = "The link is corrupt; it may have been cut short when it was copied";
+    let packed = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|_| corrupt.to_string())?;
+    decompress_to_vec_with_limit(&packed, MAX_DOCUMENT_BYTES)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| corrupt.to_string())
+}
+
+// Rebuild the company from its inputs and replay its history.
+fn open_shared_model(json: &str) -> Result<ModelDocument, String> {
+    let shared: SharedModel =
+        serde_json::from_str(json).map_err(|e| format!("it does not describe a model: {}", e))?;
+    if shared.version > SCHEMA_VERSION {
+        return Err(format!(
+            "it uses schema version {} but this app reads up to version {}",
+            shared.version, SCHEMA_VERSION
+        ));
+    }
+
+    let mut company = Company {
+        name: shared.name,
+        founding_share_count: shared.founding_share_count,
+        founders: shared.founders,
+        events: shared.events,
+        ..Company::default()
+    };
+    company
+        .recompute()
+        .map_err(|e| format!("its history does not replay: {}", e))?;
+    Ok(ModelDocument::new(&company, &shared.exit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::export_document;
+    use crate::fixtures::{company, date, round};
+    use crate::models::{DealType, ExitType};
+    use crate::money::dec;
+
+    fn seeded() -> Company {
+        let mut company = company();
+        company.name = "Acme".to_string();
+        company
+            .add_round(round(
+                "Seed",
+                date(2021, 6, 1),
+                dec!(1_000_000),
+                dec!(4_000_000),
+                DealType::PreferredStock,
+            ))
+            .unwrap();
+        company
+    }
+
+    fn exit() -> ExitSettings {
+        ExitSettings {
+            exit_type: ExitType::Merger,
+            exit_date: date(2028, 3, 1),
+        }
+    }
+
+    #[test]
+    fn link_round_trips_through_a_replay() {
+        let link = encode_share_link(&seeded(), &exit()).unwrap();
+        assert!(link.starts_with("v2."), "{}", link);
+
+        let document = decode_share_link(&format!("#{}", link)).unwrap();
+        assert_eq!(document.company.name, "Acme");
+        assert_eq!(document.company.total_shares, 12_500_000);
+        assert_eq!(document.company.funding_rounds.len(), 1);
+        assert_eq!(document.company.round_history.len(), 1);
+        assert_eq!(document.exit.exit_date, date(2028, 3, 1));
+    }
+
+    #[test]
+    fn link_carries_only_the_inputs() {
+        let link = encode_share_link(&seeded(), &exit()).unwrap();
+        let whole = pack(
+            DOCUMENT_ENCODING,
+            &export_document(&seeded(), &exit()).unwrap(),
+        );
+        assert!(
+            link.len() * 2 < whole.len(),
+            "{} against {}",
+            link.len(),
+            whole.len()
+        );
+    }
+
+    #[test]
+    fn links_holding_the_whole_document_still_open() {
+        let link = pack(
+            DOCUMENT_ENCODING,
+            &export_document(&seeded(), &exit()).unwrap(),
+        );
+        let document = decode_share_link(&link).unwrap();
+        assert_eq!(document.company.total_shares, 12_500_000);
+    }
+
+    #[test]
+    fn broken_links_say_why() {
+        let link = encode_share_link(&seeded(), &exit()).unwrap();
+        let corrupt = "The link is corrupt; it may have been cut short when it was copied";
+        assert_eq!(
+            decode_share_link(&link[..link.len() / 2]).unwrap_err(),
+            corrupt
+        );
+        assert_eq!(decode_share_link("v2.@@@").unwrap_err(), corrupt);
+        assert_eq!(
+            decode_share_link("#about").unwrap_err(),
+            "The link does not contain a scenario"
+        );
+        assert_eq!(
+            decode_share_link(&link.replacen("v2", "v7", 1)).unwrap_err(),
+            "The link was made by a different version of the app (link format v7)"
+        );
+        assert_eq!(
+            decode_share_link(&link.replacen("v2", "draft", 1)).unwrap_err(),
+            "The link does not contain a scenario"
+        );
+
+        let newer = pack(
+            LINK_ENCODING,
+            &format!(
+                r#"{{"version": {}, "name": "Acme", "founding_share_count": 1,
+                "founders": [], "events": [],
+                "exit": {{"exit_type": "IPO", "exit_date": "2028-03-01"}}}}"#,
+                SCHEMA_VERSION + 1
+            ),
+        );
+        assert_eq!(
+            decode_share_link(&newer).unwrap_err(),
+            format!(
+                "The link's scenario cannot be opened: it uses schema version {} but this app reads up to version {}",
+                SCHEMA_VERSION + 1,
+                SCHEMA_VERSION
+            )
+        );
+    }
+}
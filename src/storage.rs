@@ -0,0 +1,116 @@
+use crate::document::{
+    export_document, export_library, import_document, import_library, ExitSettings, ModelDocument,
+};
+use crate::models::{Company, ScenarioLibrary};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, BlobPropertyBag, File, HtmlAnchorElement, Storage, Url};
+
+// Browser local storage, files and the address bar. The working model is
+// written on every change, as a versioned document, so a reload picks up where
+// it left off; saved scenarios live under their own key.
+const CURRENT_KEY: &str = "equity-scenario-builder:current";
+const LIBRARY_KEY: &str = "equity-scenario-builder:scenarios";
+
+fn local_storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+fn load(key: &str) -> Option<String> {
+    local_storage()?.get_item(key).ok().flatten()
+}
+
+fn store(key: &str, json: &str) -> Result<(), String> {
+    let storage = local_storage().ok_or_else(|| "Local storage is not available".to_string())?;
+    storage
+        .set_item(key, json)
+        .map_err(|_| "Local storage is full".to_string())
+}
+
+// Anything missing or unreadable loads as nothing.
+pub fn load_current_model() -> Option<ModelDocument> {
+    import_document(&load(CURRENT_KEY)?).ok()
+}
+
+pub fn save_current_model(company: &Company, exit: &ExitSettings) -> Result<(), String> {
+    store(CURRENT_KEY, &export_document(company, exit)?)
+}
+
+// No library yet is an empty one; a library that will not read is an error.
+pub fn load_scenarios() -> Result<ScenarioLibrary, String> {
+    match load(LIBRARY_KEY) {
+        Some(json) => import_library(&json),
+        None => Ok(ScenarioLibrary::default()),
+    }
+}
+
+pub fn save_scenarios(library: &ScenarioLibrary) -> Result<(), String> {
+    store(LIBRARY_KEY, &export_library(library)?)
+}
+
+// Hand `contents` to the browser as a file to download.
+pub fn download_file(filename: &str, mime_type: &str, contents: &str) -> Result<(), String> {
+    let failed = |_: JsValue| format!("Could not download {}", filename);
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or_else(|| "There is no page to download from".to_string())?;
+
+    let options = BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options).map_err(failed)?;
+    let url = Url::create_object_url_with_blob(&blob).map_err(failed)?;
+
+    let anchor: HtmlAnchorElement = document
+        .create_element("a")
+        .map_err(failed)?
+        .unchecked_into();
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+    Url::revoke_object_url(&url).map_err(failed)
+}
+
+// The text of a file the user picked.
+pub async fn read_file(file: &File) -> Result<String, String> {
+    JsFuture::from(file.text())
+        .await
+        .ok()
+        .and_then(|text| text.as_string())
+        .ok_or_else(|| format!("Could not read {}", file.name()))
+}
+
+// Keep a model in the scenario library without replacing any scenario there,
+// returning the name it was saved under.
+pub fn save_as_scenario(name: &str, company: &Company) -> Result<String, String> {
+    let mut library = load_scenarios()?;
+    let name = library.save_new(name, company, chrono::Local::now().naive_local())?;
+    save_scenarios(&library)?;
+    Ok(name)
+}
+
+// The part of the address after '#', where shared links carry their scenario.
+pub fn location_fragment() -> Option<String> {
+    let hash = web_sys::window()?.location().hash().ok()?;
+    let fragment = hash.trim_start_matches('#');
+    (!fragment.is_empty()).then(|| fragment.to_string())
+}
+
+// The page's address without any fragment.
+pub fn page_address() -> Option<String> {
+    let href = web_sys::window()?.location().href().ok()?;
+    href.split('#').next().map(String::from)
+}
+
+// Drop the fragment from the address bar without adding a history entry.
+pub fn clear_location_fragment() -> Result<(), String> {
+    let failed = |_: JsValue| "Could not update the address bar".to_string();
+    let address = page_address().ok_or_else(|| "There is no page address".to_string())?;
+    let history = web_sys::window()
+        .ok_or_else(|| "There is no page address".to_string())?
+        .history()
+        .map_err(failed)?;
+    history
+        .replace_state_with_url(&JsValue::NULL, "", Some(&address))
+        .map_err(failed)
+}
@@ -1,181 +1,509 @@
-use crate::models::{Company, ExitScenario, FundingRound, OwnershipSnapshot};
-
-pub fn format_currency(amount: f64) -> String {
-    if amount >= 1_000_000_000.0 {
-        format!("${:.1}B", amount / 1_000_000_000.0)
-    } else if amount >= 1_000_000.0 {
-        format!("${:.1}M", amount / 1_000_000.0)
-    } else if amount >= 1_000.0 {
-        format!("${:.1}K", amount / 1_000.0)
+use crate::models::{
+    CapTableRow, Company, CompanyEvent, DealType, EventKind, ExitScenario, ExitType, FundingRound,
+    HolderKind, OwnershipSnapshot, RoundInput,
+};
+use crate::money::{dec, percentage_of, whole_shares, Decimal, HUNDRED};
+use crate::validation::{check_round_terms, ValidationError};
+use chrono::{Datelike, NaiveDate};
+
+pub fn format_currency(amount: Decimal) -> String {
+    if amount >= dec!(1_000_000_000) {
+        format!("${:.1}B", amount / dec!(1_000_000_000))
+    } else if amount >= dec!(1_000_000) {
+        format!("${:.1}M", amount / dec!(1_000_000))
+    } else if amount >= dec!(1_000) {
+        format!("${:.1}K", amount / dec!(1_000))
     } else {
         format!("${:.0}", amount)
     }
 }
 
-pub fn format_percentage(value: f64) -> String {
+pub fn format_percentage(value: Decimal) -> String {
     format!("{:.1}%", value)
 }
 
 pub fn format_shares(shares: u64) -> String {
     if shares >= 1_000_000 {
-        format!("{:.1}M", shares as f64 / 1_000_000.0)
+        format!("{:.1}M", Decimal::from(shares) / dec!(1_000_000))
     } else if shares >= 1_000 {
-        format!("{:.1}K", shares as f64 / 1_000.0)
+        format!("{:.1}K", Decimal::from(shares) / dec!(1_000))
     } else {
         shares.to_string()
     }
 }
 
-pub fn calculate_dilution_impact(initial_ownership: f64, current_ownership: f64) -> f64 {
+pub fn calculate_dilution_impact(
+    initial_ownership: Decimal,
+    current_ownership: Decimal,
+) -> Decimal {
     initial_ownership - current_ownership
 }
 
-pub fn calculate_ownership_value(ownership_percentage: f64, company_valuation: f64) -> f64 {
-    (ownership_percentage / 100.0) * company_valuation
+pub fn calculate_ownership_value(
+    ownership_percentage: Decimal,
+    company_valuation: Decimal,
+) -> Decimal {
+    (ownership_percentage / HUNDRED) * company_valuation
 }
 
-pub fn calculate_post_money_valuation(pre_money: f64, investment_amount: f64) -> f64 {
+pub fn calculate_post_money_valuation(pre_money: Decimal, investment_amount: Decimal) -> Decimal {
     pre_money + investment_amount
 }
 
-pub fn calculate_equity_percentage(investment_amount: f64, post_money_valuation: f64) -> f64 {
-    (investment_amount / post_money_valuation) * 100.0
+pub fn calculate_equity_percentage(
+    investment_amount: Decimal,
+    post_money_valuation: Decimal,
+) -> Decimal {
+    (investment_amount / post_money_valuation) * HUNDRED
 }
 
 pub fn calculate_new_shares_issued(
-    investment_amount: f64,
-    pre_money_valuation: f64,
+    investment_amount: Decimal,
+    pre_money_valuation: Decimal,
     existing_shares: u64,
 ) -> u64 {
     let post_money = pre_money_valuation + investment_amount;
     let equity_percentage = investment_amount / post_money;
-    let new_shares =
-        (existing_shares as f64 * equity_percentage / (1.0 - equity_percentage)) as u64;
-    new_shares
+    whole_shares(
+        Decimal::from(existing_shares) * equity_percentage / (Decimal::ONE - equity_percentage),
+    )
+}
+
+// CP2 = CP1 * (A + B) / (A + C), where A is the share base before the round,
+// B the shares the new money would buy at CP1 and C the shares actually issued.
+pub fn calculate_weighted_average_price(
+    conversion_price: Decimal,
+    base_shares: u64,
+    amount_raised: Decimal,
+    new_shares: u64,
+) -> Decimal {
+    let base = Decimal::from(base_shares);
+    let shares_at_old_price = amount_raised / conversion_price;
+    conversion_price * (base + shares_at_old_price) / (base + Decimal::from(new_shares))
+}
+
+// New pool shares, issued in the pre-money, that bring the pool to
+// `target_percentage` of the post-money fully-diluted shares. With the pool in
+// the pre-money, post-money shares are (F + P) * post / pre, so solving
+// (E + P) = t * (F + P) * post / pre gives P = (kF - E) / (1 - k).
+pub fn calculate_pool_top_up(
+    target_percentage: Decimal,
+    pre_money_valuation: Decimal,
+    investment_amount: Decimal,
+    existing_pool_shares: Decimal,
+    pre_money_shares: Decimal,
+) -> Decimal {
+    let post_money = pre_money_valuation + investment_amount;
+    let k = target_percentage / HUNDRED * post_money / pre_money_valuation;
+    if k >= Decimal::ONE {
+        return Decimal::ZERO;
+    }
+    ((k * pre_money_shares - existing_pool_shares) / (Decimal::ONE - k)).max(Decimal::ZERO)
+}
+
+// Whole calendar months from `start` to `end`, counting a month only once
+// its day-of-month has been reached.
+pub fn months_between(start: NaiveDate, end: NaiveDate) -> u32 {
+    if end <= start {
+        return 0;
+    }
+
+    let mut months = (end.year() - start.year()) * 12 + end.month() as i32 - start.month() as i32;
+    if end.day() < start.day() {
+        months -= 1;
+    }
+    months.max(0) as u32
 }
 
 pub fn calculate_founder_dilution(
     founder_shares: u64,
     total_shares_before: u64,
     total_shares_after: u64,
-) -> f64 {
-    let ownership_before = (founder_shares as f64 / total_shares_before as f64) * 100.0;
-    let ownership_after = (founder_shares as f64 / total_shares_after as f64) * 100.0;
+) -> Decimal {
+    let ownership_before = percentage_of(founder_shares, total_shares_before);
+    let ownership_after = percentage_of(founder_shares, total_shares_after);
     ownership_before - ownership_after
 }
 
-pub fn simulate_funding_round(company: &mut Company, round: &FundingRound) -> OwnershipSnapshot {
-    // Calculate new total shares
-    let pre_round_shares = company.total_shares;
-    let post_round_valuation = round.valuation + round.amount;
-    let new_shares = (pre_round_shares as f64 * post_round_valuation / round.valuation) as u64;
-
-    // Update company total shares
-    company.total_shares = new_shares;
-
-    // Calculate dilution for each founder
-    for founder in &mut company.founders {
-        let old_ownership = founder.current_ownership;
-        let new_ownership = (founder.shares as f64 / new_shares as f64) * 100.0;
-        founder.current_ownership = new_ownership;
-
-        // Note: shares remain the same, only ownership percentage changes
+// Fill in whichever of amount, pre-money, equity sold and price per share
+// the round's input leaves open. Prices are quoted against the fully-diluted
+// shares before the round; a priced round settles them again once its SAFEs,
+// notes and pool top-up are in.
+pub fn resolve_round_terms(
+    round: &mut FundingRound,
+    pre_round_shares: u64,
+) -> Result<(), ValidationError> {
+    if pre_round_shares == 0 {
+        return Err(ValidationError::NoSharesToPrice);
+    }
+    let shares = Decimal::from(pre_round_shares);
+    let ownership = round.equity_sold / HUNDRED;
+    let needs_ownership = matches!(
+        round.input,
+        RoundInput::AmountAndOwnership
+            | RoundInput::PreMoneyAndOwnership
+            | RoundInput::PriceAndOwnership
+    );
+    if needs_ownership && (ownership <= Decimal::ZERO || ownership >= Decimal::ONE) {
+        return Err(ValidationError::EquitySoldOutOfRange);
+    }
+    // A post-money SAFE is priced by its cap, so a pre-money left blank is the
+    // cap less the money the SAFE brings in
+    if let DealType::SAFE(terms) = &round.deal_type {
+        if matches!(round.input, RoundInput::AmountAndPreMoney)
+            && round.valuation <= Decimal::ZERO
+            && terms.valuation_cap > Decimal::ZERO
+        {
+            round.valuation = terms.valuation_cap - round.amount;
+        }
+    }
+    // Everything downstream divides by the pre-money or the price, so the
+    // term the round is priced from has to be positive
+    match round.input {
+        RoundInput::AmountAndPreMoney | RoundInput::PreMoneyAndOwnership
+            if round.valuation <= Decimal::ZERO =>
+        {
+            return Err(ValidationError::NonPositiveValuation)
+        }
+        RoundInput::AmountAndPrice | RoundInput::PriceAndOwnership
+            if round.price_per_share <= Decimal::ZERO =>
+        {
+            return Err(ValidationError::NonPositivePrice)
+        }
+        RoundInput::AmountAndOwnership if round.amount <= Decimal::ZERO => {
+            return Err(ValidationError::NonPositiveAmount)
+        }
+        _ => {}
     }
 
-    // Update ESOP pool
-    if round.esop_allocation > 0.0 {
-        company.esop_pool.total_allocation += round.esop_allocation;
+    match round.input {
+        RoundInput::AmountAndPreMoney => {}
+        RoundInput::AmountAndPrice => round.valuation = round.price_per_share * shares,
+        RoundInput::AmountAndOwnership => {
+            round.valuation = round.amount * (Decimal::ONE - ownership) / ownership
+        }
+        RoundInput::PreMoneyAndOwnership => {
+            round.amount = round.valuation * ownership / (Decimal::ONE - ownership)
+        }
+        RoundInput::PriceAndOwnership => {
+            round.valuation = round.price_per_share * shares;
+            round.amount = round.valuation * ownership / (Decimal::ONE - ownership);
+        }
     }
 
-    // Create ownership snapshot
-    let founder_ownership: Vec<(String, f64)> = company
-        .founders
-        .iter()
-        .map(|f| (f.name.clone(), f.current_ownership))
-        .collect();
+    let post_money = calculate_post_money_valuation(round.valuation, round.amount);
+    if post_money <= Decimal::ZERO {
+        return Err(ValidationError::NonPositivePostMoney);
+    }
+    round.equity_sold = calculate_equity_percentage(round.amount, post_money);
+    round.price_per_share = round.valuation / shares;
+    Ok(())
+}
 
-    let investor_ownership: Vec<(String, f64)> = round
-        .investors
+pub fn simulate_funding_round(
+    company: &mut Company,
+    round: &FundingRound,
+) -> Result<OwnershipSnapshot, String> {
+    // A backdated round is priced against the shares on its close date
+    let pre_round_shares = company.fully_diluted_shares_on(round.close_date)?;
+    check_round_terms(round, pre_round_shares).map_err(|e| e.to_string())?;
+    // The round goes in after every event dated on or before it, so a
+    // backdated round's snapshot is not the last one
+    let position = company
+        .events
         .iter()
-        .map(|name| {
-            (
-                name.clone(),
-                round.equity_sold / round.investors.len() as f64,
-            )
-        })
-        .collect();
+        .filter(|e| e.date <= round.close_date && matches!(e.kind, EventKind::RoundClose(_)))
+        .count();
+    company.record_event(CompanyEvent {
+        date: round.close_date,
+        kind: EventKind::RoundClose(round.clone()),
+    })?;
+    Ok(company
+        .round_history
+        .get(position)
+        .cloned()
+        .expect("closing a round records its snapshot"))
+}
 
-    OwnershipSnapshot {
-        round_name: round.name.clone(),
-        founder_ownership,
-        investor_ownership,
-        esop_ownership: company.esop_pool.total_allocation,
-        total_valuation: post_round_valuation,
-    }
+// The exit values every scenario is priced at, from $10M to $1B.
+pub fn default_exit_valuations() -> Vec<Decimal> {
+    vec![
+        dec!(10_000_000),
+        dec!(25_000_000),
+        dec!(50_000_000),
+        dec!(100_000_000),
+        dec!(250_000_000),
+        dec!(500_000_000),
+        dec!(1_000_000_000),
+    ]
 }
 
-pub fn generate_exit_scenarios(company: &Company, exit_valuations: Vec<f64>) -> Vec<ExitScenario> {
+pub fn generate_exit_scenarios(
+    company: &Company,
+    exit_valuations: Vec<Decimal>,
+    exit_type: ExitType,
+    exit_date: NaiveDate,
+) -> Vec<ExitScenario> {
     exit_valuations
         .into_iter()
-        .map(|valuation| company.calculate_exit_scenario(valuation))
+        .map(|valuation| company.calculate_exit_scenario(valuation, exit_type.clone(), exit_date))
         .collect()
 }
 
-pub fn calculate_cap_table_summary(company: &Company) -> Vec<(String, f64, u64)> {
+pub fn calculate_cap_table_summary(company: &Company) -> Vec<CapTableRow> {
+    let ledger = &company.ledger;
+    let is_option =
+        |kind: &HolderKind| matches!(kind, HolderKind::OptionHolder | HolderKind::OptionPool);
+    let issued: u64 = ledger
+        .holdings
+        .iter()
+        .filter(|h| !is_option(&h.kind))
+        .map(|h| h.shares)
+        .sum();
+    let outstanding_percentage = |shares: u64| percentage_of(shares, issued);
     let mut cap_table = Vec::new();
 
-    // Add founders
-    for founder in &company.founders {
-        cap_table.push((
-            founder.name.clone(),
-            founder.current_ownership,
-            founder.shares,
-        ));
-    }
-
-    // Add ESOP pool
-    if company.esop_pool.total_allocation > 0.0 {
-        let esop_shares =
-            (company.total_shares as f64 * company.esop_pool.total_allocation / 100.0) as u64;
-        cap_table.push((
-            "ESOP Pool".to_string(),
-            company.esop_pool.total_allocation,
-            esop_shares,
-        ));
-    }
-
-    // Add investors from funding rounds
-    for round in &company.funding_rounds {
-        for investor in &round.investors {
-            let investor_ownership = round.equity_sold / round.investors.len() as f64;
-            let investor_shares = (company.total_shares as f64 * investor_ownership / 100.0) as u64;
-            cap_table.push((investor.clone(), investor_ownership, investor_shares));
+    // Each class in issue order with founders, then employees, then investors
+    // in the order they came in
+    for class in &company.share_classes {
+        for kind in [
+            HolderKind::Founder,
+            HolderKind::Employee,
+            HolderKind::Investor,
+        ] {
+            for holding in ledger
+                .holders_of_kind(kind)
+                .into_iter()
+                .filter(|h| h.share_class == class.name)
+            {
+                cap_table.push(CapTableRow {
+                    holder: holding.holder.clone(),
+                    kind: holding.kind.clone(),
+                    share_class: class.name.clone(),
+                    outstanding_shares: class.outstanding_shares(holding.shares),
+                    as_converted_shares: holding.shares,
+                    outstanding_percentage: outstanding_percentage(holding.shares),
+                    fully_diluted_percentage: ledger.percentage(holding.shares),
+                });
+            }
+        }
+    }
+
+    // Granted options, then whatever is left unallocated in the pool
+    for kind in [HolderKind::OptionHolder, HolderKind::OptionPool] {
+        for holding in ledger.holders_of_kind(kind) {
+            cap_table.push(CapTableRow {
+                holder: holding.holder.clone(),
+                kind: holding.kind.clone(),
+                share_class: holding.share_class.clone(),
+                outstanding_shares: 0,
+                as_converted_shares: holding.shares,
+                outstanding_percentage: Decimal::ZERO,
+                fully_diluted_percentage: ledger.percentage(holding.shares),
+            });
         }
     }
 
     cap_table
 }
 
-pub fn validate_funding_round(round: &FundingRound) -> Result<(), String> {
-    if round.amount <= 0.0 {
-        return Err("Investment amount must be positive".to_string());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{company, date, round};
+    use crate::models::SafeTerms;
+
+    fn simulate(company: &mut Company, mut round: FundingRound) -> OwnershipSnapshot {
+        let pre_round_shares = company.fully_diluted_shares_on(round.close_date).unwrap();
+        resolve_round_terms(&mut round, pre_round_shares).unwrap();
+        simulate_funding_round(company, &round).unwrap()
+    }
+
+    #[test]
+    fn backdated_round_returns_its_own_snapshot() {
+        let mut company = company();
+        simulate(
+            &mut company,
+            round(
+                "A",
+                date(2022, 1, 1),
+                dec!(5_000_000),
+                dec!(20_000_000),
+                DealType::Equity,
+            ),
+        );
+        let seed = simulate(
+            &mut company,
+            round(
+                "Seed",
+                date(2021, 1, 1),
+                dec!(1_000_000),
+                dec!(4_000_000),
+                DealType::Equity,
+            ),
+        );
+
+        assert_eq!(seed.round_name, "Seed");
+        assert_eq!(seed.pre_round_shares, 10_000_000);
+        assert_eq!(seed.price_per_share, dec!(0.4));
+        assert_eq!(company.round_history[1].round_name, "A");
     }
 
-    if round.valuation <= 0.0 {
-        return Err("Pre-money valuation must be positive".to_string());
+    #[test]
+    fn backdated_round_is_checked_against_its_close_date() {
+        let mut company = company();
+        simulate(
+            &mut company,
+            round(
+                "A",
+                date(2022, 1, 1),
+                dec!(5_000_000),
+                dec!(20_000_000),
+                DealType::Equity,
+            ),
+        );
+        let seed = |shares| {
+            let mut seed = round(
+                "Seed",
+                date(2021, 1, 1),
+                dec!(1_000_000),
+                dec!(4_000_000),
+                DealType::Equity,
+            );
+            resolve_round_terms(&mut seed, shares).unwrap();
+            seed
+        };
+
+        // Priced over today's 12.5M shares the Seed asks $0.32, but in 2021
+        // there were 10M and its $4M pre-money was $0.40 a share
+        let today = company.ledger.fully_diluted_shares();
+        let error = simulate_funding_round(&mut company.clone(), &seed(today)).unwrap_err();
+        assert!(error.starts_with("Price per share is $0.3200"), "{}", error);
+        let snapshot = simulate_funding_round(&mut company, &seed(10_000_000)).unwrap();
+        assert_eq!(snapshot.price_per_share, dec!(0.4));
     }
 
-    if round.equity_sold <= 0.0 || round.equity_sold > 100.0 {
-        return Err("Equity sold must be between 0% and 100%".to_string());
+    #[test]
+    fn rounds_need_a_positive_price() {
+        let mut company = company();
+        let mut free = round(
+            "Seed",
+            date(2021, 1, 1),
+            dec!(1_000_000),
+            Decimal::ZERO,
+            DealType::Equity,
+        );
+        assert_eq!(
+            resolve_round_terms(&mut free, 10_000_000),
+            Err(ValidationError::NonPositiveValuation)
+        );
+        free.input = RoundInput::AmountAndPrice;
+        assert_eq!(
+            resolve_round_terms(&mut free, 10_000_000),
+            Err(ValidationError::NonPositivePrice)
+        );
+
+        // Straight onto the log, past the editor and its checks
+        let error = company.add_round(free).unwrap_err();
+        assert_eq!(error, "Seed: Price per share must be positive");
+        assert!(company.funding_rounds.is_empty());
     }
 
-    if round.esop_allocation < 0.0 || round.esop_allocation > 100.0 {
-        return Err("ESOP allocation must be between 0% and 100%".to_string());
+    #[test]
+    fn safe_without_a_pre_money_is_priced_by_its_cap() {
+        let terms = SafeTerms {
+            valuation_cap: dec!(5_000_000),
+            discount_rate: Decimal::ZERO,
+            mfn: false,
+        };
+        let mut safe = round(
+            "Pre-Seed",
+            date(2021, 1, 1),
+            dec!(1_000_000),
+            Decimal::ZERO,
+            DealType::SAFE(terms),
+        );
+        resolve_round_terms(&mut safe, 10_000_000).unwrap();
+        assert_eq!(safe.valuation, dec!(4_000_000));
+        assert_eq!(safe.equity_sold, dec!(20));
+
+        let mut company = company();
+        company.add_round(safe).unwrap();
+        assert_eq!(company.safes[0].investment, dec!(1_000_000));
     }
 
-    if round.investors.is_empty() {
-        return Err("At least one investor must be specified".to_string());
+    #[test]
+    fn pool_top_up_solves_for_the_post_money_target() {
+        // 10% post-money on $8M pre and $2M new money: k = 0.125, so
+        // P = 0.125 * 10M / 0.875
+        let top_up = calculate_pool_top_up(
+            dec!(10),
+            dec!(8_000_000),
+            dec!(2_000_000),
+            Decimal::ZERO,
+            dec!(10_000_000),
+        );
+        assert_eq!(top_up.round_dp(2), dec!(1_428_571.43));
     }
 
-    Ok(())
+    #[test]
+    fn pool_shuffle_lands_on_its_target_and_dilutes_only_the_founders() {
+        let mut series_a = round(
+            "Series A",
+            date(2021, 1, 1),
+            dec!(2_000_000),
+            dec!(8_000_000),
+            DealType::PreferredStock,
+        );
+        series_a.esop_target = Some(dec!(10));
+        let mut company = company();
+        let snapshot = simulate(&mut company, series_a);
+
+        // The pool comes out of the pre-money, so the price is $0.70 rather
+        // than $0.80 and the investor still buys 20% of the post-money
+        assert_eq!(snapshot.price_per_share.round_dp(4), dec!(0.7));
+        assert_eq!(snapshot.pool_shares_added, 1_428_571);
+        assert_eq!(snapshot.shares_issued.new_money, 2_857_142);
+        assert_eq!(snapshot.post_round_shares, 14_285_713);
+        let pool = percentage_of(snapshot.pool_shares_added, snapshot.post_round_shares);
+        assert!((pool - dec!(10)).abs() < dec!(0.000005), "{}", pool);
+        let investor = percentage_of(snapshot.shares_issued.new_money, snapshot.post_round_shares);
+        assert!((investor - dec!(20)).abs() < dec!(0.00001), "{}", investor);
+
+        // Founders carry both the pool and the new money: 100% to 70%
+        let dilution =
+            calculate_founder_dilution(10_000_000, 10_000_000, snapshot.post_round_shares);
+        assert_eq!(dilution.round_dp(4), dec!(30.0000));
+        assert_eq!(
+            company.founders[0].current_ownership.round_dp(4),
+            dec!(35.0000)
+        );
+    }
+
+    #[test]
+    fn pool_shuffle_keeps_a_price_entered_for_the_round() {
+        let mut series_a = round(
+            "Series A",
+            date(2021, 1, 1),
+            dec!(2_000_000),
+            Decimal::ZERO,
+            DealType::PreferredStock,
+        );
+        series_a.input = RoundInput::AmountAndPrice;
+        series_a.price_per_share = dec!(0.80);
+        series_a.esop_target = Some(dec!(10));
+        let mut company = company();
+        let snapshot = simulate(&mut company, series_a);
+
+        // The investor pays the $0.80 asked, so the pool comes out of a
+        // pre-money that grows with it: 1,388,889 shares and $9,111,111
+        assert_eq!(snapshot.price_per_share, dec!(0.80));
+        assert_eq!(snapshot.shares_issued.new_money, 2_500_000);
+        assert_eq!(snapshot.pool_shares_added, 1_388_889);
+        let pool = percentage_of(snapshot.pool_shares_added, snapshot.post_round_shares);
+        assert!((pool - dec!(10)).abs() < dec!(0.00001), "{}", pool);
+        let issued = &company.funding_rounds[0];
+        assert_eq!(issued.price_per_share, dec!(0.80));
+        assert_eq!(issued.valuation.round_dp(0), dec!(9_111_111));
+    }
 }
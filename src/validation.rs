@@ -0,0 +1,771 @@
+use crate::models::{Company, DealType, FundingRound, HolderKind, ProRataParticipation};
+use crate::money::{dec, Decimal, HUNDRED};
+use crate::utils::{
+    calculate_equity_percentage, calculate_post_money_valuation, format_currency,
+    format_percentage, format_shares,
+};
+use std::fmt;
+
+// A problem with a round's terms or with the company as a whole. Each error
+// knows which input it came from so the UI can show it beside that field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    // Round terms
+    NonPositiveAmount,
+    NonPositiveValuation,
+    NonPositivePostMoney,
+    NonPositivePrice,
+    NoSharesToPrice,
+    EquitySoldOutOfRange,
+    EquitySoldMismatch {
+        equity_sold: Decimal,
+        implied: Decimal,
+        amount: Decimal,
+        valuation: Decimal,
+    },
+    PriceMismatch {
+        price_per_share: Decimal,
+        implied: Decimal,
+        valuation: Decimal,
+        pre_round_shares: u64,
+    },
+    EsopAllocationOutOfRange,
+    EsopTargetOutOfRange,
+    EsopAllocationWithTarget,
+    NoInvestors,
+    UnnamedInvestor,
+    NonPositiveCheck {
+        investor: String,
+    },
+    NegativeProRata {
+        investor: String,
+    },
+    ChecksDoNotMatchAmount {
+        allocated: Decimal,
+        amount: Decimal,
+    },
+    NegativeSafeCap,
+    SafeDiscountOutOfRange,
+    SafeWithoutTerms,
+    NegativeNoteInterest,
+    NoteMaturesBeforeIssue,
+    NegativeNoteCap,
+    NoteDiscountOutOfRange,
+    PriceAndOwnershipWithConversions,
+
+    // Company invariants
+    FounderOwnershipNotWhole {
+        total: Decimal,
+    },
+    SharesOutOfSync {
+        ledger: u64,
+        total_shares: u64,
+    },
+    FounderSharesOutOfSync {
+        founder: String,
+        recorded: u64,
+        ledger: u64,
+    },
+    PoolOverAllocated {
+        promised: u64,
+        unallocated: u64,
+    },
+    InRound {
+        index: usize,
+        round: String,
+        error: Box<ValidationError>,
+    },
+}
+
+impl ValidationError {
+    // The input the error belongs to, named the way the forms name their fields.
+    pub fn field(&self) -> &'static str {
+        match self {
+            ValidationError::NonPositiveAmount => "amount",
+            ValidationError::NonPositiveValuation | ValidationError::NonPositivePostMoney => {
+                "valuation"
+            }
+            ValidationError::NonPositivePrice
+            | ValidationError::NoSharesToPrice
+            | ValidationError::PriceMismatch { .. } => "price",
+            ValidationError::EquitySoldOutOfRange
+            | ValidationError::EquitySoldMismatch { .. }
+            | ValidationError::PriceAndOwnershipWithConversions => "ownership",
+            ValidationError::EsopAllocationOutOfRange => "esop_allocation",
+            ValidationError::EsopTargetOutOfRange | ValidationError::EsopAllocationWithTarget => {
+                "esop_target"
+            }
+            ValidationError::NoInvestors
+            | ValidationError::UnnamedInvestor
+            | ValidationError::NonPositiveCheck { .. }
+            | ValidationError::ChecksDoNotMatchAmount { .. } => "investors",
+            ValidationError::NegativeProRata { .. } => "pro_rata",
+            ValidationError::NegativeSafeCap
+            | ValidationError::SafeWithoutTerms
+            | ValidationError::NegativeNoteCap => "valuation_cap",
+            ValidationError::SafeDiscountOutOfRange | ValidationError::NoteDiscountOutOfRange => {
+                "discount_rate"
+            }
+            ValidationError::NegativeNoteInterest => "interest_rate",
+            ValidationError::NoteMaturesBeforeIssue => "maturity_date",
+            ValidationError::FounderOwnershipNotWhole { .. }
+            | ValidationError::FounderSharesOutOfSync { .. } => "founders",
+            ValidationError::SharesOutOfSync { .. } => "shares",
+            ValidationError::PoolOverAllocated { .. } => "esop_pool",
+            ValidationError::InRound { .. } => "rounds",
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::NonPositiveAmount => write!(f, "Investment amount must be positive"),
+            ValidationError::NonPositiveValuation => {
+                write!(f, "Pre-money valuation must be positive")
+            }
+            ValidationError::NonPositivePostMoney => {
+                write!(f, "Post-money valuation must be positive")
+            }
+            ValidationError::NonPositivePrice => write!(f, "Price per share must be positive"),
+            ValidationError::NoSharesToPrice => {
+                write!(f, "There are no shares to price the round against")
+            }
+            ValidationError::EquitySoldOutOfRange => {
+                write!(f, "Equity sold must be between 0% and 100%")
+            }
+            ValidationError::EquitySoldMismatch {
+                equity_sold,
+                implied,
+                amount,
+                valuation,
+            } => write!(
+                f,
+                "Equity sold is {} but {} on a {} pre-money buys {}",
+                format_percentage(*equity_sold),
+                format_currency(*amount),
+                format_currency(*valuation),
+                format_percentage(*implied)
+            ),
+            ValidationError::PriceMismatch {
+                price_per_share,
+                implied,
+                valuation,
+                pre_round_shares,
+            } => write!(
+                f,
+                "Price per share is ${:.4} but a {} pre-money over {} shares is ${:.4}",
+                price_per_share,
+                format_currency(*valuation),
+                format_shares(*pre_round_shares),
+                implied
+            ),
+            ValidationError::EsopAllocationOutOfRange => {
+                write!(f, "ESOP allocation must be between 0% and 100%")
+            }
+            ValidationError::EsopTargetOutOfRange => {
+                write!(f, "Target pool size must be between 0% and 100%")
+            }
+            ValidationError::EsopAllocationWithTarget => write!(
+                f,
+                "Use either a post-money ESOP allocation or a target pool size"
+            ),
+            ValidationError::NoInvestors => write!(f, "At least one investor must be specified"),
+            ValidationError::UnnamedInvestor => write!(f, "Every investor needs a name"),
+            ValidationError::NonPositiveCheck { investor } => {
+                write!(f, "{}'s check must be positive", investor)
+            }
+            ValidationError::NegativeProRata { investor } => {
+                write!(f, "{}'s pro-rata take-up cannot be negative", investor)
+            }
+            ValidationError::ChecksDoNotMatchAmount { allocated, amount } => write!(
+                f,
+                "Investor checks total {} but the round is {}",
+                format_currency(*allocated),
+                format_currency(*amount)
+            ),
+            ValidationError::NegativeSafeCap => write!(f, "SAFE valuation cap cannot be negative"),
+            ValidationError::SafeDiscountOutOfRange => {
+                write!(f, "SAFE discount must be between 0% and 100%")
+            }
+            ValidationError::SafeWithoutTerms => {
+                write!(f, "SAFE needs a valuation cap, a discount or MFN")
+            }
+            ValidationError::NegativeNoteInterest => {
+                write!(f, "Note interest rate cannot be negative")
+            }
+            ValidationError::NoteMaturesBeforeIssue => {
+                write!(f, "Note maturity date must be after the issue date")
+            }
+            ValidationError::NegativeNoteCap => write!(f, "Note valuation cap cannot be negative"),
+            ValidationError::NoteDiscountOutOfRange => {
+                write!(f, "Note discount must be between 0% and 100%")
+            }
+            ValidationError::PriceAndOwnershipWithConversions => write!(
+                f,
+                "SAFEs, notes or a pool top-up convert ahead of this round, so it cannot hold both its price and its ownership; enter the amount instead"
+            ),
+            ValidationError::FounderOwnershipNotWhole { total } => write!(
+                f,
+                "Founder ownership adds up to {} rather than 100%",
+                format_percentage(*total)
+            ),
+            ValidationError::SharesOutOfSync {
+                ledger,
+                total_shares,
+            } => write!(
+                f,
+                "The ledger holds {} shares but the company records {}",
+                format_shares(*ledger),
+                format_shares(*total_shares)
+            ),
+            ValidationError::FounderSharesOutOfSync {
+                founder,
+                recorded,
+                ledger,
+            } => write!(
+                f,
+                "{} is recorded with {} shares but holds {}",
+                founder,
+                format_shares(*recorded),
+                format_shares(*ledger)
+            ),
+            ValidationError::PoolOverAllocated {
+                promised,
+                unallocated,
+            } => write!(
+                f,
+                "Promised grants need {} options but the pool only has {}",
+                format_shares(*promised),
+                format_shares(*unallocated)
+            ),
+            ValidationError::InRound { round, error, .. } => write!(f, "{}: {}", round, error),
+        }
+    }
+}
+
+// Every problem with a round's own terms, in the order the form lists them.
+pub fn validate_funding_round(round: &FundingRound) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+
+    if round.amount <= Decimal::ZERO {
+        errors.push(ValidationError::NonPositiveAmount);
+    }
+
+    if round.valuation <= Decimal::ZERO {
+        errors.push(ValidationError::NonPositiveValuation);
+    }
+
+    if round.equity_sold <= Decimal::ZERO || round.equity_sold > HUNDRED {
+        errors.push(ValidationError::EquitySoldOutOfRange);
+    }
+
+    if round.esop_allocation < Decimal::ZERO || round.esop_allocation > HUNDRED {
+        errors.push(ValidationError::EsopAllocationOutOfRange);
+    }
+
+    if let Some(target) = round.esop_target {
+        if target <= Decimal::ZERO || target >= HUNDRED {
+            errors.push(ValidationError::EsopTargetOutOfRange);
+        }
+
+        if round.esop_allocation > Decimal::ZERO {
+            errors.push(ValidationError::EsopAllocationWithTarget);
+        }
+    }
+
+    if round.investors.is_empty() {
+        errors.push(ValidationError::NoInvestors);
+    }
+
+    for allocation in &round.investors {
+        if allocation.investor.trim().is_empty() {
+            errors.push(ValidationError::UnnamedInvestor);
+        } else if allocation.amount <= Decimal::ZERO {
+            errors.push(ValidationError::NonPositiveCheck {
+                investor: allocation.investor.clone(),
+            });
+        }
+    }
+
+    for election in &round.pro_rata {
+        if let ProRataParticipation::Partial { amount } = election.participation {
+            if amount < Decimal::ZERO {
+                errors.push(ValidationError::NegativeProRata {
+                    investor: election.investor.clone(),
+                });
+            }
+        }
+    }
+
+    if !round.investors.is_empty() && (round.allocated_amount() - round.amount).abs() > dec!(0.01) {
+        errors.push(ValidationError::ChecksDoNotMatchAmount {
+            allocated: round.allocated_amount(),
+            amount: round.amount,
+        });
+    }
+
+    if let DealType::SAFE(terms) = &round.deal_type {
+        if terms.valuation_cap < Decimal::ZERO {
+            errors.push(ValidationError::NegativeSafeCap);
+        }
+
+        if terms.discount_rate < Decimal::ZERO || terms.discount_rate >= HUNDRED {
+            errors.push(ValidationError::SafeDiscountOutOfRange);
+        }
+
+        if terms.valuation_cap == Decimal::ZERO
+            && terms.discount_rate == Decimal::ZERO
+            && !terms.mfn
+        {
+            errors.push(ValidationError::SafeWithoutTerms);
+        }
+    }
+
+    if let DealType::ConvertibleNote(terms) = &round.deal_type {
+        if terms.interest_rate < Decimal::ZERO {
+            errors.push(ValidationError::NegativeNoteInterest);
+        }
+
+        if terms.maturity_date <= terms.issue_date {
+            errors.push(ValidationError::NoteMaturesBeforeIssue);
+        }
+
+        if terms.valuation_cap < Decimal::ZERO {
+            errors.push(ValidationError::NegativeNoteCap);
+        }
+
+        if terms.discount_rate < Decimal::ZERO || terms.discount_rate >= HUNDRED {
+            errors.push(ValidationError::NoteDiscountOutOfRange);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// The stored terms must agree with each other: equity sold with amount over
+// post-money, and for priced rounds the price with pre-money over shares.
+pub fn check_round_terms(
+    round: &FundingRound,
+    pre_round_shares: u64,
+) -> Result<(), ValidationError> {
+    let post_money = calculate_post_money_valuation(round.valuation, round.amount);
+    if post_money <= Decimal::ZERO {
+        return Err(ValidationError::NonPositivePostMoney);
+    }
+    let implied_ownership = calculate_equity_percentage(round.amount, post_money);
+    if (implied_ownership - round.equity_sold).abs() > dec!(0.01) {
+        return Err(ValidationError::EquitySoldMismatch {
+            equity_sold: round.equity_sold,
+            implied: implied_ownership,
+            amount: round.amount,
+            valuation: round.valuation,
+        });
+    }
+
+    let priced = matches!(round.deal_type, DealType::Equity | DealType::PreferredStock);
+    if priced && pre_round_shares > 0 {
+        let implied_price = round.valuation / Decimal::from(pre_round_shares);
+        if (implied_price - round.price_per_share).abs() > implied_price * dec!(0.005) {
+            return Err(ValidationError::PriceMismatch {
+                price_per_share: round.price_per_share,
+                implied: implied_price,
+                valuation: round.valuation,
+                pre_round_shares,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// Invariants that span the whole company: the founding split, the ledger
+// against the recorded totals, the pool against what has been promised from
+// it, and every round's terms against the share count it was priced on.
+pub fn validate_company(company: &Company) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+
+    if !company.founders.is_empty() {
+        let total: Decimal = company.founders.iter().map(|f| f.initial_ownership).sum();
+        if (total - HUNDRED).abs() > dec!(0.01) {
+            errors.push(ValidationError::FounderOwnershipNotWhole { total });
+        }
+    }
+
+    let ledger = company.ledger.fully_diluted_shares();
+    if ledger != company.total_shares {
+        errors.push(ValidationError::SharesOutOfSync {
+            ledger,
+            total_shares: company.total_shares,
+        });
+    }
+
+    for founder in &company.founders {
+        let held = company.ledger.shares_of(&founder.name);
+        if held != founder.shares {
+            errors.push(ValidationError::FounderSharesOutOfSync {
+                founder: founder.name.clone(),
+                recorded: founder.shares,
+                ledger: held,
+            });
+        }
+    }
+
+    let promised: u64 = company
+        .esop_pool
+        .grants
+        .iter()
+        .filter(|g| !g.board_approved)
+        .map(|g| g.outstanding_shares())
+        .sum();
+    let unallocated = company.ledger.shares_of_kind(HolderKind::OptionPool);
+    if promised > unallocated {
+        errors.push(ValidationError::PoolOverAllocated {
+            promised,
+            unallocated,
+        });
+    }
+
+    for (index, round) in company.funding_rounds.iter().enumerate() {
+        // The price divides the shares after conversions and any pool top-up
+        let priced_shares = company
+            .round_history
+            .get(index)
+            .map_or(0, |snapshot| snapshot.priced_shares);
+        let round_errors = validate_funding_round(round)
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .chain(check_round_terms(round, priced_shares).err());
+        errors.extend(round_errors.map(|error| ValidationError::InRound {
+            index,
+            round: round.name.clone(),
+            error: Box::new(error),
+        }));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{company, date, round};
+    use crate::models::{
+        InterestType, InvestorAllocation, MaturityBehavior, NoteTerms, OptionGrant,
+        ProRataElection, SafeTerms,
+    };
+    use crate::utils::resolve_round_terms;
+
+    fn seed() -> FundingRound {
+        let mut seed = round(
+            "Seed",
+            date(2021, 1, 1),
+            dec!(1_000_000),
+            dec!(4_000_000),
+            DealType::Equity,
+        );
+        resolve_round_terms(&mut seed, 10_000_000).unwrap();
+        seed
+    }
+
+    fn errors(round: &FundingRound) -> Vec<ValidationError> {
+        validate_funding_round(round).err().unwrap_or_default()
+    }
+
+    #[test]
+    fn resolved_round_passes() {
+        assert_eq!(errors(&seed()), vec![]);
+        assert_eq!(check_round_terms(&seed(), 10_000_000), Ok(()));
+    }
+
+    #[test]
+    fn round_reports_each_bad_term() {
+        let mut bad = seed();
+        bad.amount = Decimal::ZERO;
+        bad.valuation = dec!(-1);
+        bad.equity_sold = dec!(101);
+        bad.esop_allocation = dec!(-5);
+        bad.esop_target = Some(HUNDRED);
+        assert_eq!(
+            errors(&bad),
+            vec![
+                ValidationError::NonPositiveAmount,
+                ValidationError::NonPositiveValuation,
+                ValidationError::EquitySoldOutOfRange,
+                ValidationError::EsopAllocationOutOfRange,
+                ValidationError::EsopTargetOutOfRange,
+                ValidationError::ChecksDoNotMatchAmount {
+                    allocated: dec!(1_000_000),
+                    amount: Decimal::ZERO,
+                },
+            ]
+        );
+
+        let mut both_pools = seed();
+        both_pools.esop_allocation = dec!(10);
+        both_pools.esop_target = Some(dec!(15));
+        assert_eq!(
+            errors(&both_pools),
+            vec![ValidationError::EsopAllocationWithTarget]
+        );
+    }
+
+    #[test]
+    fn round_reports_each_bad_investor() {
+        let mut nobody = seed();
+        nobody.investors.clear();
+        assert_eq!(errors(&nobody), vec![ValidationError::NoInvestors]);
+
+        let mut bad = seed();
+        bad.investors = vec![
+            InvestorAllocation {
+                investor: " ".to_string(),
+                amount: dec!(500_000),
+            },
+            InvestorAllocation {
+                investor: "Angel".to_string(),
+                amount: Decimal::ZERO,
+            },
+        ];
+        bad.pro_rata = vec![ProRataElection {
+            investor: "Lead".to_string(),
+            participation: ProRataParticipation::Partial { amount: dec!(-1) },
+        }];
+        assert_eq!(
+            errors(&bad),
+            vec![
+                ValidationError::UnnamedInvestor,
+                ValidationError::NonPositiveCheck {
+                    investor: "Angel".to_string()
+                },
+                ValidationError::NegativeProRata {
+                    investor: "Lead".to_string()
+                },
+                ValidationError::ChecksDoNotMatchAmount {
+                    allocated: dec!(500_000),
+                    amount: dec!(1_000_000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn round_reports_bad_safe_and_note_terms() {
+        let mut safe = seed();
+        safe.deal_type = DealType::SAFE(SafeTerms {
+            valuation_cap: dec!(-1),
+            discount_rate: HUNDRED,
+            mfn: false,
+        });
+        assert_eq!(
+            errors(&safe),
+            vec![
+                ValidationError::NegativeSafeCap,
+                ValidationError::SafeDiscountOutOfRange,
+            ]
+        );
+        safe.deal_type = DealType::SAFE(SafeTerms {
+            valuation_cap: Decimal::ZERO,
+            discount_rate: Decimal::ZERO,
+            mfn: false,
+        });
+        assert_eq!(errors(&safe), vec![ValidationError::SafeWithoutTerms]);
+
+        let mut note = seed();
+        note.deal_type = DealType::ConvertibleNote(NoteTerms {
+            interest_rate: dec!(-1),
+            interest_type: InterestType::Simple,
+            issue_date: date(2021, 1, 1),
+            maturity_date: date(2020, 1, 1),
+            valuation_cap: dec!(-1),
+            discount_rate: HUNDRED,
+            qualified_financing: Decimal::ZERO,
+            maturity_behavior: MaturityBehavior::Repay,
+        });
+        assert_eq!(
+            errors(&note),
+            vec![
+                ValidationError::NegativeNoteInterest,
+                ValidationError::NoteMaturesBeforeIssue,
+                ValidationError::NegativeNoteCap,
+                ValidationError::NoteDiscountOutOfRange,
+            ]
+        );
+    }
+
+    #[test]
+    fn stored_terms_must_agree() {
+        let mut underwater = seed();
+        underwater.valuation = dec!(-2_000_000);
+        assert_eq!(
+            check_round_terms(&underwater, 10_000_000),
+            Err(ValidationError::NonPositivePostMoney)
+        );
+
+        let mut oversold = seed();
+        oversold.equity_sold = dec!(25);
+        assert_eq!(
+            check_round_terms(&oversold, 10_000_000),
+            Err(ValidationError::EquitySoldMismatch {
+                equity_sold: dec!(25),
+                implied: dec!(20),
+                amount: dec!(1_000_000),
+                valuation: dec!(4_000_000),
+            })
+        );
+
+        let mut overpriced = seed();
+        overpriced.price_per_share = dec!(0.5);
+        assert_eq!(
+            check_round_terms(&overpriced, 10_000_000),
+            Err(ValidationError::PriceMismatch {
+                price_per_share: dec!(0.5),
+                implied: dec!(0.4),
+                valuation: dec!(4_000_000),
+                pre_round_shares: 10_000_000,
+            })
+        );
+
+        // A SAFE sells no shares, so it has no price to disagree with
+        overpriced.deal_type = DealType::SAFE(SafeTerms {
+            valuation_cap: dec!(5_000_000),
+            discount_rate: Decimal::ZERO,
+            mfn: false,
+        });
+        assert_eq!(check_round_terms(&overpriced, 10_000_000), Ok(()));
+
+        assert_eq!(
+            resolve_round_terms(&mut seed(), 0),
+            Err(ValidationError::NoSharesToPrice)
+        );
+    }
+
+    #[test]
+    fn errors_name_the_field_they_belong_to() {
+        let fields = [
+            (ValidationError::NonPositivePostMoney, "valuation"),
+            (ValidationError::NonPositivePrice, "price"),
+            (
+                ValidationError::PriceAndOwnershipWithConversions,
+                "ownership",
+            ),
+            (
+                ValidationError::NegativeProRata {
+                    investor: String::new(),
+                },
+                "pro_rata",
+            ),
+            (ValidationError::SafeWithoutTerms, "valuation_cap"),
+            (ValidationError::NoteMaturesBeforeIssue, "maturity_date"),
+            (
+                ValidationError::PoolOverAllocated {
+                    promised: 1,
+                    unallocated: 0,
+                },
+                "esop_pool",
+            ),
+        ];
+        for (error, field) in fields {
+            assert_eq!(error.field(), field, "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn company_with_a_converted_safe_is_consistent() {
+        let mut company = company();
+        company
+            .add_round(round(
+                "Pre-Seed",
+                date(2021, 1, 1),
+                dec!(1_000_000),
+                Decimal::ZERO,
+                DealType::SAFE(SafeTerms {
+                    valuation_cap: dec!(5_000_000),
+                    discount_rate: Decimal::ZERO,
+                    mfn: false,
+                }),
+            ))
+            .unwrap();
+        let mut series_a = round(
+            "Series A",
+            date(2022, 1, 1),
+            dec!(2_000_000),
+            dec!(20_000_000),
+            DealType::PreferredStock,
+        );
+        series_a.esop_target = Some(dec!(10));
+        company.add_round(series_a).unwrap();
+
+        // The Series A is priced over the shares after the SAFE and the pool,
+        // not the founders' 10M it closed on
+        assert_eq!(validate_company(&company), Ok(()));
+    }
+
+    #[test]
+    fn inconsistent_company_reports_every_invariant() {
+        let mut company = company();
+        company.add_round(seed()).unwrap();
+        company.founders[0].initial_ownership = dec!(40);
+        company.founders[1].shares = 1;
+        company.total_shares += 1;
+        company.esop_pool.grants.push(OptionGrant {
+            grantee: "Engineer".to_string(),
+            shares: 1_000,
+            strike_price: dec!(0.4),
+            grant_date: date(2021, 6, 1),
+            vesting: None,
+            board_approved: false,
+            exercised_shares: 0,
+            forfeited_shares: 0,
+        });
+        company.funding_rounds[0].equity_sold = dec!(50);
+
+        let errors = validate_company(&company).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError::FounderOwnershipNotWhole { total: dec!(90) },
+                ValidationError::SharesOutOfSync {
+                    ledger: 12_500_000,
+                    total_shares: 12_500_001,
+                },
+                ValidationError::FounderSharesOutOfSync {
+                    founder: company.founders[1].name.clone(),
+                    recorded: 1,
+                    ledger: 5_000_000,
+                },
+                ValidationError::PoolOverAllocated {
+                    promised: 1_000,
+                    unallocated: 0,
+                },
+                ValidationError::InRound {
+                    index: 0,
+                    round: "Seed".to_string(),
+                    error: Box::new(ValidationError::EquitySoldMismatch {
+                        equity_sold: dec!(50),
+                        implied: dec!(20),
+                        amount: dec!(1_000_000),
+                        valuation: dec!(4_000_000),
+                    }),
+                },
+            ]
+        );
+        assert_eq!(errors[4].field(), "rounds");
+        assert!(
+            errors[4]
+                .to_string()
+                .starts_with("Seed: Equity sold is 50.0%"),
+            "{}",
+            errors[4]
+        );
+    }
+}
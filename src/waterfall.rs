@@ -0,0 +1,603 @@
+use crate::models::{
+    AntiDilution, Company, HolderKind, HolderPayout, Holding, LiquidationPreference, Participation,
+    Seniority, ShareClass, COMMON_CLASS,
+};
+use crate::money::{dec, reconcile_shares, Decimal};
+use chrono::NaiveDate;
+
+// Distribute an exit across every outstanding holding. Outstanding notes are
+// debt and are repaid with their interest first. Preferred classes are then
+// paid their preferences by seniority, and the residual is shared by common,
+// converted preferred and participating preferred (up to any participation
+// cap). Each preferred class converts to common when that pays its holders more.
+// Outstanding SAFEs take the greater of their purchase amount and their
+// as-converted payout. Outstanding options join common when their strike is
+// below the common price; the strike is paid into the proceeds and netted off
+// the option holder's payout.
+pub fn distribute(
+    company: &Company,
+    exit_value: Decimal,
+    exit_date: NaiveDate,
+) -> Vec<HolderPayout> {
+    let mut note_payouts = repay_notes(company, exit_value, exit_date);
+    let exit_value = exit_value
+        - note_payouts
+            .iter()
+            .map(|p| p.payout_amount)
+            .sum::<Decimal>();
+
+    // Unallocated pool shares are not outstanding and receive nothing. Options
+    // are taken grant by grant so each keeps its own strike.
+    let mut classes = company.share_classes.clone();
+    let mut holdings: Vec<Holding> = company
+        .ledger
+        .holdings
+        .iter()
+        .filter(|h| !matches!(h.kind, HolderKind::OptionPool | HolderKind::OptionHolder))
+        .cloned()
+        .collect();
+    let (safe_classes, safe_holdings) = safe_positions(company);
+    classes.extend(safe_classes);
+    holdings.extend(safe_holdings);
+    let shareholders = holdings.len();
+
+    let options: Vec<(Holding, Decimal)> = company
+        .esop_pool
+        .grants
+        .iter()
+        .filter(|g| g.board_approved && g.outstanding_shares() > 0)
+        .map(|g| {
+            let holding = Holding {
+                holder: g.grantee.clone(),
+                kind: HolderKind::OptionHolder,
+                share_class: COMMON_CLASS.to_string(),
+                shares: g.outstanding_shares(),
+                invested: Decimal::ZERO,
+            };
+            (holding, g.strike_price)
+        })
+        .collect();
+
+    // Start from every option exercising, then drop the ones whose strike is
+    // at or above the resulting common price until the set settles.
+    let mut exercised = vec![true; options.len()];
+    let mut payouts = Vec::new();
+
+    for _ in 0..=options.len() {
+        holdings.truncate(shareholders);
+        let mut proceeds = exit_value;
+        for ((holding, strike), _) in options.iter().zip(&exercised).filter(|(_, e)| **e) {
+            holdings.push(holding.clone());
+            proceeds += strike * Decimal::from(holding.shares);
+        }
+
+        payouts = distribute_shares(&classes, &holdings, proceeds);
+
+        let common_price = common_price(&payouts);
+        let next: Vec<bool> = options
+            .iter()
+            .map(|(_, strike)| *strike < common_price)
+            .collect();
+        if next == exercised {
+            break;
+        }
+        exercised = next;
+    }
+
+    let mut exercised_options = options.iter().zip(&exercised).filter(|(_, e)| **e);
+    for payout in payouts.iter_mut().skip(shareholders) {
+        if let Some(((_, strike), _)) = exercised_options.next() {
+            payout.payout_amount -= strike * Decimal::from(payout.shares);
+        }
+    }
+    note_payouts.extend(payouts);
+    note_payouts
+}
+
+// Each note's principal and interest to the exit date, pro rata to what each
+// is owed if the exit does not cover them all.
+fn repay_notes(company: &Company, exit_value: Decimal, exit_date: NaiveDate) -> Vec<HolderPayout> {
+    let owed: Vec<Decimal> = company
+        .notes
+        .iter()
+        .map(|note| note.accrued_amount(exit_date))
+        .collect();
+    let total_owed: Decimal = owed.iter().sum();
+    let paid_fraction = if total_owed > Decimal::ZERO {
+        (exit_value.max(Decimal::ZERO) / total_owed).min(Decimal::ONE)
+    } else {
+        Decimal::ZERO
+    };
+
+    company
+        .notes
+        .iter()
+        .zip(owed)
+        .map(|(note, amount)| HolderPayout {
+            holder: note.holder.clone(),
+            kind: HolderKind::Investor,
+            share_class: format!("{} (note)", note.round_name),
+            shares: 0,
+            preference_amount: amount * paid_fraction,
+            participation_amount: Decimal::ZERO,
+            payout_amount: amount * paid_fraction,
+            converted_to_common: false,
+        })
+        .collect()
+}
+
+// Outstanding SAFEs as holdings in a class per round. Taking the greater of
+// the purchase amount and the as-converted payout is a 1x non-participating
+// preference over the shares the SAFE converts into at its cap. A post-money
+// cap is measured against the capitalization including every capped SAFE's
+// conversion shares but not the unissued pool; an uncapped SAFE converts at
+// the exit price, which only ever returns its purchase amount, so it takes
+// that. SAFEs rank with the most junior preferred.
+fn safe_positions(company: &Company) -> (Vec<ShareClass>, Vec<Holding>) {
+    let capped = |investment: Decimal, cap: Decimal| {
+        if cap > Decimal::ZERO {
+            investment / cap
+        } else {
+            Decimal::ZERO
+        }
+    };
+    let outstanding = company.ledger.fully_diluted_shares()
+        - company.ledger.shares_of_kind(HolderKind::OptionPool);
+    let cap_fraction: Decimal = company
+        .safes
+        .iter()
+        .map(|safe| capped(safe.investment, safe.terms.valuation_cap))
+        .sum();
+    // Caps that add up to the whole company leave nothing to convert into
+    let capitalization = if cap_fraction < Decimal::ONE {
+        Decimal::from(outstanding) / (Decimal::ONE - cap_fraction)
+    } else {
+        Decimal::ZERO
+    };
+    let shares = reconcile_shares(
+        &company
+            .safes
+            .iter()
+            .map(|safe| capped(safe.investment, safe.terms.valuation_cap) * capitalization)
+            .collect::<Vec<_>>(),
+    );
+
+    let seniority_rank = company
+        .share_classes
+        .iter()
+        .filter(|c| c.preference.is_some())
+        .map(|c| c.seniority_rank)
+        .min()
+        .unwrap_or(1);
+    let mut classes: Vec<ShareClass> = Vec::new();
+    let mut holdings = Vec::new();
+    for (safe, shares) in company.safes.iter().zip(shares) {
+        let class_name = format!("{} (SAFE)", safe.round_name);
+        if !classes.iter().any(|c| c.name == class_name) {
+            classes.push(ShareClass {
+                name: class_name.clone(),
+                original_issue_price: Decimal::ZERO,
+                conversion_price: Decimal::ZERO,
+                preference: Some(LiquidationPreference {
+                    multiple: Decimal::ONE,
+                    participation: Participation::NonParticipating,
+                    seniority: Seniority::PariPassu,
+                }),
+                seniority_rank,
+                anti_dilution: AntiDilution::None,
+                votes_per_share: Decimal::ZERO,
+            });
+        }
+        holdings.push(Holding {
+            holder: safe.holder.clone(),
+            kind: HolderKind::Investor,
+            share_class: class_name,
+            shares,
+            invested: safe.investment,
+        });
+    }
+    (classes, holdings)
+}
+
+// What a share of common (or converted preferred) received.
+fn common_price(payouts: &[HolderPayout]) -> Decimal {
+    let (amount, count) = payouts
+        .iter()
+        .filter(|p| p.share_class == COMMON_CLASS)
+        .fold((Decimal::ZERO, 0u64), |(amount, count), p| {
+            (amount + p.participation_amount, count + p.shares)
+        });
+    if count == 0 {
+        Decimal::ZERO
+    } else {
+        amount / Decimal::from(count)
+    }
+}
+
+fn distribute_shares(
+    classes: &[ShareClass],
+    holdings: &[Holding],
+    exit_value: Decimal,
+) -> Vec<HolderPayout> {
+    let mut converted = vec![false; classes.len()];
+
+    // Holders of a class share its terms pro rata, so each holder's choice
+    // is the class's choice. Flip one class at a time until nobody gains.
+    for _ in 0..=classes.len() * 2 {
+        let mut changed = false;
+
+        for class_index in 0..classes.len() {
+            if classes[class_index].preference.is_none() {
+                continue;
+            }
+
+            let current = class_payout(
+                &run(classes, holdings, exit_value, &converted),
+                classes,
+                class_index,
+            );
+
+            let mut flipped = converted.clone();
+            flipped[class_index] = !flipped[class_index];
+            let alternative = class_payout(
+                &run(classes, holdings, exit_value, &flipped),
+                classes,
+                class_index,
+            );
+
+            if alternative > current + dec!(0.005) {
+                converted = flipped;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    run(classes, holdings, exit_value, &converted)
+}
+
+fn class_payout(payouts: &[HolderPayout], classes: &[ShareClass], class_index: usize) -> Decimal {
+    let name = &classes[class_index].name;
+    payouts
+        .iter()
+        .filter(|p| &p.share_class == name)
+        .map(|p| p.payout_amount)
+        .sum()
+}
+
+fn run(
+    classes: &[ShareClass],
+    holdings: &[Holding],
+    exit_value: Decimal,
+    converted: &[bool],
+) -> Vec<HolderPayout> {
+    // Only classes carrying a preference are treated apart from common.
+    let class_of: Vec<Option<usize>> = holdings
+        .iter()
+        .map(|h| {
+            classes
+                .iter()
+                .position(|c| c.name == h.share_class && c.preference.is_some())
+        })
+        .collect();
+    let holds_preference =
+        |index: usize| class_of[index].is_some_and(|class_index| !converted[class_index]);
+
+    let mut payouts: Vec<HolderPayout> = holdings
+        .iter()
+        .zip(&class_of)
+        .map(|(h, class_index)| HolderPayout {
+            holder: h.holder.clone(),
+            kind: h.kind.clone(),
+            share_class: h.share_class.clone(),
+            shares: h.shares,
+            preference_amount: Decimal::ZERO,
+            participation_amount: Decimal::ZERO,
+            payout_amount: Decimal::ZERO,
+            converted_to_common: class_index.is_some_and(|i| converted[i]),
+        })
+        .collect();
+
+    let mut remaining = exit_value.max(Decimal::ZERO);
+
+    // Preferences, most senior rank first. A rank that cannot be paid in full
+    // is paid pro rata to what each holder is owed.
+    let mut ranks: Vec<u32> = classes.iter().map(|c| c.seniority_rank).collect();
+    ranks.sort_unstable_by(|a, b| b.cmp(a));
+    ranks.dedup();
+
+    for rank in ranks {
+        let owed: Vec<(usize, Decimal)> = (0..holdings.len())
+            .filter(|&i| holds_preference(i))
+            .filter_map(|i| {
+                let class = &classes[class_of[i]?];
+                let preference = class.preference.as_ref()?;
+                (class.seniority_rank == rank)
+                    .then(|| (i, holdings[i].invested * preference.multiple))
+            })
+            .collect();
+        let total_owed: Decimal = owed.iter().map(|(_, amount)| amount).sum();
+        if total_owed <= Decimal::ZERO {
+            continue;
+        }
+
+        let paid_fraction = (remaining / total_owed).min(Decimal::ONE);
+        for (i, amount) in owed {
+            payouts[i].preference_amount = amount * paid_fraction;
+        }
+        remaining -= total_owed * paid_fraction;
+    }
+
+    // How much more each holder may take from the residual, or None if the
+    // holder does not share in it at all.
+    let mut room: Vec<Option<Decimal>> = (0..holdings.len())
+        .map(|i| match class_of[i] {
+            Some(class_index) if !converted[class_index] => {
+                match classes[class_index]
+                    .preference
+                    .as_ref()
+                    .map(|p| &p.participation)
+                {
+                    Some(Participation::NonParticipating) => None,
+                    Some(Participation::CappedParticipating { cap_multiple }) => Some(
+                        (holdings[i].invested * cap_multiple - payouts[i].preference_amount)
+                            .max(Decimal::ZERO),
+                    ),
+                    Some(Participation::Participating) | None => Some(Decimal::MAX),
+                }
+            }
+            _ => Some(Decimal::MAX),
+        })
+        .collect();
+
+    // Share the residual per share. Holders that hit their participation cap
+    // are fixed at the cap and the rest is shared again among the others.
+    while remaining > Decimal::ZERO {
+        let open: Vec<usize> = (0..holdings.len()).filter(|&i| room[i].is_some()).collect();
+        let open_shares: Decimal = open
+            .iter()
+            .map(|&i| Decimal::from(holdings[i].shares))
+            .sum();
+        if open_shares <= Decimal::ZERO {
+            break;
+        }
+
+        let per_share = remaining / open_shares;
+        let capped: Vec<usize> = open
+            .iter()
+            .copied()
+            .filter(|&i| {
+                per_share * Decimal::from(holdings[i].shares) > room[i].unwrap_or_default()
+            })
+            .collect();
+
+        if capped.is_empty() {
+            for i in open {
+                payouts[i].participation_amount += per_share * Decimal::from(holdings[i].shares);
+            }
+            break;
+        }
+
+        for i in capped {
+            let amount = room[i].take().unwrap_or_default();
+            payouts[i].participation_amount += amount;
+            remaining -= amount;
+        }
+    }
+
+    for payout in &mut payouts {
+        payout.payout_amount = payout.preference_amount + payout.participation_amount;
+    }
+
+    payouts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{company, date, round};
+    use crate::models::{
+        DealType, InterestType, InvestorAllocation, LiquidationPreference, MaturityBehavior,
+        NoteTerms, Participation, SafeTerms, Seniority,
+    };
+
+    // Two founders with 5M shares each and an angel's round after them.
+    fn company_with(deal_type: DealType, amount: Decimal, pre_money: Decimal) -> Company {
+        company_with_preference(deal_type, amount, pre_money, None)
+    }
+
+    fn company_with_preference(
+        deal_type: DealType,
+        amount: Decimal,
+        pre_money: Decimal,
+        liquidation_preference: Option<LiquidationPreference>,
+    ) -> Company {
+        let mut pre_seed = round("Pre-Seed", date(2021, 1, 1), amount, pre_money, deal_type);
+        pre_seed.investors[0].investor = "Angel".to_string();
+        pre_seed.liquidation_preference = liquidation_preference;
+        let mut company = company();
+        company.add_round(pre_seed).unwrap();
+        company
+    }
+
+    fn paid(payouts: &[HolderPayout], holder: &str) -> Decimal {
+        payouts
+            .iter()
+            .filter(|p| p.holder == holder)
+            .map(|p| p.payout_amount)
+            .sum()
+    }
+
+    fn safe(valuation_cap: Decimal) -> Company {
+        let terms = SafeTerms {
+            valuation_cap,
+            discount_rate: Decimal::ZERO,
+            mfn: false,
+        };
+        company_with(DealType::SAFE(terms), dec!(1_000_000), dec!(9_000_000))
+    }
+
+    #[test]
+    fn safe_takes_its_purchase_amount_in_a_small_exit() {
+        let payouts = distribute(&safe(dec!(10_000_000)), dec!(5_000_000), date(2022, 1, 1));
+
+        assert_eq!(paid(&payouts, "Angel"), dec!(1_000_000));
+        assert_eq!(paid(&payouts, "Founder 1"), dec!(2_000_000));
+        assert_eq!(paid(&payouts, "Founder 2"), dec!(2_000_000));
+    }
+
+    #[test]
+    fn safe_converts_at_its_cap_in_a_large_exit() {
+        let payouts = distribute(&safe(dec!(10_000_000)), dec!(100_000_000), date(2022, 1, 1));
+
+        // 10% of the company after conversion, to the share
+        let angel = paid(&payouts, "Angel");
+        assert!((angel - dec!(10_000_000)).abs() < dec!(10), "{}", angel);
+        let total: Decimal = payouts.iter().map(|p| p.payout_amount).sum();
+        assert!((total - dec!(100_000_000)).abs() < dec!(0.000001));
+    }
+
+    #[test]
+    fn uncapped_safe_takes_its_purchase_amount() {
+        let mut company = safe(Decimal::ZERO);
+        company.safes[0].terms.discount_rate = dec!(20);
+        let payouts = distribute(&company, dec!(100_000_000), date(2022, 1, 1));
+
+        assert_eq!(paid(&payouts, "Angel"), dec!(1_000_000));
+        assert_eq!(paid(&payouts, "Founder 1"), dec!(49_500_000));
+    }
+
+    fn note() -> Company {
+        let terms = NoteTerms {
+            interest_rate: dec!(10),
+            interest_type: InterestType::Simple,
+            issue_date: date(2021, 1, 1),
+            maturity_date: date(2025, 1, 1),
+            valuation_cap: dec!(8_000_000),
+            discount_rate: dec!(20),
+            qualified_financing: dec!(1_000_000),
+            maturity_behavior: MaturityBehavior::Repay,
+        };
+        company_with(
+            DealType::ConvertibleNote(terms),
+            dec!(500_000),
+            dec!(8_000_000),
+        )
+    }
+
+    #[test]
+    fn notes_are_repaid_with_interest_ahead_of_equity() {
+        // A year at 10% simple
+        let payouts = distribute(&note(), dec!(5_000_000), date(2022, 1, 1));
+
+        assert_eq!(payouts[0].holder, "Angel");
+        assert_eq!(paid(&payouts, "Angel"), dec!(550_000));
+        assert_eq!(paid(&payouts, "Founder 1"), dec!(2_225_000));
+    }
+
+    #[test]
+    fn notes_share_an_exit_too_small_to_repay_them() {
+        let payouts = distribute(&note(), dec!(275_000), date(2022, 1, 1));
+
+        assert_eq!(paid(&payouts, "Angel"), dec!(275_000));
+        assert_eq!(paid(&payouts, "Founder 1"), Decimal::ZERO);
+    }
+
+    // $1M for a fifth of the company as 1x preferred.
+    fn preferred(participation: Participation) -> Company {
+        let preference = LiquidationPreference {
+            multiple: Decimal::ONE,
+            participation,
+            seniority: Seniority::Senior,
+        };
+        company_with_preference(
+            DealType::PreferredStock,
+            dec!(1_000_000),
+            dec!(4_000_000),
+            Some(preference),
+        )
+    }
+
+    #[test]
+    fn nothing_to_distribute_pays_nothing() {
+        let payouts = distribute(
+            &preferred(Participation::Participating),
+            Decimal::ZERO,
+            date(2022, 1, 1),
+        );
+        assert!(payouts.iter().all(|p| p.payout_amount == Decimal::ZERO));
+    }
+
+    #[test]
+    fn exit_below_the_preference_goes_to_the_preferred() {
+        let payouts = distribute(
+            &preferred(Participation::NonParticipating),
+            dec!(600_000),
+            date(2022, 1, 1),
+        );
+        assert_eq!(paid(&payouts, "Angel"), dec!(600_000));
+        assert_eq!(paid(&payouts, "Founder 1"), Decimal::ZERO);
+    }
+
+    #[test]
+    fn non_participating_preferred_takes_the_better_of_preference_or_conversion() {
+        let company = preferred(Participation::NonParticipating);
+
+        let small = distribute(&company, dec!(3_000_000), date(2022, 1, 1));
+        assert_eq!(paid(&small, "Angel"), dec!(1_000_000));
+        assert_eq!(paid(&small, "Founder 1"), dec!(1_000_000));
+
+        let large = distribute(&company, dec!(20_000_000), date(2022, 1, 1));
+        assert_eq!(paid(&large, "Angel"), dec!(4_000_000));
+        assert_eq!(paid(&large, "Founder 1"), dec!(8_000_000));
+    }
+
+    #[test]
+    fn participating_preferred_shares_what_is_left() {
+        let payouts = distribute(
+            &preferred(Participation::Participating),
+            dec!(6_000_000),
+            date(2022, 1, 1),
+        );
+        assert_eq!(paid(&payouts, "Angel"), dec!(2_000_000));
+        assert_eq!(paid(&payouts, "Founder 1"), dec!(2_000_000));
+    }
+
+    #[test]
+    fn capped_participation_stops_at_the_cap_until_conversion_pays_more() {
+        let company = preferred(Participation::CappedParticipating {
+            cap_multiple: dec!(2),
+        });
+
+        // Participating would pay $2.4M, over the 2x cap; converting pays $1.6M
+        let capped = distribute(&company, dec!(8_000_000), date(2022, 1, 1));
+        assert_eq!(paid(&capped, "Angel"), dec!(2_000_000));
+        assert_eq!(paid(&capped, "Founder 1"), dec!(3_000_000));
+
+        let converted = distribute(&company, dec!(20_000_000), date(2022, 1, 1));
+        assert_eq!(paid(&converted, "Angel"), dec!(4_000_000));
+    }
+
+    #[test]
+    fn senior_preference_is_paid_before_earlier_rounds() {
+        let mut company = preferred(Participation::NonParticipating);
+        let mut series_a = company.funding_rounds[0].clone();
+        series_a.name = "Series A".to_string();
+        series_a.close_date = date(2021, 6, 1);
+        series_a.amount = dec!(2_000_000);
+        series_a.valuation = dec!(8_000_000);
+        series_a.investors = vec![InvestorAllocation {
+            investor: "Fund".to_string(),
+            amount: dec!(2_000_000),
+        }];
+        company.add_round(series_a).unwrap();
+
+        let payouts = distribute(&company, dec!(2_500_000), date(2022, 1, 1));
+        assert_eq!(paid(&payouts, "Fund"), dec!(2_000_000));
+        assert_eq!(paid(&payouts, "Angel"), dec!(500_000));
+        assert_eq!(paid(&payouts, "Founder 1"), Decimal::ZERO);
+    }
+}